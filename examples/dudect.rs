@@ -0,0 +1,39 @@
+use num_bigint::BigUint;
+
+use ine5429_primes::constant_time::dudect;
+use ine5429_primes::functions::find_miller_rabin;
+use ine5429_primes::prime_test::{miller_rabin_tester, miller_rabin_tester_ct};
+use ine5429_primes::rand_gen::Mlcg;
+
+/// Compara, no estilo dudect, o tempo dos testes de Miller-Rabin comum e de tempo constante entre um primo fixo e primos aleatórios de mesmo tamanho.
+/// Execute com `cargo run --release --example dudect`.
+fn main() {
+    let size = 256;
+    let mut seed_gen: Mlcg<BigUint> = Mlcg::new_std(512, &BigUint::from(89898989u32));
+    let primes: Vec<BigUint> = (0..16)
+        .map(|_| find_miller_rabin(size, &seed_gen.rand()))
+        .collect();
+    let fixed = &primes[0];
+    let random = &primes[1..];
+    let seed = BigUint::from(1u32);
+
+    let report = dudect(fixed, random, 20000, &seed, |p| {
+        miller_rabin_tester(p, &seed);
+    });
+    println!(
+        "miller_rabin_tester:    t = {:8.3} ({} samples) {}",
+        report.t_statistic,
+        report.samples.0 + report.samples.1,
+        if report.leaks() { "LEAKS" } else { "ok" }
+    );
+
+    let report = dudect(fixed, random, 20000, &seed, |p| {
+        miller_rabin_tester_ct(p, &seed);
+    });
+    println!(
+        "miller_rabin_tester_ct: t = {:8.3} ({} samples) {}",
+        report.t_statistic,
+        report.samples.0 + report.samples.1,
+        if report.leaks() { "LEAKS" } else { "ok" }
+    );
+}
@@ -0,0 +1,367 @@
+use std::fmt;
+
+use crate::backend::BigInteger;
+
+/// Tag DER de INTEGER
+const TAG_INTEGER: u8 = 0x02;
+/// Tag DER de BIT STRING
+const TAG_BIT_STRING: u8 = 0x03;
+/// Tag DER de OCTET STRING
+const TAG_OCTET_STRING: u8 = 0x04;
+/// Tag DER de NULL
+const TAG_NULL: u8 = 0x05;
+/// Tag DER de OBJECT IDENTIFIER
+const TAG_OBJECT_IDENTIFIER: u8 = 0x06;
+/// Tag DER de SEQUENCE (construída)
+const TAG_SEQUENCE: u8 = 0x30;
+/// Profundidade máxima de SEQUENCEs aninhadas aceita pelo decodificador; as chaves RSA usam no máximo 2
+const MAX_DEPTH: usize = 32;
+
+/// Valor ASN.1 com o subconjunto de tipos necessário para as chaves RSA
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Der {
+    /// INTEGER, guardado como o conteúdo em complemento de dois big-endian
+    Integer(Vec<u8>),
+    /// BIT STRING sem bits não utilizados
+    BitString(Vec<u8>),
+    /// OCTET STRING
+    OctetString(Vec<u8>),
+    /// NULL
+    Null,
+    /// OBJECT IDENTIFIER, como a lista de arcos
+    ObjectIdentifier(Vec<u64>),
+    /// SEQUENCE
+    Sequence(Vec<Der>),
+}
+
+/// Erro na decodificação de uma estrutura DER ou PEM
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// Os dados acabaram antes do fim do elemento
+    UnexpectedEnd,
+    /// Comprimento codificado fora da forma mínima do DER ou grande demais
+    InvalidLength,
+    /// Tag que não faz parte do subconjunto suportado
+    UnsupportedTag(u8),
+    /// Conteúdo inválido para o tipo do elemento
+    InvalidContent(&'static str),
+    /// Sobraram bytes após o elemento
+    TrailingData,
+    /// Estrutura diferente da esperada para a chave
+    UnexpectedStructure(&'static str),
+    /// Armadura PEM ausente ou malformada
+    InvalidPem(&'static str),
+    /// SEQUENCEs aninhadas além de [MAX_DEPTH]
+    TooDeep,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnexpectedEnd => write!(f, "DER data ended in the middle of an element"),
+            DecodeError::InvalidLength => write!(f, "invalid DER length"),
+            DecodeError::UnsupportedTag(tag) => write!(f, "unsupported DER tag 0x{:02x}", tag),
+            DecodeError::InvalidContent(what) => write!(f, "invalid DER content: {}", what),
+            DecodeError::TrailingData => write!(f, "trailing data after DER element"),
+            DecodeError::UnexpectedStructure(what) => write!(f, "unexpected structure: {}", what),
+            DecodeError::InvalidPem(what) => write!(f, "invalid PEM: {}", what),
+            DecodeError::TooDeep => write!(f, "DER nesting deeper than {} levels", MAX_DEPTH),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl Der {
+    /// Constrói um INTEGER a partir de um inteiro não negativo.
+    pub fn integer<T: BigInteger>(value: &T) -> Der {
+        let mut bytes = value.to_bytes_be();
+        if bytes.first().is_none_or(|byte| byte & 0x80 != 0) {
+            bytes.insert(0, 0);
+        }
+        Der::Integer(bytes)
+    }
+
+    /// Retorna o valor de um INTEGER não negativo, ou None para outros tipos e inteiros negativos.
+    pub fn to_integer<T: BigInteger>(&self) -> Option<T> {
+        match self {
+            Der::Integer(bytes) if bytes.first().is_some_and(|byte| byte & 0x80 == 0) => {
+                Some(T::from_bytes_be(bytes))
+            }
+            _ => None,
+        }
+    }
+
+    /// Retorna os elementos de uma SEQUENCE, ou None para outros tipos.
+    pub fn as_sequence(&self) -> Option<&[Der]> {
+        match self {
+            Der::Sequence(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Codifica o valor em DER.
+    pub fn encode(&self) -> Vec<u8> {
+        let (tag, content) = match self {
+            Der::Integer(bytes) => (TAG_INTEGER, bytes.clone()),
+            Der::BitString(bytes) => {
+                let mut content = vec![0];
+                content.extend_from_slice(bytes);
+                (TAG_BIT_STRING, content)
+            }
+            Der::OctetString(bytes) => (TAG_OCTET_STRING, bytes.clone()),
+            Der::Null => (TAG_NULL, Vec::new()),
+            Der::ObjectIdentifier(arcs) => (TAG_OBJECT_IDENTIFIER, encode_oid(arcs)),
+            Der::Sequence(items) => (TAG_SEQUENCE, items.iter().flat_map(Der::encode).collect()),
+        };
+        let mut out = vec![tag];
+        encode_length(content.len(), &mut out);
+        out.extend(content);
+        out
+    }
+
+    /// Decodifica exatamente um valor DER de _bytes_.
+    pub fn decode(bytes: &[u8]) -> Result<Der, DecodeError> {
+        let (value, rest) = decode_element(bytes, 0)?;
+        match rest.is_empty() {
+            true => Ok(value),
+            false => Err(DecodeError::TrailingData),
+        }
+    }
+}
+
+/// Escreve _len_ em _out_ na forma mínima: curta até 127 e longa a partir de 128
+fn encode_length(len: usize, out: &mut Vec<u8>) {
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes: Vec<u8> = len
+            .to_be_bytes()
+            .iter()
+            .copied()
+            .skip_while(|byte| *byte == 0)
+            .collect();
+        out.push(0x80 | bytes.len() as u8);
+        out.extend(bytes);
+    }
+}
+
+/// Codifica os arcos de um OBJECT IDENTIFIER, juntando os dois primeiros e usando base 128 nos demais
+fn encode_oid(arcs: &[u64]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let first = arcs[0] * 40 + arcs[1];
+    for arc in std::iter::once(first).chain(arcs[2..].iter().copied()) {
+        let mut groups = vec![(arc & 0x7f) as u8];
+        let mut rest = arc >> 7;
+        while rest > 0 {
+            groups.push((rest & 0x7f) as u8 | 0x80);
+            rest >>= 7;
+        }
+        out.extend(groups.iter().rev());
+    }
+    out
+}
+
+/// Decodifica o conteúdo de um OBJECT IDENTIFIER
+fn decode_oid(content: &[u8]) -> Result<Vec<u64>, DecodeError> {
+    let mut values = Vec::new();
+    let mut current: u64 = 0;
+    for (i, byte) in content.iter().enumerate() {
+        if current == 0 && *byte == 0x80 {
+            return Err(DecodeError::InvalidContent(
+                "non-minimal object identifier arc",
+            ));
+        }
+        if current >> 57 != 0 {
+            return Err(DecodeError::InvalidContent(
+                "object identifier arc too large",
+            ));
+        }
+        current = (current << 7) | (byte & 0x7f) as u64;
+        if byte & 0x80 == 0 {
+            values.push(current);
+            current = 0;
+        } else if i == content.len() - 1 {
+            return Err(DecodeError::InvalidContent("truncated object identifier"));
+        }
+    }
+    let first = *values
+        .first()
+        .ok_or(DecodeError::InvalidContent("empty object identifier"))?;
+    let mut arcs = match first {
+        0..=39 => vec![0, first],
+        40..=79 => vec![1, first - 40],
+        _ => vec![2, first - 80],
+    };
+    arcs.extend(&values[1..]);
+    Ok(arcs)
+}
+
+/// Lê o comprimento no início de _bytes_, exigindo a forma mínima, e retorna o restante
+fn decode_length(bytes: &[u8]) -> Result<(usize, &[u8]), DecodeError> {
+    let (first, rest) = bytes.split_first().ok_or(DecodeError::UnexpectedEnd)?;
+    if first & 0x80 == 0 {
+        return Ok((*first as usize, rest));
+    }
+    let count = (first & 0x7f) as usize;
+    if count == 0 || count > std::mem::size_of::<usize>() {
+        return Err(DecodeError::InvalidLength);
+    }
+    if rest.len() < count {
+        return Err(DecodeError::UnexpectedEnd);
+    }
+    if rest[0] == 0 {
+        return Err(DecodeError::InvalidLength);
+    }
+    let len = rest[..count]
+        .iter()
+        .fold(0, |len, byte| (len << 8) | *byte as usize);
+    match len < 0x80 {
+        true => Err(DecodeError::InvalidLength),
+        false => Ok((len, &rest[count..])),
+    }
+}
+
+/// Decodifica o primeiro elemento de _bytes_, aninhado em _depth_ SEQUENCEs, e retorna os bytes restantes
+fn decode_element(bytes: &[u8], depth: usize) -> Result<(Der, &[u8]), DecodeError> {
+    if depth > MAX_DEPTH {
+        return Err(DecodeError::TooDeep);
+    }
+    let (tag, rest) = bytes.split_first().ok_or(DecodeError::UnexpectedEnd)?;
+    let (len, rest) = decode_length(rest)?;
+    if rest.len() < len {
+        return Err(DecodeError::UnexpectedEnd);
+    }
+    let (content, rest) = rest.split_at(len);
+    let value = match *tag {
+        TAG_INTEGER => match content {
+            [] => return Err(DecodeError::InvalidContent("empty integer")),
+            [0x00, next, ..] if next & 0x80 == 0 => {
+                return Err(DecodeError::InvalidContent("non-minimal integer"))
+            }
+            [0xff, next, ..] if next & 0x80 != 0 => {
+                return Err(DecodeError::InvalidContent("non-minimal integer"))
+            }
+            _ => Der::Integer(content.to_vec()),
+        },
+        TAG_BIT_STRING => match content.split_first() {
+            Some((0, bits)) => Der::BitString(bits.to_vec()),
+            _ => return Err(DecodeError::InvalidContent("bit string with unused bits")),
+        },
+        TAG_OCTET_STRING => Der::OctetString(content.to_vec()),
+        TAG_NULL => match content.is_empty() {
+            true => Der::Null,
+            false => return Err(DecodeError::InvalidContent("null with content")),
+        },
+        TAG_OBJECT_IDENTIFIER => Der::ObjectIdentifier(decode_oid(content)?),
+        TAG_SEQUENCE => {
+            let mut items = Vec::new();
+            let mut rest = content;
+            while !rest.is_empty() {
+                let (item, next) = decode_element(rest, depth + 1)?;
+                items.push(item);
+                rest = next;
+            }
+            Der::Sequence(items)
+        }
+        other => return Err(DecodeError::UnsupportedTag(other)),
+    };
+    Ok((value, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::BigUint;
+
+    #[test]
+    fn round_trips_every_supported_type() {
+        let value = Der::Sequence(vec![
+            Der::integer(&BigUint::from(0u32)),
+            Der::integer(&BigUint::from(0x80u32)),
+            Der::integer(&(BigUint::from(1u32) << 1000)),
+            Der::BitString(vec![1, 2, 3]),
+            Der::OctetString(vec![0xaa; 200]),
+            Der::Null,
+            Der::ObjectIdentifier(vec![1, 2, 840, 113549, 1, 1, 1]),
+            Der::Sequence(Vec::new()),
+        ]);
+        let bytes = value.encode();
+        assert_eq!(Der::decode(&bytes), Ok(value.clone()));
+        let items = value.as_sequence().unwrap();
+        assert_eq!(items[1].to_integer(), Some(BigUint::from(0x80u32)));
+        assert_eq!(items[2].to_integer(), Some(BigUint::from(1u32) << 1000));
+    }
+
+    #[test]
+    fn matches_known_encodings() {
+        assert_eq!(
+            Der::integer(&BigUint::from(0x80u32)).encode(),
+            [0x02, 0x02, 0x00, 0x80]
+        );
+        assert_eq!(Der::Null.encode(), [0x05, 0x00]);
+        assert_eq!(
+            Der::ObjectIdentifier(vec![1, 2, 840, 113549]).encode(),
+            [0x06, 0x06, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d]
+        );
+        let long = Der::OctetString(vec![0; 300]).encode();
+        assert_eq!(long[..4], [0x04, 0x82, 0x01, 0x2c]);
+    }
+
+    #[test]
+    fn rejects_non_canonical_encodings() {
+        assert_eq!(
+            Der::decode(&[0x02, 0x02, 0x00, 0x01]),
+            Err(DecodeError::InvalidContent("non-minimal integer"))
+        );
+        assert_eq!(
+            Der::decode(&[0x02, 0x00]),
+            Err(DecodeError::InvalidContent("empty integer"))
+        );
+        assert_eq!(
+            Der::decode(&[0x04, 0x81, 0x05, 0, 0, 0, 0, 0]),
+            Err(DecodeError::InvalidLength)
+        );
+        assert_eq!(
+            Der::decode(&[0x04, 0x03, 0]),
+            Err(DecodeError::UnexpectedEnd)
+        );
+        assert_eq!(
+            Der::decode(&[0x05, 0x00, 0x00]),
+            Err(DecodeError::TrailingData)
+        );
+        assert_eq!(
+            Der::decode(&[0x01, 0x01, 0xff]),
+            Err(DecodeError::UnsupportedTag(0x01))
+        );
+    }
+
+    #[test]
+    fn rejects_deep_nesting() {
+        let nested = |depth: usize| {
+            (0..depth)
+                .fold(Der::Null, |inner, _| Der::Sequence(vec![inner]))
+                .encode()
+        };
+        assert!(Der::decode(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(
+            Der::decode(&nested(MAX_DEPTH + 1)),
+            Err(DecodeError::TooDeep)
+        );
+        // Muito além do limite: o erro vem antes de descer por todos os níveis
+        let mut deep = vec![0x05, 0x00];
+        for _ in 0..1_000 {
+            let mut outer = vec![TAG_SEQUENCE];
+            encode_length(deep.len(), &mut outer);
+            outer.extend(deep);
+            deep = outer;
+        }
+        assert_eq!(Der::decode(&deep), Err(DecodeError::TooDeep));
+    }
+
+    #[test]
+    fn empty_or_negative_integers_have_no_value() {
+        assert_eq!(Der::Integer(Vec::new()).to_integer::<BigUint>(), None);
+        assert_eq!(Der::Integer(vec![0xff]).to_integer::<BigUint>(), None);
+    }
+}
@@ -0,0 +1,339 @@
+use std::fmt::{Debug, Display};
+use std::ops::{Add, BitAnd, BitOr, Div, Mul, Rem, Sub};
+use std::str::FromStr;
+
+use num_bigint::BigUint;
+
+use crate::modular::Montgomery;
+
+/// Inteiro de tamanho arbitrário utilizado pelo executável: [BigUint] por padrão ou `rug::Integer` (GMP) com a feature `gmp`.
+#[cfg(not(feature = "gmp"))]
+pub type Int = BigUint;
+
+/// Inteiro de tamanho arbitrário utilizado pelo executável: [BigUint] por padrão ou `rug::Integer` (GMP) com a feature `gmp`.
+#[cfg(feature = "gmp")]
+pub type Int = rug::Integer;
+
+/// Operações sobre inteiros não negativos de tamanho arbitrário necessárias para os geradores, testes de primalidade e buscas. Permite trocar a biblioteca de aritmética sem alterar o restante do código.
+///
+/// As operações aritméticas também aceitam o operando da direita por referência (`a * &b % &n`), o que evita clonar multiplicadores e módulos fixos nos laços. Os limites são sobre o valor (`Self: Mul<&Self>`) e não sobre referências (`&Self: Mul<&Self>`) porque só os primeiros são herdados por quem usa `T: BigInteger`; os outros teriam que ser repetidos em cada função genérica.
+pub trait BigInteger:
+    Clone
+    + Ord
+    + Debug
+    + Display
+    + FromStr
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+    + for<'a> Add<&'a Self, Output = Self>
+    + for<'a> Sub<&'a Self, Output = Self>
+    + for<'a> Mul<&'a Self, Output = Self>
+    + for<'a> Rem<&'a Self, Output = Self>
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+{
+    /// Contexto de aritmética modular reutilizável para um módulo fixo
+    type Context: ModularContext<Self>;
+
+    /// Constrói o valor a partir de um inteiro primitivo.
+    fn from_u64(value: u64) -> Self;
+
+    /// Retorna a quantidade de bits significativos do valor.
+    fn bits(&self) -> u64;
+
+    /// Retorna o bit na posição _index_.
+    fn bit(&self, index: u64) -> bool;
+
+    /// Retorna o valor deslocado _n_ bits para a esquerda.
+    fn shl(&self, n: u64) -> Self;
+
+    /// Retorna o valor deslocado _n_ bits para a direita.
+    fn shr(&self, n: u64) -> Self;
+
+    /// Retorna o valor elevado a _exp_.
+    fn pow(&self, exp: u32) -> Self;
+
+    /// Retorna self^_exp_ mod _modulus_.
+    fn modpow(&self, exp: &Self, modulus: &Self) -> Self;
+
+    /// Retorna os limbs de 64 bits do valor, do menos significativo para o mais significativo.
+    fn to_limbs(&self) -> Vec<u64>;
+
+    /// Constrói o valor a partir de limbs de 64 bits, do menos significativo para o mais significativo.
+    fn from_limbs(limbs: &[u64]) -> Self;
+
+    /// Retorna os bytes do valor em big-endian, sem zeros à esquerda (vazio para zero).
+    fn to_bytes_be(&self) -> Vec<u8> {
+        self.to_limbs()
+            .iter()
+            .rev()
+            .flat_map(|limb| limb.to_be_bytes())
+            .skip_while(|byte| *byte == 0)
+            .collect()
+    }
+
+    /// Retorna os bytes do valor em big-endian com exatamente _len_ bytes, completando com zeros à esquerda, ou None se o valor não couber (I2OSP do PKCS#1).
+    fn to_bytes_be_padded(&self, len: usize) -> Option<Vec<u8>> {
+        let bytes = self.to_bytes_be();
+        if bytes.len() > len {
+            return None;
+        }
+        let mut out = vec![0; len - bytes.len()];
+        out.extend(bytes);
+        Some(out)
+    }
+
+    /// Constrói o valor a partir de bytes em big-endian.
+    fn from_bytes_be(bytes: &[u8]) -> Self {
+        let limbs: Vec<u64> = bytes
+            .rchunks(8)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .fold(0, |limb, byte| (limb << 8) | *byte as u64)
+            })
+            .collect();
+        Self::from_limbs(&limbs)
+    }
+
+    /// Retorna o valor zero.
+    fn zero() -> Self {
+        Self::from_u64(0)
+    }
+
+    /// Retorna o valor um.
+    fn one() -> Self {
+        Self::from_u64(1)
+    }
+
+    /// Checa se o valor é par.
+    fn is_even(&self) -> bool {
+        !self.bit(0)
+    }
+
+    /// Retorna o máximo divisor comum entre o valor e _other_, pelo algoritmo de Euclides.
+    fn gcd(&self, other: &Self) -> Self {
+        let (mut a, mut b) = (self.clone(), other.clone());
+        while b != Self::zero() {
+            let r = a % b.clone();
+            a = b;
+            b = r;
+        }
+        a
+    }
+
+    /// Retorna a raiz quadrada inteira do valor (o maior r com r² ≤ valor), pelo método de Newton.
+    fn isqrt(&self) -> Self {
+        if *self < Self::from_u64(2) {
+            return self.clone();
+        }
+        let mut x = Self::one().shl(self.bits().div_ceil(2));
+        loop {
+            let y = (x.clone() + self.clone() / x.clone()).shr(1);
+            if y >= x {
+                return x;
+            }
+            x = y;
+        }
+    }
+
+    /// Retorna o inverso do valor módulo _modulus_, ou None caso não sejam coprimos. Usa o algoritmo de Euclides estendido mantendo os coeficientes reduzidos módulo _modulus_.
+    fn mod_inverse(&self, modulus: &Self) -> Option<Self> {
+        let (mut a, mut b) = (modulus.clone(), self.clone() % modulus.clone());
+        let (mut x0, mut x1) = (Self::zero(), Self::one());
+        while b != Self::zero() {
+            let q = a.clone() / b.clone();
+            let r = a % b.clone();
+            a = b;
+            b = r;
+            let qx = q * x1.clone() % modulus.clone();
+            let x = (x0 + modulus.clone() - qx) % modulus.clone();
+            x0 = x1;
+            x1 = x;
+        }
+        match a == Self::one() {
+            true => Some(x0 % modulus.clone()),
+            false => None,
+        }
+    }
+}
+
+/// Contexto de aritmética modular para um módulo ímpar fixo, compartilhado por todas as rodadas de um teste sobre o mesmo candidato.
+pub trait ModularContext<T> {
+    /// Representação interna dos valores módulo n
+    type Element;
+
+    /// Constrói um novo contexto para o módulo _modulus_.
+    fn new(modulus: &T) -> Self;
+
+    /// Calcula base^exp mod n na representação interna.
+    fn pow(&mut self, base: &T, exp: &T) -> Self::Element;
+
+    /// Eleva _a_ ao quadrado módulo n, in-place.
+    fn square(&mut self, a: &mut Self::Element);
+
+    /// Checa se _a_ corresponde a 1.
+    fn is_one(&self, a: &Self::Element) -> bool;
+
+    /// Checa se _a_ corresponde a n-1.
+    fn is_minus_one(&self, a: &Self::Element) -> bool;
+}
+
+impl BigInteger for BigUint {
+    type Context = Montgomery;
+
+    fn from_u64(value: u64) -> Self {
+        BigUint::from(value)
+    }
+
+    fn bits(&self) -> u64 {
+        BigUint::bits(self)
+    }
+
+    fn bit(&self, index: u64) -> bool {
+        BigUint::bit(self, index)
+    }
+
+    fn shl(&self, n: u64) -> Self {
+        self << n
+    }
+
+    fn shr(&self, n: u64) -> Self {
+        self >> n
+    }
+
+    fn pow(&self, exp: u32) -> Self {
+        BigUint::pow(self, exp)
+    }
+
+    fn modpow(&self, exp: &Self, modulus: &Self) -> Self {
+        BigUint::modpow(self, exp, modulus)
+    }
+
+    fn to_limbs(&self) -> Vec<u64> {
+        self.to_u64_digits()
+    }
+
+    fn from_limbs(limbs: &[u64]) -> Self {
+        let digits: Vec<u32> = limbs
+            .iter()
+            .flat_map(|limb| vec![*limb as u32, (*limb >> 32) as u32])
+            .collect();
+        BigUint::new(digits)
+    }
+}
+
+impl ModularContext<BigUint> for Montgomery {
+    type Element = Vec<u64>;
+
+    fn new(modulus: &BigUint) -> Self {
+        Montgomery::new(modulus)
+    }
+
+    fn pow(&mut self, base: &BigUint, exp: &BigUint) -> Vec<u64> {
+        self.pow_mont(base, exp)
+    }
+
+    fn square(&mut self, a: &mut Vec<u64>) {
+        Montgomery::square(self, a)
+    }
+
+    fn is_one(&self, a: &Vec<u64>) -> bool {
+        Montgomery::is_one(self, a)
+    }
+
+    fn is_minus_one(&self, a: &Vec<u64>) -> bool {
+        Montgomery::is_minus_one(self, a)
+    }
+}
+
+#[cfg(feature = "gmp")]
+pub use self::gmp::GmpContext;
+
+#[cfg(feature = "gmp")]
+mod gmp {
+    use rug::integer::Order;
+    use rug::ops::Pow;
+    use rug::Integer;
+
+    use super::{BigInteger, ModularContext};
+
+    impl BigInteger for Integer {
+        type Context = GmpContext;
+
+        fn from_u64(value: u64) -> Self {
+            Integer::from(value)
+        }
+
+        fn bits(&self) -> u64 {
+            self.significant_bits() as u64
+        }
+
+        fn bit(&self, index: u64) -> bool {
+            self.get_bit(index as u32)
+        }
+
+        fn shl(&self, n: u64) -> Self {
+            Integer::from(self << n as u32)
+        }
+
+        fn shr(&self, n: u64) -> Self {
+            Integer::from(self >> n as u32)
+        }
+
+        fn pow(&self, exp: u32) -> Self {
+            Integer::from(Pow::pow(self, exp))
+        }
+
+        fn modpow(&self, exp: &Self, modulus: &Self) -> Self {
+            self.clone().pow_mod(exp, modulus).unwrap()
+        }
+
+        fn to_limbs(&self) -> Vec<u64> {
+            self.to_digits::<u64>(Order::Lsf)
+        }
+
+        fn from_limbs(limbs: &[u64]) -> Self {
+            Integer::from_digits(limbs, Order::Lsf)
+        }
+    }
+
+    /// Contexto modular sobre GMP, que já realiza internamente a redução de Montgomery nas exponenciações
+    pub struct GmpContext {
+        /// Módulo do contexto
+        modulus: Integer,
+        /// n-1, para comparação direta
+        minus_one: Integer,
+    }
+
+    impl ModularContext<Integer> for GmpContext {
+        type Element = Integer;
+
+        fn new(modulus: &Integer) -> Self {
+            GmpContext {
+                modulus: modulus.clone(),
+                minus_one: Integer::from(modulus - 1u32),
+            }
+        }
+
+        fn pow(&mut self, base: &Integer, exp: &Integer) -> Integer {
+            base.clone().pow_mod(exp, &self.modulus).unwrap()
+        }
+
+        fn square(&mut self, a: &mut Integer) {
+            a.square_mut();
+            *a %= &self.modulus;
+        }
+
+        fn is_one(&self, a: &Integer) -> bool {
+            *a == 1u32
+        }
+
+        fn is_minus_one(&self, a: &Integer) -> bool {
+            *a == self.minus_one
+        }
+    }
+}
@@ -0,0 +1,807 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::time::Instant;
+
+use log::info;
+
+use crate::backend::BigInteger;
+use crate::ecpp::{Ecpp, EcppProof};
+use crate::functions::{small_primes, small_rem, Tester};
+use crate::provable::{is_prime_u64, ProvablePrime};
+
+/// Maior valor aceito pelo verificador em passos de divisão por tentativa
+const TRIAL_DIVISION_LIMIT: u64 = 1 << 32;
+
+/// Limite dos primos usados na fatoração de n-1 por divisão por tentativa; primos menores que ele são certificados diretamente por divisão por tentativa
+const FACTOR_BOUND: u64 = 1 << 16;
+
+/// Quantidade máxima de bases tentadas na busca por uma testemunha
+const MAX_WITNESS: u64 = 10_000;
+
+/// Fator primo conhecido de n-1 em um passo de Pocklington, com a testemunha que o acompanha
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PocklingtonFactor<T> {
+    /// Fator primo q de n-1, provado em um passo anterior
+    pub prime: T,
+    /// Expoente de q na parte fatorada de n-1
+    pub exponent: u32,
+    /// Base a com a^(n-1) ≡ 1 e mdc(a^((n-1)/q) - 1, n) = 1
+    pub witness: T,
+}
+
+/// Passo de um certificado de primalidade, que prova um primo a partir dos provados nos passos anteriores
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CertificateStep<T> {
+    /// Primo menor que 2^32, provado por divisão por tentativa
+    TrialDivision(T),
+    /// Certificado de Pratt: n-1 completamente fatorado e uma testemunha de ordem n-1
+    Pratt {
+        prime: T,
+        witness: T,
+        factors: Vec<(T, u32)>,
+    },
+    /// Certificado de Pocklington: parte fatorada F de n-1 com F² > n, ou F³ ≥ n com o critério de Brillhart-Lehmer-Selfridge, e uma testemunha por fator
+    Pocklington {
+        prime: T,
+        factors: Vec<PocklingtonFactor<T>>,
+    },
+    /// Certificado de ECPP: curva elíptica módulo n com um ponto de ordem prima q > (n^(1/4) + 1)²
+    Ecpp(EcppProof<T>),
+}
+
+/// Certificado de primalidade: sequência de passos em que cada fator usado já foi provado antes e o último passo prova o primo certificado
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Certificate<T> {
+    /// Passos do certificado, do menor para o maior primo
+    pub steps: Vec<CertificateStep<T>>,
+}
+
+/// Motivo da rejeição de um certificado; _step_ é o número do passo, a partir de 1
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CertificateError {
+    /// O certificado não tem passos
+    Empty,
+    /// O passo usa um fator que não foi provado em um passo anterior
+    UnprovenFactor { step: usize },
+    /// Os fatores não correspondem a n-1 (Pratt) ou não dividem n-1 (Pocklington)
+    WrongFactorization { step: usize },
+    /// Alguma testemunha não satisfaz as congruências exigidas
+    InvalidWitness { step: usize },
+    /// A parte fatorada de n-1 é pequena demais para provar n
+    InsufficientFactorization { step: usize },
+    /// O número não é primo pelo critério do passo
+    NotPrime { step: usize },
+    /// n não é coprimo com 6, a curva é singular ou o ponto não está nela
+    InvalidCurve { step: usize },
+    /// A ordem do ponto não passa de (n^(1/4) + 1)²
+    SmallOrder { step: usize },
+}
+
+impl fmt::Display for CertificateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CertificateError::Empty => write!(f, "the certificate has no steps"),
+            CertificateError::UnprovenFactor { step } => {
+                write!(f, "step {}: factor not proven by an earlier step", step)
+            }
+            CertificateError::WrongFactorization { step } => {
+                write!(f, "step {}: factors do not match n-1", step)
+            }
+            CertificateError::InvalidWitness { step } => {
+                write!(f, "step {}: witness does not satisfy the congruences", step)
+            }
+            CertificateError::InsufficientFactorization { step } => {
+                write!(f, "step {}: factored part of n-1 is too small", step)
+            }
+            CertificateError::NotPrime { step } => {
+                write!(
+                    f,
+                    "step {}: the number is not prime by this criterion",
+                    step
+                )
+            }
+            CertificateError::InvalidCurve { step } => {
+                write!(f, "step {}: invalid curve or point not on the curve", step)
+            }
+            CertificateError::SmallOrder { step } => {
+                write!(f, "step {}: order of the point is too small", step)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CertificateError {}
+
+impl<T: BigInteger> CertificateStep<T> {
+    /// Retorna o primo provado pelo passo.
+    pub fn prime(&self) -> &T {
+        match self {
+            CertificateStep::TrialDivision(prime) => prime,
+            CertificateStep::Pratt { prime, .. } => prime,
+            CertificateStep::Pocklington { prime, .. } => prime,
+            CertificateStep::Ecpp(proof) => &proof.prime,
+        }
+    }
+}
+
+impl<T: BigInteger> Certificate<T> {
+    /// Retorna o primo certificado, ou None para um certificado vazio.
+    pub fn prime(&self) -> Option<&T> {
+        self.steps.last().map(CertificateStep::prime)
+    }
+
+    /// Verifica o certificado usando apenas aritmética modular: cada passo é checado supondo primos somente os fatores provados nos passos anteriores.
+    pub fn verify(&self) -> Result<(), CertificateError> {
+        if self.steps.is_empty() {
+            return Err(CertificateError::Empty);
+        }
+        let mut proven = BTreeSet::new();
+        for (i, step) in self.steps.iter().enumerate() {
+            verify_step(step, &proven, i + 1)?;
+            proven.insert(step.prime().clone());
+        }
+        Ok(())
+    }
+
+    /// Lê um certificado no formato escrito por [fmt::Display]: um passo por linha, `trial N`, `pratt N witness A factors Q^E ...`, `pocklington N factors Q^E:A ...` ou `ecpp N a A b B order Q point X Y`. Linhas iniciadas por `#` são ignoradas.
+    pub fn parse(text: &str) -> Option<Self> {
+        let steps = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(parse_step)
+            .collect::<Option<Vec<_>>>()?;
+        match steps.is_empty() {
+            true => None,
+            false => Some(Certificate { steps }),
+        }
+    }
+}
+
+/// Lê vários certificados separados por linhas em branco, como escritos pelo `primetool -p --provable`.
+pub fn parse_certificates<T: BigInteger>(text: &str) -> Option<Vec<Certificate<T>>> {
+    let mut blocks = vec![String::new()];
+    for line in text.lines() {
+        match line.trim().is_empty() {
+            true => blocks.push(String::new()),
+            false => {
+                let block = blocks.last_mut().unwrap();
+                block.push_str(line);
+                block.push('\n');
+            }
+        }
+    }
+    blocks
+        .iter()
+        .filter(|block| block.lines().any(|line| !line.trim().starts_with('#')))
+        .map(|block| Certificate::parse(block))
+        .collect()
+}
+
+impl<T: BigInteger> fmt::Display for CertificateStep<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CertificateStep::TrialDivision(prime) => write!(f, "trial {}", prime),
+            CertificateStep::Pratt {
+                prime,
+                witness,
+                factors,
+            } => {
+                write!(f, "pratt {} witness {} factors", prime, witness)?;
+                for (q, e) in factors {
+                    write!(f, " {}^{}", q, e)?;
+                }
+                Ok(())
+            }
+            CertificateStep::Pocklington { prime, factors } => {
+                write!(f, "pocklington {} factors", prime)?;
+                for factor in factors {
+                    write!(
+                        f,
+                        " {}^{}:{}",
+                        factor.prime, factor.exponent, factor.witness
+                    )?;
+                }
+                Ok(())
+            }
+            CertificateStep::Ecpp(proof) => write!(
+                f,
+                "ecpp {} a {} b {} order {} point {} {}",
+                proof.prime, proof.a, proof.b, proof.order, proof.x, proof.y
+            ),
+        }
+    }
+}
+
+impl<T: BigInteger> fmt::Display for Certificate<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines: Vec<String> = self.steps.iter().map(ToString::to_string).collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl<T: BigInteger> From<ProvablePrime<T>> for Certificate<T> {
+    /// Converte a cadeia do Shawe-Taylor em certificado: a base por divisão por tentativa e cada passo como Pocklington com um único fator.
+    fn from(proven: ProvablePrime<T>) -> Self {
+        let mut steps = vec![CertificateStep::TrialDivision(T::from_u64(proven.base))];
+        steps.extend(
+            proven
+                .steps
+                .into_iter()
+                .map(|step| CertificateStep::Pocklington {
+                    prime: step.prime,
+                    factors: vec![PocklingtonFactor {
+                        prime: step.factor,
+                        exponent: 1,
+                        witness: step.witness,
+                    }],
+                }),
+        );
+        Certificate { steps }
+    }
+}
+
+/// Constrói um certificado para _n_. n-1 é fatorado por divisão por tentativa até 2^16: se o cofator restante passar em _tester_ ele é certificado recursivamente e o passo é de Pratt, senão o passo é de Pocklington com a parte fatorada. Quando n-1 não está fatorado o bastante o passo é de ECPP, com um primo provável menor certificado recursivamente. Retorna None se _n_ for composto.
+pub fn certify<T: BigInteger>(n: &T, tester: Tester, seed: &T) -> Option<Certificate<T>> {
+    let now = Instant::now();
+    let mut certifier = Certifier {
+        primes: small_primes(FACTOR_BOUND),
+        tester,
+        seed: seed.clone(),
+        ecpp: Ecpp::new(),
+        steps: Vec::new(),
+    };
+    certifier.certify(n)?;
+    info!(
+        "Certificate with {} steps built in {:.4}s",
+        certifier.steps.len(),
+        now.elapsed().as_secs_f32()
+    );
+    Some(Certificate {
+        steps: certifier.steps,
+    })
+}
+
+/// Estado da construção de um certificado, compartilhado entre as chamadas recursivas
+struct Certifier<T> {
+    /// Primos usados na fatoração de n-1
+    primes: Vec<u64>,
+    /// Teste usado para escolher os fatores prováveis primos
+    tester: Tester,
+    /// Seed das testemunhas do teste
+    seed: T,
+    /// Contexto do ECPP
+    ecpp: Ecpp,
+    /// Passos já construídos
+    steps: Vec<CertificateStep<T>>,
+}
+
+impl<T: BigInteger> Certifier<T> {
+    /// Acrescenta os passos que provam _n_, se ele ainda não estiver provado. Em caso de falha os passos acrescentados são descartados.
+    fn certify(&mut self, n: &T) -> Option<()> {
+        if self.steps.iter().any(|step| step.prime() == n) {
+            return Some(());
+        }
+        if *n < T::from_u64(FACTOR_BOUND) {
+            return match is_prime_u64(small_rem(n, FACTOR_BOUND)) {
+                true => {
+                    self.steps.push(CertificateStep::TrialDivision(n.clone()));
+                    Some(())
+                }
+                false => None,
+            };
+        }
+        if !self.tester.test(n, &self.seed) {
+            return None;
+        }
+        let mark = self.steps.len();
+        if self.certify_n_minus_1(n).is_some() {
+            return Some(());
+        }
+        self.steps.truncate(mark);
+
+        let mut search = self.ecpp.search(n);
+        while let Some(candidate) = self
+            .ecpp
+            .next_candidate(&mut search, self.tester, &self.seed)
+        {
+            let proof = match self.ecpp.prove(n, &candidate) {
+                Some(proof) => proof,
+                None => continue,
+            };
+            if self.certify(&proof.order).is_some()
+                && self.push_checked(CertificateStep::Ecpp(proof)).is_some()
+            {
+                return Some(());
+            }
+            self.steps.truncate(mark);
+        }
+        None
+    }
+
+    /// Tenta provar _n_ a partir da fatoração de n-1, com um passo de Pratt ou de Pocklington
+    fn certify_n_minus_1(&mut self, n: &T) -> Option<()> {
+        let n1 = n.clone() - T::one();
+        let mut cofactor = n1.clone();
+        let mut factors = Vec::new();
+        for &q in &self.primes {
+            if cofactor == T::one() {
+                break;
+            }
+            let mut exponent = 0;
+            while small_rem(&cofactor, q) == 0 {
+                cofactor = cofactor / T::from_u64(q);
+                exponent += 1;
+            }
+            if exponent > 0 {
+                factors.push((T::from_u64(q), exponent));
+            }
+        }
+        if cofactor > T::one() && self.tester.test(&cofactor, &self.seed) {
+            factors.push((cofactor, 1));
+            cofactor = T::one();
+        }
+        for (q, _) in &factors {
+            self.certify(q)?;
+        }
+
+        let step = match cofactor == T::one() {
+            true => {
+                let witness = (2..MAX_WITNESS).map(T::from_u64).find(|a| {
+                    a.modpow(&n1, n) == T::one()
+                        && factors
+                            .iter()
+                            .all(|(q, _)| a.modpow(&(n1.clone() / q.clone()), n) != T::one())
+                })?;
+                CertificateStep::Pratt {
+                    prime: n.clone(),
+                    witness,
+                    factors,
+                }
+            }
+            false => {
+                let factors = factors
+                    .into_iter()
+                    .map(|(q, exponent)| {
+                        let witness = (2..MAX_WITNESS)
+                            .map(T::from_u64)
+                            .find(|a| pocklington_witness(n, &q, a))?;
+                        Some(PocklingtonFactor {
+                            prime: q,
+                            exponent,
+                            witness,
+                        })
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                CertificateStep::Pocklington {
+                    prime: n.clone(),
+                    factors,
+                }
+            }
+        };
+        self.push_checked(step)
+    }
+
+    /// Acrescenta _step_ depois de verificá-lo, descartando compostos que passaram pelo teste, como os rejeitados pelo critério BLS
+    fn push_checked(&mut self, step: CertificateStep<T>) -> Option<()> {
+        let proven = self.steps.iter().map(|s| s.prime().clone()).collect();
+        verify_step(&step, &proven, 0).ok()?;
+        self.steps.push(step);
+        Some(())
+    }
+}
+
+/// Checa um passo supondo primos os valores de _proven_
+fn verify_step<T: BigInteger>(
+    step: &CertificateStep<T>,
+    proven: &BTreeSet<T>,
+    index: usize,
+) -> Result<(), CertificateError> {
+    match step {
+        CertificateStep::TrialDivision(n) => {
+            match *n < T::from_u64(TRIAL_DIVISION_LIMIT)
+                && is_prime_u64(small_rem(n, TRIAL_DIVISION_LIMIT))
+            {
+                true => Ok(()),
+                false => Err(CertificateError::NotPrime { step: index }),
+            }
+        }
+        CertificateStep::Pratt {
+            prime: n,
+            witness,
+            factors,
+        } => {
+            if *n < T::from_u64(3) {
+                return Err(CertificateError::NotPrime { step: index });
+            }
+            if factors.iter().any(|(q, _)| !proven.contains(q)) {
+                return Err(CertificateError::UnprovenFactor { step: index });
+            }
+            let n1 = n.clone() - T::one();
+            let product = factors.iter().fold(T::one(), |acc, (q, e)| acc * q.pow(*e));
+            if product != n1 || factors.iter().any(|(_, e)| *e == 0) {
+                return Err(CertificateError::WrongFactorization { step: index });
+            }
+            match witness.modpow(&n1, n) == T::one()
+                && factors
+                    .iter()
+                    .all(|(q, _)| witness.modpow(&(n1.clone() / q.clone()), n) != T::one())
+            {
+                true => Ok(()),
+                false => Err(CertificateError::InvalidWitness { step: index }),
+            }
+        }
+        CertificateStep::Pocklington { prime: n, factors } => {
+            if *n < T::from_u64(3) {
+                return Err(CertificateError::NotPrime { step: index });
+            }
+            if factors.iter().any(|factor| !proven.contains(&factor.prime)) {
+                return Err(CertificateError::UnprovenFactor { step: index });
+            }
+            let n1 = n.clone() - T::one();
+            let f = factors.iter().fold(T::one(), |acc, factor| {
+                acc * factor.prime.pow(factor.exponent)
+            });
+            if n1.clone() % f.clone() != T::zero() || factors.iter().any(|q| q.exponent == 0) {
+                return Err(CertificateError::WrongFactorization { step: index });
+            }
+            if !factors
+                .iter()
+                .all(|factor| pocklington_witness(n, &factor.prime, &factor.witness))
+            {
+                return Err(CertificateError::InvalidWitness { step: index });
+            }
+            // Todo fator primo de n é ≡ 1 (mod F): basta F+1 > √n, ou F ≥ ∛n e o critério BLS
+            let f1 = f.clone() + T::one();
+            if f1.clone() * f1 > *n {
+                return Ok(());
+            }
+            if f.clone() * f.clone() * f.clone() < *n {
+                return Err(CertificateError::InsufficientFactorization { step: index });
+            }
+            // n = c2·F² + c1·F + 1; n é primo se e somente se c1² - 4·c2 não é quadrado perfeito
+            let r = n1 / f.clone();
+            let (c2, c1) = (r.clone() / f.clone(), r % f);
+            let (square, four_c2) = (c1.clone() * c1, c2 * T::from_u64(4));
+            match square >= four_c2 && is_square(&(square - four_c2)) {
+                true => Err(CertificateError::NotPrime { step: index }),
+                false => Ok(()),
+            }
+        }
+        CertificateStep::Ecpp(proof) => {
+            if !proven.contains(&proof.order) {
+                return Err(CertificateError::UnprovenFactor { step: index });
+            }
+            if !proof.valid_curve() {
+                return Err(CertificateError::InvalidCurve { step: index });
+            }
+            if !proof.order_is_large() {
+                return Err(CertificateError::SmallOrder { step: index });
+            }
+            match proof.point_has_order() {
+                true => Ok(()),
+                false => Err(CertificateError::InvalidWitness { step: index }),
+            }
+        }
+    }
+}
+
+/// Checa as condições de Pocklington para a testemunha _a_ do fator _q_ de n-1: a^(n-1) ≡ 1 e mdc(a^((n-1)/q) - 1, n) = 1
+fn pocklington_witness<T: BigInteger>(n: &T, q: &T, a: &T) -> bool {
+    let n1 = n.clone() - T::one();
+    let z = a.modpow(&(n1.clone() / q.clone()), n);
+    z.modpow(q, n) == T::one() && ((z + n1) % n.clone()).gcd(n) == T::one()
+}
+
+/// Checa se _n_ é quadrado perfeito
+fn is_square<T: BigInteger>(n: &T) -> bool {
+    let root = n.isqrt();
+    root.clone() * root == *n
+}
+
+/// Lê um passo do certificado
+fn parse_step<T: BigInteger>(line: &str) -> Option<CertificateStep<T>> {
+    let mut words = line.split_whitespace();
+    let kind = words.next()?;
+    let prime = words.next()?.parse().ok()?;
+    let step = match kind {
+        "trial" => CertificateStep::TrialDivision(prime),
+        "pratt" => {
+            if words.next()? != "witness" {
+                return None;
+            }
+            let witness = words.next()?.parse().ok()?;
+            if words.next()? != "factors" {
+                return None;
+            }
+            let factors = words
+                .by_ref()
+                .map(parse_power)
+                .collect::<Option<Vec<_>>>()?;
+            CertificateStep::Pratt {
+                prime,
+                witness,
+                factors,
+            }
+        }
+        "pocklington" => {
+            if words.next()? != "factors" {
+                return None;
+            }
+            let factors = words
+                .by_ref()
+                .map(|word| {
+                    let (power, witness) = word.split_once(':')?;
+                    let (prime, exponent) = parse_power(power)?;
+                    Some(PocklingtonFactor {
+                        prime,
+                        exponent,
+                        witness: witness.parse().ok()?,
+                    })
+                })
+                .collect::<Option<Vec<_>>>()?;
+            CertificateStep::Pocklington { prime, factors }
+        }
+        "ecpp" => {
+            let mut field = |name: &str| match words.next()? == name {
+                true => words.next()?.parse().ok(),
+                false => None,
+            };
+            let (a, b, order) = (field("a")?, field("b")?, field("order")?);
+            let x = field("point")?;
+            CertificateStep::Ecpp(EcppProof {
+                prime,
+                a,
+                b,
+                order,
+                x,
+                y: words.next()?.parse().ok()?,
+            })
+        }
+        _ => return None,
+    };
+    match words.next() {
+        Some(_) => None,
+        None => Some(step),
+    }
+}
+
+/// Lê uma potência no formato `Q^E`
+fn parse_power<T: BigInteger>(word: &str) -> Option<(T, u32)> {
+    let (q, e) = word.split_once('^')?;
+    Some((q.parse().ok()?, e.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use super::*;
+
+    fn big(n: u64) -> BigUint {
+        BigUint::from(n)
+    }
+
+    fn proven(primes: &[u64]) -> BTreeSet<BigUint> {
+        primes.iter().map(|&p| big(p)).collect()
+    }
+
+    fn pratt(prime: u64, witness: u64, factors: &[(u64, u32)]) -> CertificateStep<BigUint> {
+        CertificateStep::Pratt {
+            prime: big(prime),
+            witness: big(witness),
+            factors: factors.iter().map(|&(q, e)| (big(q), e)).collect(),
+        }
+    }
+
+    fn pocklington(prime: u64, factors: &[(u64, u32, u64)]) -> CertificateStep<BigUint> {
+        CertificateStep::Pocklington {
+            prime: big(prime),
+            factors: factors
+                .iter()
+                .map(|&(q, exponent, witness)| PocklingtonFactor {
+                    prime: big(q),
+                    exponent,
+                    witness: big(witness),
+                })
+                .collect(),
+        }
+    }
+
+    /// Certificado de 933219711947 = 2·525949·887177 + 1, em que n-1 não tem um cofator primo e a prova termina em ECPP
+    fn ecpp_certificate() -> Certificate<BigUint> {
+        let certificate = certify(&big(933219711947), Tester::MillerRabin, &big(5429)).unwrap();
+        assert!(matches!(
+            certificate.steps.last(),
+            Some(CertificateStep::Ecpp(_))
+        ));
+        certificate
+    }
+
+    #[test]
+    fn certified_primes_verify_and_round_trip() {
+        let seed = big(5429);
+        let mersenne = (BigUint::from(1u32) << 89) - 1u32;
+        for n in [big(65537), big(4294967311), mersenne].iter() {
+            let certificate = certify(n, Tester::MillerRabin, &seed).unwrap();
+            assert_eq!(certificate.prime(), Some(n));
+            assert_eq!(certificate.verify(), Ok(()));
+            assert_eq!(
+                Certificate::parse(&certificate.to_string()),
+                Some(certificate)
+            );
+        }
+        assert!(certify(&big(4294967297), Tester::MillerRabin, &seed).is_none());
+
+        let ecpp = ecpp_certificate();
+        assert_eq!(ecpp.verify(), Ok(()));
+        let pratt = Certificate {
+            steps: vec![
+                CertificateStep::TrialDivision(big(2)),
+                CertificateStep::TrialDivision(big(3)),
+                pratt(7, 3, &[(2, 1), (3, 1)]),
+            ],
+        };
+        let text = format!("# dois certificados\n{}\n\n{}\n", pratt, ecpp);
+        assert_eq!(parse_certificates(&text), Some(vec![pratt, ecpp]));
+    }
+
+    #[test]
+    fn parse_rejects_malformed_steps() {
+        for text in [
+            "",
+            "# só um comentário",
+            "trial",
+            "trial 7 8",
+            "pratt 7 3 factors 2^1 3^1",
+            "pratt 7 witness 3 factors 2^1 3",
+            "pocklington 7 factors 2^1",
+            "ecpp 7 a 1 b 2 order 3 point 4",
+            "lucas 7",
+        ]
+        .iter()
+        {
+            assert_eq!(Certificate::<BigUint>::parse(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn verify_rejects_empty_and_trial_composites() {
+        let empty = Certificate::<BigUint> { steps: Vec::new() };
+        assert_eq!(empty.verify(), Err(CertificateError::Empty));
+        let step = CertificateStep::TrialDivision(big(91));
+        assert_eq!(
+            verify_step(&step, &proven(&[]), 1),
+            Err(CertificateError::NotPrime { step: 1 })
+        );
+        let step = CertificateStep::TrialDivision(big(TRIAL_DIVISION_LIMIT + 15));
+        assert_eq!(
+            verify_step(&step, &proven(&[]), 1),
+            Err(CertificateError::NotPrime { step: 1 })
+        );
+    }
+
+    #[test]
+    fn verify_rejects_bad_pratt_steps() {
+        let known = proven(&[2, 3]);
+        assert_eq!(
+            verify_step(&pratt(7, 3, &[(2, 1), (3, 1)]), &known, 1),
+            Ok(())
+        );
+        // 2 tem ordem 3 módulo 7, não 6
+        assert_eq!(
+            verify_step(&pratt(7, 2, &[(2, 1), (3, 1)]), &known, 1),
+            Err(CertificateError::InvalidWitness { step: 1 })
+        );
+        assert_eq!(
+            verify_step(&pratt(7, 3, &[(2, 1), (3, 1)]), &proven(&[2]), 2),
+            Err(CertificateError::UnprovenFactor { step: 2 })
+        );
+        assert_eq!(
+            verify_step(&pratt(7, 3, &[(2, 2)]), &known, 1),
+            Err(CertificateError::WrongFactorization { step: 1 })
+        );
+        // 2^8 ≢ 1 (mod 9): nenhuma testemunha faz um Pratt provar o composto 9
+        assert_eq!(
+            verify_step(&pratt(9, 2, &[(2, 3)]), &known, 1),
+            Err(CertificateError::InvalidWitness { step: 1 })
+        );
+    }
+
+    #[test]
+    fn verify_rejects_bad_pocklington_steps() {
+        // 1019 - 1 = 2·509; F = 509 passa de √1019
+        let known = proven(&[2, 509]);
+        assert_eq!(
+            verify_step(&pocklington(1019, &[(509, 1, 2)]), &known, 1),
+            Ok(())
+        );
+        assert_eq!(
+            verify_step(&pocklington(1019, &[(509, 1, 2)]), &proven(&[2]), 1),
+            Err(CertificateError::UnprovenFactor { step: 1 })
+        );
+        assert_eq!(
+            verify_step(&pocklington(1019, &[(2, 2, 2)]), &known, 1),
+            Err(CertificateError::WrongFactorization { step: 1 })
+        );
+        // 4^509 ≡ 1 (mod 1019), já que 4 é resíduo quadrático
+        assert_eq!(
+            verify_step(&pocklington(1019, &[(2, 1, 4)]), &known, 1),
+            Err(CertificateError::InvalidWitness { step: 1 })
+        );
+        // Só F = 2 não prova 1019: F³ < n
+        assert_eq!(
+            verify_step(&pocklington(1019, &[(2, 1, 2)]), &known, 1),
+            Err(CertificateError::InsufficientFactorization { step: 1 })
+        );
+    }
+
+    #[test]
+    fn verify_rejects_composites_caught_by_bls() {
+        // 206981 = 263·787, com os dois fatores ≡ 1 (mod 131): a testemunha 3 passa pelo Pocklington, mas F = 131 só chega a ∛n e c1² - 4·c2 = 8² - 4·12 = 4²
+        let step = pocklington(206981, &[(131, 1, 3)]);
+        assert_eq!(
+            verify_step(&step, &proven(&[131]), 1),
+            Err(CertificateError::NotPrime { step: 1 })
+        );
+    }
+
+    #[test]
+    fn verify_rejects_bad_ecpp_steps() {
+        let certificate = ecpp_certificate();
+        let (last, earlier) = certificate.steps.split_last().unwrap();
+        let known: BTreeSet<BigUint> = earlier.iter().map(|step| step.prime().clone()).collect();
+        let proof = match last {
+            CertificateStep::Ecpp(proof) => proof.clone(),
+            _ => unreachable!(),
+        };
+        let index = certificate.steps.len();
+        let check = |proof: &EcppProof<BigUint>, proven: &BTreeSet<BigUint>| {
+            verify_step(&CertificateStep::Ecpp(proof.clone()), proven, index)
+        };
+        assert_eq!(check(&proof, &known), Ok(()));
+        assert_eq!(
+            check(&proof, &proven(&[])),
+            Err(CertificateError::UnprovenFactor { step: index })
+        );
+
+        let mut off_curve = proof.clone();
+        off_curve.b += 1u32;
+        assert_eq!(
+            check(&off_curve, &known),
+            Err(CertificateError::InvalidCurve { step: index })
+        );
+
+        // A ordem precisa passar de (n^(1/4) + 1)², mesmo que seja um primo provado
+        let mut small_order = proof.clone();
+        small_order.order = big(3);
+        assert_eq!(
+            check(&small_order, &proven(&[3])),
+            Err(CertificateError::SmallOrder { step: index })
+        );
+
+        let mut wrong_order = proof;
+        wrong_order.order = known
+            .iter()
+            .rev()
+            .find(|&q| *q != wrong_order.order)
+            .unwrap()
+            .clone();
+        assert_eq!(
+            check(&wrong_order, &known),
+            Err(CertificateError::InvalidWitness { step: index })
+        );
+    }
+
+    #[test]
+    fn errors_name_the_step() {
+        assert_eq!(
+            CertificateError::SmallOrder { step: 4 }.to_string(),
+            "step 4: order of the point is too small"
+        );
+        assert_eq!(
+            CertificateError::Empty.to_string(),
+            "the certificate has no steps"
+        );
+    }
+}
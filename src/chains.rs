@@ -0,0 +1,298 @@
+use std::time::Instant;
+
+use log::info;
+
+use crate::backend::BigInteger;
+use crate::functions::{
+    combine_residues, small_primes, small_rem, ConstraintError, PrimeConstraints, PrimeKind,
+    SearchError, SearchLimits, Tester,
+};
+use crate::rand_gen::Mlcg;
+
+/// Limite dos primos pequenos utilizados no crivo da busca por cadeias
+const CHAIN_SIEVE_LIMIT: u64 = 4096;
+
+/// Tipo de uma cadeia de Cunningham
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChainKind {
+    /// Primeiro tipo: cada elemento é o dobro do anterior mais um (p, 2p+1, 4p+3, ...)
+    First,
+    /// Segundo tipo: cada elemento é o dobro do anterior menos um (p, 2p-1, 4p-3, ...)
+    Second,
+}
+
+impl ChainKind {
+    /// Retorna o elemento seguinte a _p_ na cadeia.
+    pub fn next<T: BigInteger>(&self, p: &T) -> T {
+        match self {
+            ChainKind::First => p.shl(1) + T::one(),
+            ChainKind::Second => p.shl(1) - T::one(),
+        }
+    }
+
+    /// Retorna o resto do elemento seguinte a um elemento com resto _x_ módulo _r_.
+    fn next_rem(&self, x: u64, r: u64) -> u64 {
+        match self {
+            ChainKind::First => (2 * x + 1) % r,
+            ChainKind::Second => (2 * x + r - 1) % r,
+        }
+    }
+}
+
+/// Cadeia de Cunningham encontrada, com todos os seus elementos primos em ordem
+#[derive(Clone, Debug)]
+pub struct CunninghamChain<T> {
+    /// Tipo da cadeia
+    pub kind: ChainKind,
+    /// Elementos da cadeia, começando pelo menor
+    pub members: Vec<T>,
+}
+
+/// Acha um provável primo de Sophie Germain p de _size_ bits, ou seja, com 2p+1 também primo
+pub fn find_sophie_germain<T: BigInteger>(
+    size: u64,
+    seed: &T,
+    tester: Tester,
+    limits: &SearchLimits,
+) -> Result<T, SearchError> {
+    find_cunningham_chain(
+        size,
+        2,
+        ChainKind::First,
+        seed,
+        tester,
+        &PrimeConstraints::new(),
+        limits,
+    )
+    .map(|chain| chain.members[0].clone())
+}
+
+/// Acha uma cadeia de Cunningham do tipo _kind_ com _length_ elementos prováveis primos, sendo o primeiro de _size_ bits e satisfazendo _constraints_. Os candidatos são percorridos a partir de um ponto aleatório de 2 em 2, ou apenas na classe ímpar da condição de resíduo quando existe, mantendo os restos pelos primos pequenos para descartar sem custo os candidatos em que algum elemento da cadeia tem um fator pequeno.
+pub fn find_cunningham_chain<T: BigInteger>(
+    size: u64,
+    length: usize,
+    kind: ChainKind,
+    seed: &T,
+    tester: Tester,
+    constraints: &PrimeConstraints<T>,
+    limits: &SearchLimits,
+) -> Result<CunninghamChain<T>, SearchError> {
+    assert!(size >= 3, "Cunningham chains need at least 3 bits");
+    assert!(length >= 1, "Cunningham chains need at least one member");
+    constraints
+        .check(size, PrimeKind::Chain(kind, length))
+        .map_err(SearchError::Unsatisfiable)?;
+    info!(
+        "Finding Cunningham chain of the {:?} kind with length {} and {} bits with {} method",
+        kind,
+        length,
+        size,
+        tester.name()
+    );
+    let now = Instant::now();
+    let mut counter = 0;
+    let mut mlcg_gen: Mlcg<T> = Mlcg::new_std(size, seed);
+
+    let two = T::from_u64(2);
+    let (p_res, step) = match constraints.residue_condition() {
+        None => (T::one(), two.clone()),
+        Some((a, m)) => combine_residues(a, m, &T::one(), &two).ok_or(
+            SearchError::Unsatisfiable(ConstraintError::IncompatibleKind),
+        )?,
+    };
+    let sieve: Vec<u64> = small_primes(CHAIN_SIEVE_LIMIT)
+        .into_iter()
+        .skip(1)
+        .filter(|r| 64 - r.leading_zeros() < (size - 1) as u32)
+        .collect();
+    let steps: Vec<u64> = sieve.iter().map(|r| small_rem(&step, *r)).collect();
+    let forbidden: Vec<Vec<bool>> = sieve
+        .iter()
+        .map(|r| forbidden_residues(kind, length, *r))
+        .collect();
+
+    let limit = T::one().shl(size);
+    let draw = |p: T| {
+        let p = constraints.shape(p, size);
+        let p = p.clone() - p % step.clone() + p_res.clone();
+        match p >= limit && p >= step {
+            true => p - step.clone(),
+            false => p,
+        }
+    };
+    let mut p = draw(mlcg_gen.rand());
+    let mut residues: Vec<u64> = sieve.iter().map(|r| small_rem(&p, *r)).collect();
+
+    loop {
+        limits.check(counter, now)?;
+        if p >= limit {
+            p = draw(mlcg_gen.rand());
+            residues = sieve.iter().map(|r| small_rem(&p, *r)).collect();
+        }
+
+        let sieved = forbidden
+            .iter()
+            .zip(&residues)
+            .all(|(table, res)| !table[*res as usize]);
+        if sieved && constraints.accepts(&p, size) {
+            counter += 1;
+            let mut members = vec![p.clone()];
+            while members.len() < length && tester.test(members.last().unwrap(), seed) {
+                let next = kind.next(members.last().unwrap());
+                members.push(next);
+            }
+            if members.len() == length && tester.test(members.last().unwrap(), seed) {
+                let elapsed_time = now.elapsed().as_secs_f32();
+                info!(
+                    "Found Cunningham chain! Search stats: {} checks in {:.4}s ({:.4}s/check avg)",
+                    counter,
+                    elapsed_time,
+                    elapsed_time / counter as f32
+                );
+                for (i, member) in members.iter().enumerate() {
+                    info!("Chain member {}: {}", i + 1, member);
+                }
+                return Ok(CunninghamChain { kind, members });
+            }
+        }
+
+        p = p + step.clone();
+        for ((res, r), step) in residues.iter_mut().zip(&sieve).zip(&steps) {
+            *res = (*res + step) % r;
+        }
+    }
+}
+
+/// Checa se todos os elementos de _chain_ são prováveis primos e seguem a regra de formação do seu tipo
+pub fn verify_chain<T: BigInteger>(chain: &CunninghamChain<T>, seed: &T, tester: Tester) -> bool {
+    chain.members.iter().all(|member| tester.test(member, seed))
+        && chain
+            .members
+            .windows(2)
+            .all(|pair| chain.kind.next(&pair[0]) == pair[1])
+}
+
+/// Marca os restos módulo _r_ para os quais algum dos _length_ elementos da cadeia seria divisível por _r_
+fn forbidden_residues(kind: ChainKind, length: usize, r: u64) -> Vec<bool> {
+    (0..r)
+        .map(|x| {
+            let mut member = x;
+            for _ in 0..length {
+                if member == 0 {
+                    return true;
+                }
+                member = kind.next_rem(member, r);
+            }
+            false
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use super::*;
+
+    fn chain(kind: ChainKind, length: usize) -> CunninghamChain<BigUint> {
+        let seed = BigUint::from(1234u32);
+        find_cunningham_chain(
+            48,
+            length,
+            kind,
+            &seed,
+            Tester::MillerRabin,
+            &PrimeConstraints::new(),
+            &SearchLimits::new(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn finds_chains_of_both_kinds() {
+        let seed = BigUint::from(1234u32);
+        for kind in [ChainKind::First, ChainKind::Second] {
+            let chain = chain(kind, 3);
+            assert_eq!(chain.members.len(), 3);
+            assert_eq!(chain.members[0].bits(), 48);
+            assert!(verify_chain(&chain, &seed, Tester::MillerRabin));
+        }
+    }
+
+    #[test]
+    fn rejects_broken_chains() {
+        let seed = BigUint::from(1234u32);
+        let mut wrong_kind = chain(ChainKind::First, 2);
+        wrong_kind.kind = ChainKind::Second;
+        assert!(!verify_chain(&wrong_kind, &seed, Tester::MillerRabin));
+
+        // 89, 179, 359, 719, 1439, 2879 é uma cadeia de primeiro tipo; 5759 = 13·443 não continua
+        let members: Vec<BigUint> = [89u32, 179, 359, 719, 1439, 2879]
+            .iter()
+            .map(|&m| BigUint::from(m))
+            .collect();
+        let mut known = CunninghamChain {
+            kind: ChainKind::First,
+            members,
+        };
+        assert!(verify_chain(&known, &seed, Tester::MillerRabin));
+        known.members.push(BigUint::from(5759u32));
+        assert!(!verify_chain(&known, &seed, Tester::MillerRabin));
+    }
+
+    #[test]
+    fn forbidden_residues_mark_members_divisible_by_r() {
+        // p ≡ 1 (mod 3) faz 2p+1 ≡ 0; p ≡ 2 (mod 3) faz 2p-1 ≡ 0
+        assert_eq!(
+            forbidden_residues(ChainKind::First, 2, 3),
+            vec![true, true, false]
+        );
+        assert_eq!(
+            forbidden_residues(ChainKind::Second, 2, 3),
+            vec![true, false, true]
+        );
+    }
+
+    #[test]
+    fn chains_honour_constraints() {
+        let seed = BigUint::from(1234u32);
+        let blum = PrimeConstraints::new()
+            .blum()
+            .unwrap()
+            .residue(BigUint::from(1u32), BigUint::from(5u32))
+            .unwrap()
+            .top_two_bits();
+        for kind in [ChainKind::First, ChainKind::Second] {
+            let chain = find_cunningham_chain(
+                48,
+                3,
+                kind,
+                &seed,
+                Tester::MillerRabin,
+                &blum,
+                &SearchLimits::new(),
+            )
+            .unwrap();
+            assert!(blum.accepts(&chain.members[0], 48));
+            assert!(verify_chain(&chain, &seed, Tester::MillerRabin));
+        }
+
+        // p ≡ 1 (mod 3) faz 2p+1 ≡ 0 (mod 3): a busca precisa falhar em vez de não terminar
+        let impossible = PrimeConstraints::new()
+            .residue(BigUint::from(1u32), BigUint::from(3u32))
+            .unwrap();
+        assert_eq!(
+            find_cunningham_chain(
+                48,
+                3,
+                ChainKind::First,
+                &seed,
+                Tester::MillerRabin,
+                &impossible,
+                &SearchLimits::new().max_candidates(10_000),
+            )
+            .unwrap_err(),
+            SearchError::Unsatisfiable(ConstraintError::IncompatibleKind)
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use std::hint::black_box;
+use std::time::Instant;
+
+use num_bigint::BigUint;
+
+use crate::backend::BigInteger;
+use crate::rand_gen::Mlcg;
+
+/// Limite para o valor absoluto da estatística t de Welch a partir do qual se considera que há vazamento de tempo (mesmo valor usado pelo dudect)
+pub const DUDECT_THRESHOLD: f64 = 4.5;
+
+/// Converte um booleano em uma máscara com todos os bits ligados (true) ou desligados (false), sem desvios.
+pub fn mask(bit: bool) -> u64 {
+    black_box(bit as u64).wrapping_neg()
+}
+
+/// Checa se _x_ é zero sem desvios.
+pub fn is_zero(x: u64) -> bool {
+    ((x | x.wrapping_neg()) >> 63) == 0
+}
+
+/// Checa se _a_ < _b_ sem desvios, para valores menores que 2⁶³.
+pub fn lt(a: u64, b: u64) -> bool {
+    (a.wrapping_sub(b) >> 63) == 1
+}
+
+/// Compara dois vetores de limbs de mesmo tamanho percorrendo sempre todos os limbs.
+pub fn eq(a: &[u64], b: &[u64]) -> bool {
+    let diff = a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y));
+    is_zero(diff)
+}
+
+/// Escreve _a_ em _out_ quando _mask_ tem todos os bits ligados, mantendo _out_ caso contrário.
+pub fn assign(mask: u64, out: &mut [u64], a: &[u64]) {
+    for (o, x) in out.iter_mut().zip(a) {
+        *o = (x & mask) | (*o & !mask);
+    }
+}
+
+/// Calcula _a_ - _b_ em _out_ e retorna se houve empréstimo final.
+pub fn sub(a: &[u64], b: &[u64], out: &mut [u64]) -> bool {
+    let mut borrow = false;
+    for ((o, x), y) in out.iter_mut().zip(a).zip(b) {
+        let (diff, b1) = x.overflowing_sub(*y);
+        let (diff, b2) = diff.overflowing_sub(borrow as u64);
+        *o = diff;
+        borrow = b1 | b2;
+    }
+    borrow
+}
+
+/// Copia para _out_ a entrada _index_ da tabela, lendo todas as entradas para que o padrão de acesso à memória não dependa do índice.
+pub fn lookup(table: &[Vec<u64>], index: usize, out: &mut [u64]) {
+    for limb in out.iter_mut() {
+        *limb = 0;
+    }
+    for (i, entry) in table.iter().enumerate() {
+        let m = mask(is_zero((i ^ index) as u64));
+        for (o, x) in out.iter_mut().zip(entry) {
+            *o |= x & m;
+        }
+    }
+}
+
+/// Conta os zeros à direita de _a_ percorrendo sempre todos os limbs.
+pub fn trailing_zeros(a: &[u64]) -> u64 {
+    let mut count = 0;
+    let mut done = 0;
+    for limb in a {
+        count += (limb.trailing_zeros() as u64) & !done;
+        done |= mask(!is_zero(*limb));
+    }
+    count
+}
+
+/// Desloca _a_ para a direita em _shift_ bits, com _shift_ < 2^_shift_bits_, sem que a quantidade de operações dependa de _shift_.
+pub fn shr(a: &[u64], shift: u64, shift_bits: u32) -> Vec<u64> {
+    let mut x = a.to_vec();
+    for b in 0..shift_bits {
+        let shifted = shr_public(&x, 1 << b);
+        assign(mask((shift >> b) & 1 == 1), &mut x, &shifted);
+    }
+    x
+}
+
+/// Desloca _a_ para a direita em uma quantidade pública de bits.
+fn shr_public(a: &[u64], amount: u64) -> Vec<u64> {
+    let limbs = (amount / 64) as usize;
+    let bits = amount % 64;
+    (0..a.len())
+        .map(|i| {
+            let low = a.get(i + limbs).copied().unwrap_or(0);
+            let high = a.get(i + limbs + 1).copied().unwrap_or(0);
+            if bits == 0 {
+                low
+            } else {
+                (low >> bits) | (high << (64 - bits))
+            }
+        })
+        .collect()
+}
+
+/// Calcula o resto de _a_ por 3 sem divisões de tamanho variável, usando que 2⁶⁴ ≡ 1 (mod 3).
+pub fn rem3(a: &[u64]) -> u64 {
+    a.iter().fold(0, |acc, limb| (acc + limb % 3) % 3)
+}
+
+/// Resultado de uma medição de vazamento de tempo no estilo dudect
+pub struct TimingReport {
+    /// Quantidade de medições consideradas em cada classe
+    pub samples: (usize, usize),
+    /// Estatística t de Welch entre os tempos das duas classes
+    pub t_statistic: f64,
+}
+
+impl TimingReport {
+    /// Indica se a diferença entre as classes é estatisticamente significativa.
+    pub fn leaks(&self) -> bool {
+        self.t_statistic.abs() > DUDECT_THRESHOLD
+    }
+}
+
+/// Mede _op_ _measurements_ vezes alternando aleatoriamente (com base em _seed_) entre a entrada fixa _fixed_ e entradas de _random_, e aplica o teste t de Welch sobre os tempos, descartando os 10% mais lentos para reduzir ruído.
+pub fn dudect<I, F>(
+    fixed: &I,
+    random: &[I],
+    measurements: usize,
+    seed: &BigUint,
+    mut op: F,
+) -> TimingReport
+where
+    F: FnMut(&I),
+{
+    let mut gen: Mlcg<BigUint> = Mlcg::new_std(64, seed);
+    let mut times: Vec<(bool, f64)> = Vec::with_capacity(measurements);
+
+    for i in 0..measurements {
+        let class = gen.rand().bit(0);
+        let input = if class {
+            fixed
+        } else {
+            &random[i % random.len()]
+        };
+        let now = Instant::now();
+        op(black_box(input));
+        times.push((class, now.elapsed().as_nanos() as f64));
+    }
+
+    let mut sorted: Vec<f64> = times.iter().map(|(_, t)| *t).collect();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let cutoff = sorted[sorted.len() * 9 / 10];
+
+    let stats = |class: bool| {
+        let values: Vec<f64> = times
+            .iter()
+            .filter(|(c, t)| *c == class && *t <= cutoff)
+            .map(|(_, t)| *t)
+            .collect();
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let var = values.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        (values.len(), mean, var)
+    };
+
+    let (n_fixed, mean_fixed, var_fixed) = stats(true);
+    let (n_random, mean_random, var_random) = stats(false);
+
+    TimingReport {
+        samples: (n_fixed, n_random),
+        t_statistic: (mean_fixed - mean_random)
+            / (var_fixed / n_fixed as f64 + var_random / n_random as f64).sqrt(),
+    }
+}
+
+/// Converte _x_ para exatamente _len_ limbs.
+pub fn limbs<T: BigInteger>(x: &T, len: usize) -> Vec<u64> {
+    let mut digits = x.to_limbs();
+    digits.resize(len, 0);
+    digits
+}
@@ -0,0 +1,398 @@
+use std::fmt;
+use std::time::Instant;
+
+use log::info;
+
+use crate::backend::BigInteger;
+use crate::functions::{SearchError, SearchLimits, Tester};
+use crate::rand_gen::Mlcg;
+use crate::sha256::{sha256, DIGEST_LEN};
+
+/// Pares (L, N) de tamanhos de p e q aprovados pelo FIPS 186-4 (4.2)
+pub const DSA_SIZES: [(u64, u64); 4] = [(1024, 160), (2048, 224), (2048, 256), (3072, 256)];
+
+/// Tamanho da saída da função de hash, em bits
+const OUTLEN: u64 = DIGEST_LEN as u64 * 8;
+
+/// Rótulo "ggen" usado na geração verificável de g (FIPS 186-4, A.2.3)
+const GGEN: [u8; 4] = *b"ggen";
+
+/// Parâmetros de domínio DSA/Diffie-Hellman, com os valores que permitem refazer e validar a geração
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DomainParameters<T> {
+    /// Primo de L bits, módulo do grupo
+    pub p: T,
+    /// Primo de N bits que divide p-1, ordem do subgrupo
+    pub q: T,
+    /// Gerador do subgrupo de ordem q
+    pub g: T,
+    /// domain_parameter_seed usada na geração de p e q
+    pub seed: Vec<u8>,
+    /// Contador da iteração em que p foi encontrado
+    pub counter: u64,
+    /// Índice usado na geração verificável de g
+    pub index: u8,
+}
+
+/// Motivo da rejeição de parâmetros de domínio na validação
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DomainError {
+    /// (L, N) não é um dos pares aprovados
+    UnsupportedSize { l: u64, n: u64 },
+    /// A seed tem menos de N bits
+    ShortSeed,
+    /// O contador passa de 4L-1
+    CounterOutOfRange,
+    /// q não é primo ou não corresponde à seed
+    InvalidQ,
+    /// p não é primo ou não corresponde à seed e ao contador
+    InvalidP,
+    /// q não divide p-1
+    QDoesNotDivide,
+    /// g fora de [2, p-1], sem ordem q ou diferente do gerado a partir da seed
+    InvalidGenerator,
+}
+
+impl fmt::Display for DomainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DomainError::UnsupportedSize { l, n } => {
+                write!(f, "(L, N) = ({}, {}) is not an approved size", l, n)
+            }
+            DomainError::ShortSeed => write!(f, "domain parameter seed is shorter than N bits"),
+            DomainError::CounterOutOfRange => write!(f, "counter is greater than 4L-1"),
+            DomainError::InvalidQ => write!(f, "q is not prime or does not match the seed"),
+            DomainError::InvalidP => {
+                write!(f, "p is not prime or does not match the seed and counter")
+            }
+            DomainError::QDoesNotDivide => write!(f, "q does not divide p-1"),
+            DomainError::InvalidGenerator => {
+                write!(f, "g is not a valid generator of the order q subgroup")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DomainError {}
+
+impl<T: BigInteger> fmt::Display for DomainParameters<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "p: {}", self.p)?;
+        writeln!(f, "q: {}", self.q)?;
+        writeln!(f, "g: {}", self.g)?;
+        let seed: String = self
+            .seed
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        writeln!(f, "seed: {}", seed)?;
+        writeln!(f, "counter: {}", self.counter)?;
+        write!(f, "index: {}", self.index)
+    }
+}
+
+impl<T: BigInteger> DomainParameters<T> {
+    /// Lê parâmetros no formato escrito por [fmt::Display]: uma linha `nome: valor` para p, q, g, seed (hexadecimal), counter e index.
+    pub fn parse(text: &str) -> Option<Self> {
+        let field = |name: &str| {
+            text.lines().find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                match key.trim() == name {
+                    true => Some(value.trim()),
+                    false => None,
+                }
+            })
+        };
+        let seed = field("seed")?;
+        if !seed.len().is_multiple_of(2) {
+            return None;
+        }
+        Some(DomainParameters {
+            p: field("p")?.parse().ok()?,
+            q: field("q")?.parse().ok()?,
+            g: field("g")?.parse().ok()?,
+            seed: (0..seed.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(seed.get(i..i + 2)?, 16).ok())
+                .collect::<Option<Vec<u8>>>()?,
+            counter: field("counter")?.parse().ok()?,
+            index: field("index")?.parse().ok()?,
+        })
+    }
+}
+
+/// Gera parâmetros de domínio com p de _l_ bits e q de _n_ bits pelo procedimento de primos prováveis do FIPS 186-4 (A.1.1.2) com SHA-256, e o gerador g pelo método verificável (A.2.3) com índice _index_. As domain_parameter_seeds de _n_ bits são sorteadas de um MLCG iniciado em _seed_ e os candidatos são testados com _tester_.
+pub fn generate_domain_parameters<T: BigInteger>(
+    l: u64,
+    n: u64,
+    index: u8,
+    seed: &T,
+    tester: Tester,
+    limits: &SearchLimits,
+) -> Result<DomainParameters<T>, SearchError> {
+    assert!(
+        DSA_SIZES.contains(&(l, n)),
+        "(L, N) must be one of the FIPS 186-4 sizes"
+    );
+    info!(
+        "Generating domain parameters with L = {} and N = {} with {} method",
+        l,
+        n,
+        tester.name()
+    );
+    let now = Instant::now();
+    let mut counter_total = 0;
+    let mut seed_gen: Mlcg<T> = Mlcg::new_std(n, seed);
+
+    loop {
+        limits.check(counter_total, now)?;
+        let domain_seed = seed_gen.rand().to_bytes_be_padded(n as usize / 8).unwrap();
+        let q = derive_q(&domain_seed, n);
+        counter_total += 1;
+        if !tester.test(&q, seed) {
+            continue;
+        }
+        for counter in 0..4 * l {
+            limits.check(counter_total, now)?;
+            let p = derive_p(&domain_seed, &q, l, counter);
+            counter_total += 1;
+            if p.bits() == l && tester.test(&p, seed) {
+                let g = derive_g(&p, &q, &domain_seed, index)
+                    .expect("a generator exists for every index");
+                let elapsed_time = now.elapsed().as_secs_f32();
+                info!(
+                    "Found domain parameters! Search stats: {} checks in {:.4}s ({:.4}s/check avg)",
+                    counter_total,
+                    elapsed_time,
+                    elapsed_time / counter_total as f32
+                );
+                return Ok(DomainParameters {
+                    p,
+                    q,
+                    g,
+                    seed: domain_seed,
+                    counter,
+                    index,
+                });
+            }
+        }
+    }
+}
+
+/// Valida parâmetros de domínio recebidos de fora refazendo a geração de p e q a partir da seed e do contador (FIPS 186-4, A.1.1.3) e a de g a partir do índice (A.2.4). A primalidade é checada com _tester_.
+pub fn validate_domain_parameters<T: BigInteger>(
+    params: &DomainParameters<T>,
+    tester: Tester,
+    seed: &T,
+) -> Result<(), DomainError> {
+    let (l, n) = (params.p.bits(), params.q.bits());
+    if !DSA_SIZES.contains(&(l, n)) {
+        return Err(DomainError::UnsupportedSize { l, n });
+    }
+    if (params.seed.len() as u64) * 8 < n {
+        return Err(DomainError::ShortSeed);
+    }
+    if params.counter >= 4 * l {
+        return Err(DomainError::CounterOutOfRange);
+    }
+    if derive_q::<T>(&params.seed, n) != params.q || !tester.test(&params.q, seed) {
+        return Err(DomainError::InvalidQ);
+    }
+    // Todos os contadores anteriores precisam ter produzido candidatos compostos ou pequenos demais
+    for counter in 0..params.counter {
+        let p = derive_p(&params.seed, &params.q, l, counter);
+        if p.bits() == l && tester.test(&p, seed) {
+            return Err(DomainError::InvalidP);
+        }
+    }
+    if derive_p(&params.seed, &params.q, l, params.counter) != params.p
+        || !tester.test(&params.p, seed)
+    {
+        return Err(DomainError::InvalidP);
+    }
+    match derive_g(&params.p, &params.q, &params.seed, params.index) {
+        Some(g) if g == params.g => Ok(()),
+        _ => Err(DomainError::InvalidGenerator),
+    }
+}
+
+/// Valida parcialmente um grupo (p, q, g) sem seed (FIPS 186-4, A.2.2): tamanhos aprovados, p e q primos, q | p-1 e g com ordem q.
+pub fn validate_group<T: BigInteger>(
+    p: &T,
+    q: &T,
+    g: &T,
+    tester: Tester,
+    seed: &T,
+) -> Result<(), DomainError> {
+    let (l, n) = (p.bits(), q.bits());
+    if !DSA_SIZES.contains(&(l, n)) {
+        return Err(DomainError::UnsupportedSize { l, n });
+    }
+    if !tester.test(q, seed) {
+        return Err(DomainError::InvalidQ);
+    }
+    if !tester.test(p, seed) {
+        return Err(DomainError::InvalidP);
+    }
+    if (p.clone() - T::one()) % q.clone() != T::zero() {
+        return Err(DomainError::QDoesNotDivide);
+    }
+    match *g > T::one() && *g < *p && g.modpow(q, p) == T::one() {
+        true => Ok(()),
+        false => Err(DomainError::InvalidGenerator),
+    }
+}
+
+/// Calcula q = 2^(N-1) + U + 1 - (U mod 2), com U = Hash(seed) mod 2^(N-1)
+fn derive_q<T: BigInteger>(domain_seed: &[u8], n: u64) -> T {
+    let top = T::one().shl(n - 1);
+    let u = T::from_bytes_be(&sha256(domain_seed)) % top.clone();
+    (top + u) | T::one()
+}
+
+/// Calcula o candidato a p da iteração _counter_: X = W + 2^(L-1), com W formado pelos hashes de seed + offset + j, e p = X - (X mod 2q) + 1
+fn derive_p<T: BigInteger>(domain_seed: &[u8], q: &T, l: u64, counter: u64) -> T {
+    let blocks = l.div_ceil(OUTLEN) - 1;
+    let last_bits = l - 1 - blocks * OUTLEN;
+    let seedlen = domain_seed.len();
+    let modulus = T::one().shl(seedlen as u64 * 8);
+    let offset = 1 + counter * (blocks + 1);
+    let base = T::from_bytes_be(domain_seed);
+
+    let mut w = T::zero();
+    for j in 0..=blocks {
+        let input = (base.clone() + T::from_u64(offset + j)) % modulus.clone();
+        let mut v = T::from_bytes_be(&sha256(&input.to_bytes_be_padded(seedlen).unwrap()));
+        if j == blocks {
+            v = v % T::one().shl(last_bits);
+        }
+        w = w + v.shl(j * OUTLEN);
+    }
+    let x = w + T::one().shl(l - 1);
+    let c = x.clone() % (q.shl(1));
+    x + T::one() - c
+}
+
+/// Gera g de forma verificável (FIPS 186-4, A.2.3): g = Hash(seed || "ggen" || index || count)^((p-1)/q) mod p, para o primeiro count que produz g ≥ 2
+fn derive_g<T: BigInteger>(p: &T, q: &T, domain_seed: &[u8], index: u8) -> Option<T> {
+    let e = (p.clone() - T::one()) / q.clone();
+    (1..=u16::MAX).find_map(|count| {
+        let mut u = domain_seed.to_vec();
+        u.extend_from_slice(&GGEN);
+        u.push(index);
+        u.extend_from_slice(&count.to_be_bytes());
+        let g = T::from_bytes_be(&sha256(&u)).modpow(&e, p);
+        match g > T::one() {
+            true => Some(g),
+            false => None,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use super::*;
+
+    fn generate() -> (DomainParameters<BigUint>, BigUint) {
+        let seed = BigUint::from(5429u32);
+        let params = generate_domain_parameters(
+            1024,
+            160,
+            1,
+            &seed,
+            Tester::MillerRabin,
+            &SearchLimits::new(),
+        )
+        .unwrap();
+        (params, seed)
+    }
+
+    #[test]
+    fn generated_parameters_validate_and_round_trip() {
+        let (params, seed) = generate();
+        assert_eq!((params.p.bits(), params.q.bits()), (1024, 160));
+        assert_eq!(
+            validate_domain_parameters(&params, Tester::MillerRabin, &seed),
+            Ok(())
+        );
+        assert_eq!(
+            validate_group(&params.p, &params.q, &params.g, Tester::MillerRabin, &seed),
+            Ok(())
+        );
+        assert_eq!(
+            DomainParameters::parse(&params.to_string()),
+            Some(params.clone())
+        );
+    }
+
+    #[test]
+    fn validation_rejects_each_tampered_field() {
+        let (params, seed) = generate();
+        let validate = |params: &DomainParameters<BigUint>| {
+            validate_domain_parameters(params, Tester::MillerRabin, &seed)
+        };
+
+        let mut short_seed = params.clone();
+        short_seed.seed.pop();
+        assert_eq!(validate(&short_seed), Err(DomainError::ShortSeed));
+
+        let mut counter = params.clone();
+        counter.counter = 4 * 1024;
+        assert_eq!(validate(&counter), Err(DomainError::CounterOutOfRange));
+
+        let mut other_seed = params.clone();
+        other_seed.seed[0] ^= 1;
+        assert_eq!(validate(&other_seed), Err(DomainError::InvalidQ));
+
+        // Um contador posterior refaz um p diferente, e um anterior a ele já teria sido aceito
+        let mut later = params.clone();
+        later.counter += 1;
+        assert_eq!(validate(&later), Err(DomainError::InvalidP));
+
+        let mut index = params.clone();
+        index.index ^= 1;
+        assert_eq!(validate(&index), Err(DomainError::InvalidGenerator));
+
+        let mut small = params.clone();
+        small.q = BigUint::from(65537u32);
+        assert_eq!(
+            validate(&small),
+            Err(DomainError::UnsupportedSize { l: 1024, n: 17 })
+        );
+    }
+
+    #[test]
+    fn group_validation_rejects_bad_groups() {
+        let (params, seed) = generate();
+        let validate = |p: &BigUint, q: &BigUint, g: &BigUint| {
+            validate_group(p, q, g, Tester::MillerRabin, &seed)
+        };
+        let (p, q, g) = (&params.p, &params.q, &params.g);
+
+        assert_eq!(
+            validate(p, q, &BigUint::from(1u32)),
+            Err(DomainError::InvalidGenerator)
+        );
+        assert_eq!(validate(p, q, p), Err(DomainError::InvalidGenerator));
+        // 2 só teria ordem q com probabilidade da ordem de q/p
+        assert_eq!(
+            validate(p, q, &BigUint::from(2u32)),
+            Err(DomainError::InvalidGenerator)
+        );
+        assert_eq!(
+            validate(p, &(q.clone() + 1u32), g),
+            Err(DomainError::InvalidQ)
+        );
+        assert_eq!(
+            validate(&(p.clone() + 1u32), q, g),
+            Err(DomainError::InvalidP)
+        );
+        let mut other_q = q.clone() + 2u32;
+        while !Tester::MillerRabin.test(&other_q, &seed) {
+            other_q += 2u32;
+        }
+        assert_eq!(validate(p, &other_q, g), Err(DomainError::QDoesNotDivide));
+    }
+}
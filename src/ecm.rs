@@ -0,0 +1,241 @@
+use log::info;
+
+use crate::backend::BigInteger;
+use crate::functions::small_primes;
+use crate::rand_gen::Mlcg;
+
+/// Razão B2/B1 entre os limites dos estágios 2 e 1
+const STAGE2_RATIO: u64 = 100;
+
+/// Tamanho do passo gigante do estágio 2 (2·3·5·7·11): cada primo q é escrito como mD ± j, com j coprimo com D
+const STAGE2_STEP: u64 = 2310;
+
+/// Ponto de uma curva de Montgomery By² = x³ + Ax² + x em coordenadas projetivas (X : Z), sem a coordenada y
+#[derive(Clone, Debug)]
+struct Point<T> {
+    x: T,
+    z: T,
+}
+
+/// Curva de Montgomery módulo _n_, representada pela constante a24 = (A + 2)/4 das fórmulas de duplicação
+struct Curve<'a, T> {
+    n: &'a T,
+    a24: T,
+}
+
+impl<T: BigInteger> Curve<'_, T> {
+    fn add_mod(&self, a: &T, b: &T) -> T {
+        (a.clone() + b) % self.n
+    }
+
+    fn sub_mod(&self, a: &T, b: &T) -> T {
+        (a.clone() + self.n - b) % self.n
+    }
+
+    fn mul_mod(&self, a: &T, b: &T) -> T {
+        a.clone() * b % self.n
+    }
+
+    /// Dobra _p_: X₂ = (X+Z)²(X-Z)², Z₂ = 4XZ·((X-Z)² + a24·4XZ)
+    fn double(&self, p: &Point<T>) -> Point<T> {
+        let sum = self.add_mod(&p.x, &p.z);
+        let diff = self.sub_mod(&p.x, &p.z);
+        let t1 = self.mul_mod(&sum, &sum);
+        let t2 = self.mul_mod(&diff, &diff);
+        let t3 = self.sub_mod(&t1, &t2);
+        Point {
+            x: self.mul_mod(&t1, &t2),
+            z: self.mul_mod(&t3, &self.add_mod(&t2, &self.mul_mod(&self.a24, &t3))),
+        }
+    }
+
+    /// Soma _p_ e _q_ sabendo a diferença _d_ = p - q, que é o que as coordenadas (X : Z) permitem
+    fn add(&self, p: &Point<T>, q: &Point<T>, d: &Point<T>) -> Point<T> {
+        let u = self.mul_mod(&self.sub_mod(&p.x, &p.z), &self.add_mod(&q.x, &q.z));
+        let v = self.mul_mod(&self.add_mod(&p.x, &p.z), &self.sub_mod(&q.x, &q.z));
+        let sum = self.add_mod(&u, &v);
+        let diff = self.sub_mod(&u, &v);
+        Point {
+            x: self.mul_mod(&d.z, &self.mul_mod(&sum, &sum)),
+            z: self.mul_mod(&d.x, &self.mul_mod(&diff, &diff)),
+        }
+    }
+
+    /// Calcula [_k_]·_p_ pela escada de Montgomery, mantendo R₁ - R₀ = p
+    fn multiply(&self, p: &Point<T>, k: u64) -> Point<T> {
+        let (mut r0, mut r1) = (p.clone(), self.double(p));
+        for i in (0..63 - k.leading_zeros()).rev() {
+            match (k >> i) & 1 {
+                1 => {
+                    r0 = self.add(&r1, &r0, p);
+                    r1 = self.double(&r1);
+                }
+                _ => {
+                    r1 = self.add(&r1, &r0, p);
+                    r0 = self.double(&r0);
+                }
+            }
+        }
+        r0
+    }
+}
+
+/// Fatoração por curvas elípticas de Lenstra: testa até _curves_ curvas de Montgomery com a parametrização de Suyama, com σ sorteado por _seed_gen_, e retorna o primeiro fator próprio de _n_ encontrado. O estágio 1 multiplica o ponto inicial por todas as potências de primos até _b1_ e o estágio 2 procura um único primo a mais entre _b1_ e 100·_b1_.
+pub fn ecm<T: BigInteger>(n: &T, b1: u64, curves: u64, seed_gen: &mut Mlcg<T>) -> Option<T> {
+    info!("Running ECM with B1 = {} on up to {} curves", b1, curves);
+    let six = T::from_u64(6);
+    (1..=curves).find_map(|curve| {
+        let sigma = six.clone() + seed_gen.rand() % (n.clone() - six.clone());
+        let found = ecm_curve(n, b1, b1 * STAGE2_RATIO, &sigma);
+        if let Some(factor) = &found {
+            info!("ECM found {} on curve {} (σ = {})", factor, curve, sigma);
+        }
+        found
+    })
+}
+
+/// Roda os dois estágios do ECM sobre a curva de Suyama de parâmetro _sigma_ (u = σ² - 5, v = 4σ, ponto inicial (u³ : v³) e a24 = (v - u)³(3u + v)/(16u³v)), com limites _b1_ e _b2_. Retorna um fator próprio de _n_ se a ordem da curva módulo algum fator primo p for _b1_-suave a menos de um primo até _b2_.
+pub fn ecm_curve<T: BigInteger>(n: &T, b1: u64, b2: u64, sigma: &T) -> Option<T> {
+    let curve = Curve { n, a24: T::zero() };
+    let u = curve.sub_mod(&curve.mul_mod(sigma, sigma), &T::from_u64(5));
+    let v = curve.mul_mod(&T::from_u64(4), sigma);
+    let u3 = curve.mul_mod(&u, &curve.mul_mod(&u, &u));
+    let v_minus_u = curve.sub_mod(&v, &u);
+    let numerator = curve.mul_mod(
+        &curve.mul_mod(&v_minus_u, &curve.mul_mod(&v_minus_u, &v_minus_u)),
+        &curve.add_mod(&curve.mul_mod(&T::from_u64(3), &u), &v),
+    );
+    let denominator = curve.mul_mod(&T::from_u64(16), &curve.mul_mod(&u3, &v));
+    let a24 = match denominator.mod_inverse(n) {
+        Some(inverse) => curve.mul_mod(&numerator, &inverse),
+        // O próprio σ já revela um fator
+        None => return proper_factor(denominator.gcd(n), n),
+    };
+    let curve = Curve { n, a24 };
+    let mut q = Point {
+        x: u3,
+        z: curve.mul_mod(&v, &curve.mul_mod(&v, &v)),
+    };
+
+    // Estágio 1: [M]Q, com M o produto das maiores potências de primos até B1
+    for p in small_primes(b1 + 1) {
+        let mut power = p;
+        while power <= b1 / p {
+            power *= p;
+        }
+        q = curve.multiply(&q, power);
+    }
+    let g = q.z.gcd(n);
+    if g != T::one() {
+        return proper_factor(g, n);
+    }
+
+    stage2(&curve, &q, b1, b2)
+}
+
+/// Estágio 2 pela continuação de Montgomery: para cada primo b1 < mD ± j ≤ b2, [mD]Q = ±[j]Q módulo p se e só se X_mD·Z_j - X_j·Z_mD ≡ 0, e esses termos são acumulados em um único produto
+fn stage2<T: BigInteger>(curve: &Curve<T>, q: &Point<T>, b1: u64, b2: u64) -> Option<T> {
+    let n = curve.n;
+    let half = STAGE2_STEP / 2;
+
+    // Passos pequenos: [j]Q para j ímpar menor que D/2, com [j + 2]Q = [j]Q + [2]Q
+    let q2 = curve.double(q);
+    let mut baby: Vec<(u64, Point<T>)> = vec![(1, q.clone()), (3, curve.add(&q2, q, q))];
+    while baby.last().unwrap().0 + 2 < half {
+        let len = baby.len();
+        let next = curve.add(&baby[len - 1].1, &q2, &baby[len - 2].1);
+        baby.push((baby[len - 1].0 + 2, next));
+    }
+    baby.retain(|(j, _)| gcd_u64(*j, STAGE2_STEP) == 1);
+
+    // Passos gigantes: [(m + 1)D]Q = [mD]Q + [D]Q, com diferença [(m - 1)D]Q
+    let base_primes = small_primes(isqrt_u64(b2 + half) + 1);
+    let mut m = ((b1 + half) / STAGE2_STEP).max(1);
+    let giant = curve.multiply(q, STAGE2_STEP);
+    let mut current = curve.multiply(q, m * STAGE2_STEP);
+    let mut next = curve.multiply(q, (m + 1) * STAGE2_STEP);
+    let mut product = T::one();
+    while m * STAGE2_STEP - half <= b2 {
+        let center = m * STAGE2_STEP;
+        let primes = window_primes(center - half, center + half, &base_primes);
+        let is_candidate = |q: u64| q > b1 && q <= b2 && primes[(q + half - center) as usize];
+        for (j, point) in &baby {
+            if is_candidate(center - j) || is_candidate(center + j) {
+                let term = curve.sub_mod(
+                    &curve.mul_mod(&current.x, &point.z),
+                    &curve.mul_mod(&point.x, &current.z),
+                );
+                product = curve.mul_mod(&product, &term);
+            }
+        }
+        let after = curve.add(&next, &giant, &current);
+        current = std::mem::replace(&mut next, after);
+        m += 1;
+    }
+    proper_factor(product.gcd(n), n)
+}
+
+/// Retorna _g_ se for um fator próprio de _n_
+fn proper_factor<T: BigInteger>(g: T, n: &T) -> Option<T> {
+    match g > T::one() && g < *n {
+        true => Some(g),
+        false => None,
+    }
+}
+
+/// Crivo do intervalo [_low_, _high_]: a posição i diz se low + i é primo
+fn window_primes(low: u64, high: u64, base_primes: &[u64]) -> Vec<bool> {
+    let mut is_prime = vec![true; (high - low + 1) as usize];
+    for &p in base_primes {
+        if p * p > high {
+            break;
+        }
+        let mut multiple = (p * p).max(low.div_ceil(p) * p);
+        while multiple <= high {
+            is_prime[(multiple - low) as usize] = false;
+            multiple += p;
+        }
+    }
+    for value in low..2.min(high + 1) {
+        is_prime[(value - low) as usize] = false;
+    }
+    is_prime
+}
+
+/// Máximo divisor comum de inteiros pequenos
+fn gcd_u64(a: u64, b: u64) -> u64 {
+    match b {
+        0 => a,
+        _ => gcd_u64(b, a % b),
+    }
+}
+
+/// Raiz quadrada inteira de _n_
+fn isqrt_u64(n: u64) -> u64 {
+    let mut root = (n as f64).sqrt() as u64;
+    while root * root > n {
+        root -= 1;
+    }
+    while (root + 1) * (root + 1) <= n {
+        root += 1;
+    }
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use super::*;
+
+    #[test]
+    fn finds_a_known_factor() {
+        // 1000000000039 - 1 e 1000000000039 + 1 têm fatores primos grandes, fora do alcance do p-1 e do p+1
+        let p: BigUint = "1000000000039".parse().unwrap();
+        let q: BigUint = "100000000000000000039".parse().unwrap();
+        let n = p.clone() * q.clone();
+        let mut seed_gen: Mlcg<BigUint> = Mlcg::new_std(64, &BigUint::from(5429u32));
+        assert_eq!(ecm(&n, 2_000, 100, &mut seed_gen), Some(p));
+        assert_eq!(ecm(&q, 2_000, 5, &mut seed_gen), None);
+    }
+}
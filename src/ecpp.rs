@@ -0,0 +1,691 @@
+use std::collections::HashMap;
+
+use num_bigint::BigInt;
+
+use crate::backend::BigInteger;
+use crate::functions::{small_primes, Tester};
+use crate::hilbert::{discriminants, hilbert_class_polynomial, reduce, Discriminant};
+
+/// Maior |D| da tabela de discriminantes
+const MAX_DISCRIMINANT: i64 = 100_000;
+
+/// Maior número de classes (grau do polinômio de Hilbert) da tabela de discriminantes
+const MAX_CLASS_NUMBER: usize = 40;
+
+/// Limite dos primos removidos das ordens candidatas por divisão por tentativa
+const SMOOTH_BOUND: u64 = 1 << 16;
+
+/// Quantidade máxima de twists testados na construção da curva
+const MAX_TWISTS: u64 = 64;
+
+/// Quantidade máxima de abscissas testadas em cada curva na busca por um ponto
+const MAX_POINTS: u64 = 64;
+
+/// Quantidade máxima de tentativas em buscas que só terminam quando n é primo
+const MAX_TRIES: u64 = 1_000;
+
+/// Passo do ECPP (Goldwasser-Kilian/Atkin-Morain): curva y² = x³ + ax + b módulo _prime_ com um ponto (x, y) de ordem _order_, primo maior que (n^(1/4) + 1)²
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EcppProof<T> {
+    /// Número provado primo
+    pub prime: T,
+    /// Coeficiente a da curva
+    pub a: T,
+    /// Coeficiente b da curva
+    pub b: T,
+    /// Ordem prima do ponto, provada em um passo anterior
+    pub order: T,
+    /// Abscissa do ponto
+    pub x: T,
+    /// Ordenada do ponto
+    pub y: T,
+}
+
+impl<T: BigInteger> EcppProof<T> {
+    /// Checa que _prime_ é coprimo com 6, que a curva é não singular módulo _prime_ e que o ponto está nela.
+    pub fn valid_curve(&self) -> bool {
+        let n = &self.prime;
+        let field = Field { n };
+        let discriminant = field.add(
+            &field.mul(
+                &T::from_u64(4),
+                &field.mul(&self.a, &field.mul(&self.a, &self.a)),
+            ),
+            &field.mul(&T::from_u64(27), &field.mul(&self.b, &self.b)),
+        );
+        *n > T::from_u64(3)
+            && n.gcd(&T::from_u64(6)) == T::one()
+            && discriminant.gcd(n) == T::one()
+            && [&self.a, &self.b, &self.x, &self.y]
+                .iter()
+                .all(|v| **v < *n)
+            && field.mul(&self.y, &self.y) == curve_rhs(&field, &self.a, &self.b, &self.x)
+    }
+
+    /// Checa que a ordem é maior que (n^(1/4) + 1)², de forma conservadora: (⌊√q⌋ - 1)⁴ > n.
+    pub fn order_is_large(&self) -> bool {
+        order_is_large(&self.order, &self.prime)
+    }
+
+    /// Checa que [order]·(x, y) = O. O produto é calculado em coordenadas jacobianas sem nenhum caso especial: se em algum passo intermediário a soma for degenerada módulo um primo p | n, o resultado final é (0 : 0 : 0) módulo p e é recusado pela exigência de mdc(X, n) = 1.
+    pub fn point_has_order(&self) -> bool {
+        let field = Field { n: &self.prime };
+        let point = field.scalar_mul(&self.a, &self.order, &self.x, &self.y);
+        point.z == T::zero() && point.x.gcd(&self.prime) == T::one()
+    }
+}
+
+/// Ordem candidata m = k·q de uma curva com multiplicação complexa pelo discriminante _discriminant_ (índice na tabela), com q provável primo
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Candidate<T> {
+    /// Índice do discriminante na tabela do [Ecpp]
+    pub discriminant: usize,
+    /// Ordem da curva
+    pub order: T,
+    /// Fator provável primo da ordem, obtido removendo os fatores pequenos
+    pub q: T,
+}
+
+/// Estado da busca de ordens candidatas para um número
+pub struct CandidateSearch<T> {
+    /// Número a ser provado primo
+    n: T,
+    /// Raízes quadradas módulo n, ou None se n não tiver um não resíduo pequeno
+    roots: Option<SquareRoots<T>>,
+    /// Próximo discriminante da tabela a ser testado
+    next: usize,
+    /// Candidatos já encontrados e ainda não entregues
+    pending: Vec<Candidate<T>>,
+}
+
+/// Contexto do ECPP reaproveitado entre os passos de uma certificação: tabela de discriminantes e polinômios de classe já calculados
+pub struct Ecpp {
+    /// Discriminantes fundamentais, do menor para o maior número de classes
+    discriminants: Vec<Discriminant>,
+    /// Polinômios de Hilbert já calculados, por discriminante
+    polynomials: HashMap<i64, Vec<BigInt>>,
+    /// Primos usados na remoção dos fatores pequenos das ordens
+    primes: Vec<u64>,
+}
+
+impl Default for Ecpp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Ecpp {
+    /// Constrói o contexto, enumerando a tabela de discriminantes.
+    pub fn new() -> Self {
+        Ecpp {
+            discriminants: discriminants(MAX_DISCRIMINANT, MAX_CLASS_NUMBER),
+            polynomials: HashMap::new(),
+            primes: small_primes(SMOOTH_BOUND),
+        }
+    }
+
+    /// Inicia a busca de ordens candidatas para _n_.
+    pub fn search<T: BigInteger>(&self, n: &T) -> CandidateSearch<T> {
+        CandidateSearch {
+            n: n.clone(),
+            roots: SquareRoots::new(n),
+            next: 0,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Retorna a próxima ordem candidata da busca, percorrendo os discriminantes em ordem crescente de número de classes. Para cada D com (D/n) = 1 e 4n = u² + |D|v² (Cornacchia), as ordens n + 1 ± u (e as dos twists extras para D = -3 e -4) são aceitas se, sem os fatores menores que 2^16, sobrar um q que passe em _tester_ e seja grande o bastante para a prova.
+    pub fn next_candidate<T: BigInteger>(
+        &self,
+        search: &mut CandidateSearch<T>,
+        tester: Tester,
+        seed: &T,
+    ) -> Option<Candidate<T>> {
+        let n = search.n.clone();
+        while search.pending.is_empty() {
+            let index = search.next;
+            let disc = self.discriminants.get(index)?;
+            search.next += 1;
+            let abs_d = (-disc.d) as u64;
+            if T::from_u64(4 * abs_d) >= n || kronecker(disc.d, &n) != 1 {
+                continue;
+            }
+            let (u, v) = match cornacchia(search.roots.as_ref()?, abs_d) {
+                Some(solution) => solution,
+                None => continue,
+            };
+            let traces = match abs_d {
+                3 => {
+                    let three_v = v * T::from_u64(3);
+                    let diff = match u >= three_v {
+                        true => u.clone() - three_v.clone(),
+                        false => three_v.clone() - u.clone(),
+                    };
+                    vec![(u.clone() + three_v).shr(1), diff.shr(1), u]
+                }
+                4 => vec![u, v.shl(1)],
+                _ => vec![u],
+            };
+            for trace in traces {
+                for order in [
+                    n.clone() + T::one() - trace.clone(),
+                    n.clone() + T::one() + trace,
+                ] {
+                    let q = self.remove_small_factors(&order);
+                    if q > T::one() && q < n && order_is_large(&q, &n) && tester.test(&q, seed) {
+                        search.pending.push(Candidate {
+                            discriminant: index,
+                            order,
+                            q,
+                        });
+                    }
+                }
+            }
+            // Os menores q primeiro, para descer mais rápido
+            search.pending.sort_by(|x, y| y.q.cmp(&x.q));
+        }
+        search.pending.pop()
+    }
+
+    /// Constrói a curva do candidato: acha uma raiz j de H_D módulo _n_, percorre os twists da curva com invariante j e, em cada um, busca um ponto P tal que U = [m/q]P ≠ O e [q]U = O. Retorna None se nenhum twist servir, o que só acontece se _n_ for composto.
+    pub fn prove<T: BigInteger>(
+        &mut self,
+        n: &T,
+        candidate: &Candidate<T>,
+    ) -> Option<EcppProof<T>> {
+        let disc = &self.discriminants[candidate.discriminant];
+        let poly = self
+            .polynomials
+            .entry(disc.d)
+            .or_insert_with(|| hilbert_class_polynomial(disc));
+        let field = Field { n };
+        let roots = SquareRoots::new(n)?;
+        let j = find_root(&field, reduce(poly, n))?;
+        let cofactor = candidate.order.clone() / candidate.q.clone();
+
+        for c in 1..=MAX_TWISTS {
+            let c = T::from_u64(c);
+            let (a, b) = match (j == T::zero(), j == T::from_u64(1728)) {
+                (true, _) => (T::zero(), c),
+                (_, true) => (c, T::zero()),
+                _ => {
+                    // k = j/(1728 - j), com a curva y² = x³ + 3kc²x + 2kc³
+                    let k = field.mul(&j, &field.sub(&T::from_u64(1728), &j).mod_inverse(n)?);
+                    let c2 = field.mul(&c, &c);
+                    (
+                        field.mul(&field.mul(&T::from_u64(3), &k), &c2),
+                        field.mul(&field.mul(&T::from_u64(2), &k), &field.mul(&c2, &c)),
+                    )
+                }
+            };
+            for x in 0..MAX_POINTS {
+                let x = T::from_u64(x);
+                let rhs = curve_rhs(&field, &a, &b, &x);
+                let y = match roots.sqrt(&rhs) {
+                    Some(y) if y != T::zero() => y,
+                    _ => continue,
+                };
+                let u = field.scalar_mul(&a, &cofactor, &x, &y);
+                let z_inv = match u.z.mod_inverse(n) {
+                    Some(z_inv) => z_inv,
+                    None => continue,
+                };
+                let z_inv2 = field.mul(&z_inv, &z_inv);
+                let proof = EcppProof {
+                    prime: n.clone(),
+                    a: a.clone(),
+                    b: b.clone(),
+                    order: candidate.q.clone(),
+                    x: field.mul(&u.x, &z_inv2),
+                    y: field.mul(&u.y, &field.mul(&z_inv2, &z_inv)),
+                };
+                match proof.point_has_order() && proof.valid_curve() {
+                    true => return Some(proof),
+                    // A ordem do ponto não divide m: o twist está errado
+                    false => break,
+                }
+            }
+        }
+        None
+    }
+
+    /// Retorna _m_ sem os fatores primos menores que 2^16
+    fn remove_small_factors<T: BigInteger>(&self, m: &T) -> T {
+        let mut q = m.clone();
+        for &p in &self.primes {
+            while q > T::one() && limb_rem(&q, p) == 0 {
+                q = q / T::from_u64(p);
+            }
+        }
+        q
+    }
+}
+
+/// Checa (⌊√q⌋ - 1)⁴ > n, que garante q > (n^(1/4) + 1)²
+fn order_is_large<T: BigInteger>(q: &T, n: &T) -> bool {
+    let root = q.isqrt();
+    if root <= T::one() {
+        return false;
+    }
+    (root - T::one()).pow(4) > *n
+}
+
+/// Calcula x³ + ax + b módulo n
+fn curve_rhs<T: BigInteger>(field: &Field<T>, a: &T, b: &T, x: &T) -> T {
+    let x3 = field.mul(x, &field.mul(x, x));
+    field.add(&field.add(&x3, &field.mul(a, x)), b)
+}
+
+/// Resto de _x_ por _r_ calculado diretamente sobre os limbs
+fn limb_rem<T: BigInteger>(x: &T, r: u64) -> u64 {
+    x.to_limbs()
+        .iter()
+        .rev()
+        .fold(0u128, |rem, limb| ((rem << 64) | *limb as u128) % r as u128) as u64
+}
+
+/// Símbolo de Kronecker (_d_/n) para d pequeno e n ímpar, pela reciprocidade quadrática
+fn kronecker<T: BigInteger>(d: i64, n: &T) -> i32 {
+    let n8 = limb_rem(n, 8);
+    let mut result = match d < 0 && n8 % 4 == 3 {
+        true => -1,
+        false => 1,
+    };
+    let mut odd = d.unsigned_abs();
+    while odd.is_multiple_of(2) {
+        odd /= 2;
+        if n8 == 3 || n8 == 5 {
+            result = -result;
+        }
+    }
+    // (o/n) = (n/o)·(-1)^((o-1)/2·(n-1)/2)
+    if odd % 4 == 3 && n8 % 4 == 3 {
+        result = -result;
+    }
+    result * jacobi_u64(limb_rem(n, odd), odd)
+}
+
+/// Símbolo de Jacobi (a/n) para n ímpar pequeno
+fn jacobi_u64(mut a: u64, mut n: u64) -> i32 {
+    let mut result = 1;
+    a %= n;
+    while a != 0 {
+        while a.is_multiple_of(2) {
+            a /= 2;
+            if n % 8 == 3 || n % 8 == 5 {
+                result = -result;
+            }
+        }
+        std::mem::swap(&mut a, &mut n);
+        if a % 4 == 3 && n % 4 == 3 {
+            result = -result;
+        }
+        a %= n;
+    }
+    match n {
+        1 => result,
+        _ => 0,
+    }
+}
+
+/// Algoritmo de Cornacchia modificado: resolve 4n = u² + |D|·v², usando a raiz quadrada de D módulo n
+fn cornacchia<T: BigInteger>(roots: &SquareRoots<T>, abs_d: u64) -> Option<(T, T)> {
+    let n = &roots.p;
+    let d = T::from_u64(abs_d);
+    let mut b = roots.sqrt(&(n.clone() - d.clone() % n.clone()))?;
+    if b.is_even() != abs_d.is_multiple_of(2) {
+        b = n.clone() - b;
+    }
+    let four_n = n.shl(2);
+    let limit = four_n.isqrt();
+    let mut a = n.shl(1);
+    while b > limit {
+        let r = a % b.clone();
+        a = b;
+        b = r;
+    }
+    let rest = four_n - b.clone() * b.clone();
+    if rest.clone() % d.clone() != T::zero() {
+        return None;
+    }
+    let c = rest / d;
+    let v = c.isqrt();
+    match v.clone() * v.clone() == c {
+        true => Some((b, v)),
+        false => None,
+    }
+}
+
+/// Raízes quadradas módulo um primo _p_ pelo algoritmo de Tonelli-Shanks, com a decomposição p - 1 = q·2^s e a potência c = z^q de um não resíduo z calculadas uma única vez
+struct SquareRoots<T> {
+    p: T,
+    s: u64,
+    q: T,
+    c: T,
+}
+
+impl<T: BigInteger> SquareRoots<T> {
+    /// Prepara as raízes módulo _p_, ou retorna None se nenhum não resíduo pequeno for encontrado (o que só acontece se _p_ for composto)
+    fn new(p: &T) -> Option<Self> {
+        let p1 = p.clone() - T::one();
+        let s = (0..).find(|i| p1.bit(*i)).unwrap();
+        let q = p1.shr(s);
+        // O não resíduo é achado pelo símbolo de Kronecker, sem exponenciações
+        let c = match s {
+            1 => T::zero(),
+            _ => {
+                let z = (2..MAX_TRIES as i64).find(|z| kronecker(*z, p) == -1)?;
+                T::from_u64(z as u64).modpow(&q, p)
+            }
+        };
+        Some(SquareRoots {
+            p: p.clone(),
+            s,
+            q,
+            c,
+        })
+    }
+
+    /// Raiz quadrada de _a_ módulo p, ou None se _a_ não for resíduo quadrático
+    fn sqrt(&self, a: &T) -> Option<T> {
+        let p = &self.p;
+        let field = Field { n: p };
+        let a = a.clone() % p.clone();
+        if a == T::zero() {
+            return Some(a);
+        }
+        // w = a^((q-1)/2), r = a^((q+1)/2) = w·a e t = a^q = w·r
+        let w = a.modpow(&self.q.shr(1), p);
+        let mut r = field.mul(&w, &a);
+        let mut t = field.mul(&w, &r);
+        let (mut m, mut c) = (self.s, self.c.clone());
+        while t != T::one() {
+            let mut i = 0;
+            let mut t2 = t.clone();
+            while t2 != T::one() {
+                t2 = field.mul(&t2, &t2);
+                i += 1;
+                if i == m {
+                    return None;
+                }
+            }
+            let mut b = c;
+            for _ in 0..m - i - 1 {
+                b = field.mul(&b, &b);
+            }
+            m = i;
+            c = field.mul(&b, &b);
+            t = field.mul(&t, &c);
+            r = field.mul(&r, &b);
+        }
+        Some(r)
+    }
+}
+
+/// Acha uma raiz do polinômio mônico _f_ (coeficientes do grau 0 ao mais alto) módulo n, que se decompõe em fatores lineares, pelo algoritmo de Cantor-Zassenhaus: mdc((x + δ)^((n-1)/2) - 1, f) separa as raízes em dois grupos, e a busca continua no menor deles
+fn find_root<T: BigInteger>(field: &Field<T>, mut f: Vec<T>) -> Option<T> {
+    let exp = (field.n.clone() - T::one()).shr(1);
+    while f.len() > 2 {
+        let mut split = None;
+        for delta in 0..MAX_TRIES {
+            let mut h = field.poly_powmod_linear(&T::from_u64(delta), &exp, &f);
+            if h.is_empty() {
+                h.push(T::zero());
+            }
+            h[0] = field.sub(&h[0], &T::one());
+            let g = field.poly_gcd(f.clone(), trim(h))?;
+            if g.len() > 1 && g.len() < f.len() {
+                let (cofactor, _) = field.poly_divrem(f.clone(), &g);
+                split = Some(match 2 * g.len() > f.len() + 1 {
+                    true => cofactor,
+                    false => g,
+                });
+                break;
+            }
+        }
+        f = split?;
+    }
+    match f.len() {
+        2 => Some(field.sub(&T::zero(), &f[0])),
+        _ => None,
+    }
+}
+
+/// Remove os coeficientes nulos do topo do polinômio
+fn trim<T: BigInteger>(mut f: Vec<T>) -> Vec<T> {
+    while f.last() == Some(&T::zero()) {
+        f.pop();
+    }
+    f
+}
+
+/// Ponto em coordenadas jacobianas (X : Y : Z), correspondente a (X/Z², Y/Z³); Z = 0 é o ponto no infinito
+struct Jacobian<T> {
+    x: T,
+    y: T,
+    z: T,
+}
+
+/// Aritmética módulo _n_, de inteiros, polinômios e pontos de curvas y² = x³ + ax + b
+struct Field<'a, T> {
+    n: &'a T,
+}
+
+impl<T: BigInteger> Field<'_, T> {
+    fn add(&self, a: &T, b: &T) -> T {
+        (a.clone() + b) % self.n
+    }
+
+    fn sub(&self, a: &T, b: &T) -> T {
+        (a.clone() + self.n - b) % self.n
+    }
+
+    fn mul(&self, a: &T, b: &T) -> T {
+        a.clone() * b % self.n
+    }
+
+    /// Dobra _p_ com as fórmulas dbl-2007-bl, sem casos especiais
+    fn double(&self, a: &T, p: &Jacobian<T>) -> Jacobian<T> {
+        let xx = self.mul(&p.x, &p.x);
+        let yy = self.mul(&p.y, &p.y);
+        let yyyy = self.mul(&yy, &yy);
+        let zz = self.mul(&p.z, &p.z);
+        let s = self.mul(&T::from_u64(4), &self.mul(&p.x, &yy));
+        let m = self.add(
+            &self.mul(&T::from_u64(3), &xx),
+            &self.mul(a, &self.mul(&zz, &zz)),
+        );
+        let x = self.sub(&self.mul(&m, &m), &self.add(&s, &s));
+        let y = self.sub(
+            &self.mul(&m, &self.sub(&s, &x)),
+            &self.mul(&T::from_u64(8), &yyyy),
+        );
+        let z = self.mul(&T::from_u64(2), &self.mul(&p.y, &p.z));
+        Jacobian { x, y, z }
+    }
+
+    /// Soma _p_ com o ponto afim (_x2_, _y2_) com as fórmulas madd-2007-bl, sem casos especiais
+    fn add_affine(&self, p: &Jacobian<T>, x2: &T, y2: &T) -> Jacobian<T> {
+        let z1z1 = self.mul(&p.z, &p.z);
+        let u2 = self.mul(x2, &z1z1);
+        let s2 = self.mul(y2, &self.mul(&p.z, &z1z1));
+        let h = self.sub(&u2, &p.x);
+        let r = self.sub(&s2, &p.y);
+        let hh = self.mul(&h, &h);
+        let hhh = self.mul(&h, &hh);
+        let v = self.mul(&p.x, &hh);
+        let x = self.sub(&self.sub(&self.mul(&r, &r), &hhh), &self.add(&v, &v));
+        let y = self.sub(&self.mul(&r, &self.sub(&v, &x)), &self.mul(&p.y, &hhh));
+        let z = self.mul(&p.z, &h);
+        Jacobian { x, y, z }
+    }
+
+    /// Calcula [k]·(x, y) na curva de coeficiente _a_, da esquerda para a direita a partir do bit mais significativo
+    fn scalar_mul(&self, a: &T, k: &T, x: &T, y: &T) -> Jacobian<T> {
+        let mut result = Jacobian {
+            x: x.clone(),
+            y: y.clone(),
+            z: T::one(),
+        };
+        for i in (0..k.bits().saturating_sub(1)).rev() {
+            result = self.double(a, &result);
+            if k.bit(i) {
+                result = self.add_affine(&result, x, y);
+            }
+        }
+        result
+    }
+
+    /// Quociente e resto de _a_ pelo polinômio mônico _f_. Os coeficientes são acumulados sem redução e reduzidos módulo n apenas ao sair do topo ou no fim.
+    fn poly_divrem(&self, mut a: Vec<T>, f: &[T]) -> (Vec<T>, Vec<T>) {
+        let deg = f.len() - 1;
+        let negated: Vec<T> = f[..deg]
+            .iter()
+            .map(|coef| self.sub(&T::zero(), coef))
+            .collect();
+        let mut quotient = Vec::new();
+        while a.len() > deg {
+            let lead = a.pop().unwrap() % self.n;
+            let shift = a.len() - deg;
+            for (i, coef) in negated.iter().enumerate() {
+                a[shift + i] = a[shift + i].clone() + lead.clone() * coef;
+            }
+            quotient.push(lead);
+        }
+        quotient.reverse();
+        let rem = a.into_iter().map(|coef| coef % self.n).collect();
+        (quotient, trim(rem))
+    }
+
+    /// Resto de _a_ pelo polinômio mônico _f_
+    fn poly_rem(&self, a: Vec<T>, f: &[T]) -> Vec<T> {
+        self.poly_divrem(a, f).1
+    }
+
+    /// Quadrado de _a_ módulo o polinômio mônico _f_, somando cada produto cruzado uma única vez
+    fn poly_sqrmod(&self, a: &[T], f: &[T]) -> Vec<T> {
+        if a.is_empty() {
+            return Vec::new();
+        }
+        let mut product = vec![T::zero(); 2 * a.len() - 1];
+        for (i, x) in a.iter().enumerate() {
+            for (j, y) in a.iter().enumerate().skip(i + 1) {
+                product[i + j] = product[i + j].clone() + x.clone() * y;
+            }
+        }
+        let mut product: Vec<T> = product.into_iter().map(|coef| coef.shl(1)).collect();
+        for (i, x) in a.iter().enumerate() {
+            product[2 * i] = product[2 * i].clone() + x.clone() * x;
+        }
+        let product = product.into_iter().map(|coef| coef % self.n).collect();
+        self.poly_rem(product, f)
+    }
+
+    /// Calcula (x + _delta_)^_exp_ módulo o polinômio mônico _f_; a multiplicação pela base é só um deslocamento somado a um múltiplo
+    fn poly_powmod_linear(&self, delta: &T, exp: &T, f: &[T]) -> Vec<T> {
+        let mut result = vec![T::one()];
+        for i in (0..exp.bits()).rev() {
+            result = self.poly_sqrmod(&result, f);
+            if exp.bit(i) {
+                let mut shifted = vec![T::zero()];
+                shifted.extend(result.iter().cloned());
+                for (coef, r) in shifted.iter_mut().zip(&result) {
+                    *coef = self.add(coef, &self.mul(delta, r));
+                }
+                result = self.poly_rem(shifted, f);
+            }
+        }
+        result
+    }
+
+    /// Máximo divisor comum mônico de _a_ e _b_, ou None se algum coeficiente líder não for inversível módulo n
+    fn poly_gcd(&self, mut a: Vec<T>, mut b: Vec<T>) -> Option<Vec<T>> {
+        while !b.is_empty() {
+            let inv = b.last().unwrap().mod_inverse(self.n)?;
+            b = b.iter().map(|coef| self.mul(coef, &inv)).collect();
+            let r = self.poly_rem(a, &b);
+            a = b;
+            b = r;
+        }
+        let inv = a.last()?.mod_inverse(self.n)?;
+        Some(a.iter().map(|coef| self.mul(coef, &inv)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use super::*;
+
+    fn big(n: &str) -> BigUint {
+        n.parse().unwrap()
+    }
+
+    #[test]
+    fn cornacchia_finds_known_representations() {
+        // 4p = u² + |D|·v², única a menos de sinais para |D| > 4
+        let cases = [
+            (7, "11", Some(("4", "2"))),
+            (23, "59", Some(("12", "2"))),
+            (20, "41", Some(("12", "1"))),
+            (11, "47", Some(("12", "2"))),
+            (43, "17", Some(("5", "1"))),
+            (163, "10007", Some(("179", "7"))),
+            (
+                23,
+                "1000000000002000000082801",
+                Some(("2000000000002", "120")),
+            ),
+            (
+                163,
+                "1000000000000004000000000027551",
+                Some(("2000000000000004", "26")),
+            ),
+            // -7 não é resíduo módulo 13; -23 é resíduo módulo 47, mas 4·47 não tem a forma
+            (7, "13", None),
+            (23, "47", None),
+            (67, "1000003", None),
+        ];
+        for (abs_d, p, expected) in cases.iter() {
+            let roots = SquareRoots::new(&big(p)).unwrap();
+            assert_eq!(
+                cornacchia(&roots, *abs_d),
+                expected.map(|(u, v)| (big(u), big(v))),
+                "4·{} = u² + {}·v²",
+                p,
+                abs_d
+            );
+        }
+    }
+
+    #[test]
+    fn tonelli_shanks_handles_high_powers_of_two() {
+        // Todos ≡ 1 (mod 8); 998244353 - 1 = 119·2²³ e 2⁶⁴ - 2³² + 1 - 1 = (2³² - 1)·2³²
+        let primes = [
+            "17",
+            "41",
+            "73",
+            "97",
+            "113",
+            "998244353",
+            "18446744069414584321",
+        ];
+        for p in primes.iter() {
+            let p = big(p);
+            let roots = SquareRoots::new(&p).unwrap();
+            let exp = (p.clone() - BigUint::from(1u32)) >> 1;
+            for a in (0u64..200).chain([1 << 40, u64::MAX]) {
+                let a = BigUint::from(a) % p.clone();
+                let residue = a == BigUint::default() || a.modpow(&exp, &p) == BigUint::from(1u32);
+                match roots.sqrt(&a) {
+                    Some(r) => {
+                        assert!(residue, "{} mod {}", a, p);
+                        assert_eq!(r.clone() * r % p.clone(), a, "√{} mod {}", a, p);
+                    }
+                    None => assert!(!residue, "{} mod {}", a, p),
+                }
+            }
+        }
+    }
+}
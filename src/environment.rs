@@ -1,15 +1,70 @@
+use std::path::PathBuf;
 use std::process::exit;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use clap::{load_yaml, App};
+use clap::{load_yaml, App, ArgMatches};
 use env_logger::Env;
 use log::{error, info, warn};
-use num_bigint::{BigUint, ToBigUint};
+
+use crate::backend::{BigInteger, Int};
+use crate::chains::ChainKind;
+use crate::dsa::DSA_SIZES;
+use crate::factor::{FactorConfig, FactorMethod};
+use crate::functions::{PrimeConstraints, PrimeKind, Tester};
+use crate::pseudoprime::{Pseudoprimes, PSEUDOPRIME_MAX_BOUND};
+use crate::rsa::KeyFormat;
+use crate::special::SpecialForm;
+
+/// Operação escolhida para a execução
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Operation {
+    /// Gerar números aleatórios
+    Rng,
+    /// Gerar primos
+    Prime,
+    /// Gerar uma chave RSA com módulo de _bits_ bits e expoente público _e_, escrita em _output_ no arquivo _out_ ou na saída padrão
+    RsaKeygen {
+        bits: u64,
+        e: Int,
+        output: KeyOutput,
+        out: Option<PathBuf>,
+    },
+    /// Gerar parâmetros de domínio DSA/DH com p de _l_ bits, q de _n_ bits e gerador de índice _index_
+    DomainParameters { l: u64, n: u64, index: u8 },
+    /// Validar os parâmetros de domínio do arquivo _path_
+    ValidateDomainParameters { path: PathBuf },
+    /// Construir um certificado de primalidade para _n_
+    Certify { n: Int },
+    /// Verificar os certificados de primalidade do arquivo _path_
+    VerifyCertificate { path: PathBuf },
+    /// Fatorar os números _numbers_ com a configuração _config_
+    Factor {
+        numbers: Vec<Int>,
+        config: FactorConfig,
+    },
+    /// Testar a primalidade dos números _numbers_ com _tester_, mostrando o veredito de cada um
+    Test { numbers: Vec<Int>, tester: Tester },
+    /// Testar o número de forma especial _form_ com o teste determinístico da forma
+    Special { form: SpecialForm },
+    /// Listar os pseudoprimos da família _kind_ até _bound_ e aplicar os testes de Fermat e Miller-Rabin sobre eles
+    Pseudoprimes { kind: Pseudoprimes, bound: u64 },
+}
+
+/// Forma de escrita das chaves geradas
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyOutput {
+    /// Texto com todos os parâmetros em decimal
+    Text,
+    /// PEM no formato dado
+    Pem(KeyFormat),
+    /// DER no formato dado
+    Der(KeyFormat),
+}
 
 /// Estrutura contendo os argumentos já tratados e testados
 pub struct ParsedArgs {
-    /// Operação: true para gerar números aleatórios, false para gerar primos
-    pub op: bool,
+    /// Operação a ser executada
+    pub op: Operation,
     /// Número de bits para os valores gerados
     pub size: u64,
     /// Método: true para MLCG/Miller-Rabin, false para Lagged Fibonacci/Fermat (RNG/Checagem de primos)
@@ -17,7 +72,25 @@ pub struct ParsedArgs {
     /// Quantidade de números para gerar
     pub n: u64,
     /// Semente para todas as gerações aleatórias
-    pub seed: BigUint,
+    pub seed: Int,
+    /// Utiliza os testes de primalidade em tempo constante
+    pub constant_time: bool,
+    /// Tempo máximo para a busca de todos os primos
+    pub timeout: Option<Duration>,
+    /// Quantidade máxima de candidatos testados na busca de cada primo
+    pub max_candidates: Option<u64>,
+    /// Gera primos seguros (p = 2q + 1, com q primo)
+    pub safe: bool,
+    /// Gera primos fortes pelo algoritmo de Gordon
+    pub strong: bool,
+    /// Gera primos com prova de primalidade pelo método de Shawe-Taylor
+    pub provable: bool,
+    /// Imprime um certificado de primalidade para cada primo encontrado
+    pub certificate: bool,
+    /// Gera cadeias de Cunningham do tipo e com a quantidade de elementos dados
+    pub chain: Option<(ChainKind, usize)>,
+    /// Condições adicionais para os primos gerados
+    pub constraints: PrimeConstraints<Int>,
 }
 
 /// Inicialiaza o logging, recebe os argumentos de execução e testa se todos estão corretos
@@ -29,13 +102,520 @@ pub fn init() -> ParsedArgs {
     let yaml = load_yaml!("cli.yaml");
     let args = App::from(yaml).get_matches();
     let mut parsedargs = ParsedArgs {
-        op: true,
+        op: Operation::Rng,
         size: 256,
         method: true,
         n: 1,
-        seed: BigUint::default(),
+        seed: Int::zero(),
+        constant_time: false,
+        timeout: None,
+        max_candidates: None,
+        safe: false,
+        strong: false,
+        provable: false,
+        certificate: false,
+        chain: None,
+        constraints: PrimeConstraints::new(),
+    };
+
+    match args.subcommand() {
+        Some(("keygen", keygen)) => match keygen.subcommand() {
+            Some(("rsa", rsa)) => parsedargs.op = parse_rsa_keygen(rsa),
+            _ => {
+                error!("You need to select which key to generate: keygen rsa");
+                exit(1)
+            }
+        },
+        Some(("dsa-params", params)) => parsedargs.op = parse_domain_parameters(params),
+        Some(("certify", certify)) => match certify.value_of("number").map(str::parse::<Int>) {
+            Some(Ok(n)) => {
+                info!("Certification of {} chosen", n);
+                parsedargs.op = Operation::Certify { n };
+            }
+            _ => {
+                error!("Error trying to parse the number to certify");
+                exit(1)
+            }
+        },
+        Some(("verify-cert", verify)) => {
+            let path = PathBuf::from(verify.value_of("file").unwrap());
+            info!(
+                "Verification of the certificates in {} chosen",
+                path.display()
+            );
+            parsedargs.op = Operation::VerifyCertificate { path };
+        }
+        Some(("factor", factor)) => parsedargs.op = parse_factor(factor),
+        Some(("test", test)) => parsedargs.op = parse_test(test),
+        Some(("special", special)) => parsedargs.op = parse_special(special),
+        Some(("pseudoprimes", pseudo)) => parsedargs.op = parse_pseudoprimes(pseudo),
+        _ => parse_generation(&args, &mut parsedargs),
+    }
+
+    if args.is_present("constant-time") {
+        match parsedargs.op {
+            Operation::Prime => {
+                info!("Constant-time primality tests enabled");
+                parsedargs.constant_time = true;
+            }
+            _ => warn!("Constant-time flag only applies to prime generation; ignoring it"),
+        }
+    }
+
+    if args.is_present("safe") {
+        match (&parsedargs.op, parsedargs.size) {
+            (Operation::Prime, size) if size < 3 => {
+                error!("Safe primes need at least 3 bits");
+                exit(1)
+            }
+            (Operation::Prime, _) => {
+                info!("Safe prime generation enabled");
+                parsedargs.safe = true;
+            }
+            _ => warn!("Safe flag only applies to prime generation; ignoring it"),
+        }
+    }
+
+    if args.is_present("strong") {
+        match (&parsedargs.op, parsedargs.size) {
+            (Operation::Prime, size) if size < 64 => {
+                error!("Strong primes need at least 64 bits");
+                exit(1)
+            }
+            (Operation::Prime, _) => {
+                info!("Strong prime generation enabled");
+                parsedargs.strong = true;
+            }
+            _ => warn!("Strong flag only applies to prime generation; ignoring it"),
+        }
+    }
+
+    if args.is_present("provable") {
+        match (&parsedargs.op, parsedargs.size) {
+            (Operation::Prime, size) if size < 2 => {
+                error!("Provable primes need at least 2 bits");
+                exit(1)
+            }
+            (Operation::Prime, _) => {
+                info!("Provable prime generation enabled");
+                parsedargs.provable = true;
+            }
+            _ => warn!("Provable flag only applies to prime generation; ignoring it"),
+        }
+    }
+
+    if let Some(val) = args.value_of("chain") {
+        let parsed = match val.split_once(':') {
+            Some(("1", length)) => length.parse::<usize>().ok().map(|l| (ChainKind::First, l)),
+            Some(("2", length)) => length.parse::<usize>().ok().map(|l| (ChainKind::Second, l)),
+            _ => None,
+        };
+        match (&parsedargs.op, parsed) {
+            (Operation::Prime, Some(_)) if parsedargs.size < 3 => {
+                error!("Cunningham chains need at least 3 bits");
+                exit(1)
+            }
+            (Operation::Prime, Some((kind, length))) if length >= 1 => {
+                info!(
+                    "Cunningham chain generation enabled ({:?} kind, length {})",
+                    kind, length
+                );
+                parsedargs.chain = Some((kind, length));
+            }
+            (Operation::Prime, _) => {
+                error!("Error trying to parse chain: expected KIND:LENGTH with KIND 1 or 2 and LENGTH > 0");
+                exit(1)
+            }
+            _ => warn!("Chain flag only applies to prime generation; ignoring it"),
+        }
+    }
+
+    if args.is_present("certificate") {
+        match parsedargs.op {
+            Operation::Prime => {
+                info!("Primality certificates enabled");
+                parsedargs.certificate = true;
+            }
+            _ => warn!("Certificate flag only applies to prime generation; ignoring it"),
+        }
+    }
+
+    if args.is_present("blum") {
+        info!("Only accepting primes p ≡ 3 (mod 4)");
+        parsedargs.constraints = parsedargs.constraints.blum().unwrap();
+    }
+
+    if let Some(val) = args.value_of("residue") {
+        let parsed = match val.split_once(':') {
+            Some((a, m)) => a.parse::<Int>().ok().zip(m.parse::<Int>().ok()),
+            None => None,
+        };
+        match parsed {
+            Some((a, m)) if m > Int::zero() => {
+                info!("Only accepting primes p ≡ {} (mod {})", a, m);
+                match parsedargs.constraints.clone().residue(a, m) {
+                    Ok(constraints) => parsedargs.constraints = constraints,
+                    Err(err) => {
+                        error!("Invalid residue condition: {}", err);
+                        exit(1)
+                    }
+                }
+            }
+            _ => {
+                error!("Error trying to parse residue: expected A:M with M > 0");
+                exit(1)
+            }
+        }
+    }
+
+    if args.is_present("top-bits") {
+        info!("Setting the two most significant bits of the primes");
+        parsedargs.constraints = parsedargs.constraints.clone().top_two_bits();
+    }
+
+    if let Some(val) = args.value_of("coprime-e") {
+        match val.parse::<Int>() {
+            Ok(e) if e > Int::one() => {
+                info!("Only accepting primes p with gcd(p-1, {}) = 1", e);
+                parsedargs.constraints = parsedargs.constraints.clone().coprime_to(e);
+            }
+            _ => {
+                error!("Error trying to parse public exponent: it must be greater than 1");
+                exit(1)
+            }
+        }
+    }
+
+    // Sem essa checagem uma condição impossível faz a busca rodar para sempre
+    let prime_size = match parsedargs.op {
+        Operation::Prime => {
+            // Mesma precedência da escolha da busca em main
+            let kind = match (parsedargs.safe, parsedargs.strong, parsedargs.provable, parsedargs.chain) {
+                (_, _, _, Some((kind, length))) => PrimeKind::Chain(kind, length),
+                (_, _, true, _) => PrimeKind::Plain,
+                (_, true, _, _) => PrimeKind::Strong,
+                (true, _, _, _) => PrimeKind::Safe,
+                _ => PrimeKind::Plain,
+            };
+            Some((parsedargs.size, parsedargs.constraints.clone(), kind))
+        }
+        Operation::RsaKeygen { bits, ref e, .. } => Some((
+            bits / 2,
+            parsedargs.constraints.clone().top_two_bits().coprime_to(e.clone()),
+            PrimeKind::Plain,
+        )),
+        _ => None,
+    };
+    if let Some((size, constraints, kind)) = prime_size {
+        if let Err(err) = constraints.check(size, kind) {
+            error!("Unsatisfiable prime conditions: {}", err);
+            exit(1)
+        }
+    }
+
+    if let Some(val) = args.value_of("timeout") {
+        match val.parse::<f64>() {
+            Ok(secs) if secs > 0.0 => {
+                info!("Prime search timeout set to {}s", secs);
+                parsedargs.timeout = Some(Duration::from_secs_f64(secs));
+            }
+            _ => {
+                error!("Error trying to parse timeout: it must be a positive number of seconds");
+                exit(1)
+            }
+        }
+    }
+
+    if let Some(val) = args.value_of("max-candidates") {
+        match val.parse::<u64>() {
+            Ok(num) => {
+                info!("Maximum number of candidates per prime set to {}", num);
+                parsedargs.max_candidates = Some(num);
+            }
+            Err(_) => {
+                error!("Error trying to parse maximum number of candidates");
+                exit(1)
+            }
+        }
+    }
+
+    match args.is_present("seed") {
+        true => match args.value_of("seed") {
+            Some(val) => match val.parse::<Int>() {
+                Ok(num) => {
+                    info!("Seed successfully pasred! Value: {}", num);
+                    parsedargs.seed = num;
+                }
+                Err(_) => error!("Error trying to parse seed value"),
+            },
+            None => error!("Seed flag is used but no value is given"),
+        },
+        false => {
+            parsedargs.seed = Int::from_u64(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_millis() as u64,
+            );
+            warn!(
+                "No seed given, random used instead! Value: {}",
+                parsedargs.seed
+            );
+        }
+    }
+
+    parsedargs
+}
+
+/// Trata os argumentos do subcomando `keygen rsa`
+fn parse_rsa_keygen(args: &ArgMatches) -> Operation {
+    let bits = match args.value_of("bits") {
+        Some(val) => match val.parse::<u64>() {
+            Ok(num) if num >= 64 && num.is_multiple_of(2) => num,
+            _ => {
+                error!("Error trying to parse key size: it must be an even number of bits, at least 64");
+                exit(1)
+            }
+        },
+        None => {
+            warn!("No key size given: default is 2048 bits");
+            2048
+        }
+    };
+    let e = match args.value_of("e") {
+        Some(val) => match val.parse::<Int>() {
+            Ok(e) if e > Int::one() && !e.is_even() => e,
+            _ => {
+                error!("Error trying to parse public exponent: it must be odd and greater than 1");
+                exit(1)
+            }
+        },
+        None => Int::from_u64(65537),
+    };
+    let format = match args.value_of("format") {
+        Some("text") | None => None,
+        Some("pkcs1") => Some(KeyFormat::Pkcs1),
+        Some("pkcs8") => Some(KeyFormat::Pkcs8),
+        Some(_) => {
+            error!("Wrong format string, only text, pkcs1 or pkcs8 are valid");
+            exit(1)
+        }
+    };
+    let out = args.value_of("out").map(PathBuf::from);
+    let output = match (format, args.is_present("der"), &out) {
+        (None, false, _) => KeyOutput::Text,
+        (None, true, _) => {
+            error!("DER output needs a key format: add --format pkcs1 or --format pkcs8");
+            exit(1)
+        }
+        (Some(_), true, None) => {
+            error!("DER output is binary: add --out to write it to a file");
+            exit(1)
+        }
+        (Some(format), false, _) => KeyOutput::Pem(format),
+        (Some(format), true, _) => KeyOutput::Der(format),
+    };
+    info!("RSA key generation chosen with {} bits and e = {}", bits, e);
+    Operation::RsaKeygen {
+        bits,
+        e,
+        output,
+        out,
+    }
+}
+
+/// Trata os argumentos do subcomando `dsa-params`
+fn parse_domain_parameters(args: &ArgMatches) -> Operation {
+    if let Some(path) = args.value_of("check") {
+        info!("Validation of the domain parameters in {} chosen", path);
+        return Operation::ValidateDomainParameters {
+            path: PathBuf::from(path),
+        };
+    }
+    let parse_bits = |name: &str, default: u64| match args.value_of(name) {
+        Some(val) => match val.parse::<u64>() {
+            Ok(num) => num,
+            Err(_) => {
+                error!("Error trying to parse {}", name);
+                exit(1)
+            }
+        },
+        None => default,
+    };
+    let (l, n) = (parse_bits("L", 2048), parse_bits("N", 256));
+    if !DSA_SIZES.contains(&(l, n)) {
+        error!("(L, N) must be one of (1024, 160), (2048, 224), (2048, 256) or (3072, 256)");
+        exit(1)
+    }
+    let index = match args.value_of("index").map(str::parse::<u8>) {
+        Some(Ok(index)) => index,
+        Some(Err(_)) => {
+            error!("Error trying to parse index: it must be between 0 and 255");
+            exit(1)
+        }
+        None => 1,
+    };
+    info!(
+        "Domain parameter generation chosen with L = {} and N = {}",
+        l, n
+    );
+    Operation::DomainParameters { l, n, index }
+}
+
+/// Trata os argumentos do subcomando factor
+fn parse_factor(args: &ArgMatches) -> Operation {
+    let numbers: Option<Vec<Int>> = args
+        .values_of("numbers")
+        .and_then(|values| values.map(str::parse::<Int>).collect::<Result<_, _>>().ok());
+    let numbers = match numbers {
+        Some(numbers) if numbers.iter().all(|n| *n > Int::zero()) => numbers,
+        _ => {
+            error!("Error trying to parse the numbers to factor: they must be positive integers");
+            exit(1)
+        }
+    };
+    let method = match args.value_of("method") {
+        None | Some("all") => FactorMethod::All,
+        Some("rho") => FactorMethod::Rho,
+        Some("pm1") => FactorMethod::PMinus1,
+        Some("ecm") => FactorMethod::Ecm,
+        Some("siqs") => FactorMethod::Siqs,
+        Some(_) => {
+            error!("Factoring method must be rho, pm1, ecm, siqs or all");
+            exit(1)
+        }
+    };
+    let mut config = FactorConfig::new().method(method);
+    if let Some(val) = args.value_of("ecm") {
+        let parsed = match val.split_once(':') {
+            Some((b1, curves)) => b1.parse::<u64>().ok().zip(curves.parse::<u64>().ok()),
+            None => None,
+        };
+        match parsed {
+            Some((b1, curves)) if b1 >= 2 && curves > 0 => {
+                info!("ECM with B1 = {} on up to {} curves", b1, curves);
+                config = config.ecm_level(b1, curves);
+            }
+            _ => {
+                error!("Error trying to parse --ecm: it must be B1:CURVES, with B1 ≥ 2 and CURVES > 0");
+                exit(1)
+            }
+        }
+    }
+    info!(
+        "Factorization of {} numbers with {} chosen",
+        numbers.len(),
+        method.name()
+    );
+    Operation::Factor { numbers, config }
+}
+
+/// Trata os argumentos do subcomando test
+fn parse_test(args: &ArgMatches) -> Operation {
+    let numbers: Vec<Int> = match args
+        .values_of("numbers")
+        .and_then(|values| values.map(str::parse::<Int>).collect::<Result<_, _>>().ok())
+    {
+        Some(numbers) => numbers,
+        None => {
+            error!("Error trying to parse the numbers to test: they must be non-negative integers");
+            exit(1)
+        }
     };
+    let tester = match args.value_of("method") {
+        None | Some("m") => Tester::MillerRabin,
+        Some("f") => Tester::Fermat,
+        Some("s") => Tester::SolovayStrassen,
+        Some("a") => Tester::Aks,
+        Some(_) => {
+            error!("Test method must be m (Miller-Rabin), f (Fermat), s (Solovay-Strassen) or a (AKS)");
+            exit(1)
+        }
+    };
+    info!(
+        "Primality test of {} numbers with {} chosen",
+        numbers.len(),
+        tester.name()
+    );
+    Operation::Test { numbers, tester }
+}
+
+/// Trata os argumentos do subcomando special
+fn parse_special(args: &ArgMatches) -> Operation {
+    let exponent = match args.value_of("exponent").map(str::parse::<u32>) {
+        Some(Ok(exponent)) => exponent,
+        _ => {
+            error!("Error trying to parse the exponent: it must be a non-negative integer");
+            exit(1)
+        }
+    };
+    let k = match args.value_of("k").map(str::parse::<u64>) {
+        None => 1,
+        Some(Ok(k)) => k,
+        Some(Err(_)) => {
+            error!("Error trying to parse k: it must be a positive integer");
+            exit(1)
+        }
+    };
+    let form = match args.value_of("form") {
+        Some("mersenne") => SpecialForm::Mersenne { p: exponent },
+        Some("fermat") => SpecialForm::Fermat { m: exponent },
+        Some("proth") => SpecialForm::Proth { k, n: exponent },
+        Some("riesel") => SpecialForm::Riesel { k, n: exponent },
+        _ => {
+            error!("Form must be mersenne, fermat, proth or riesel");
+            exit(1)
+        }
+    };
+    if !form.is_valid() {
+        error!(
+            "Invalid parameters for {}: p must be at least 2, m at most 30 and k odd with k < 2^n",
+            form
+        );
+        exit(1)
+    }
+    info!("{} test of {} chosen", form.test_name(), form);
+    Operation::Special { form }
+}
+
+/// Trata os argumentos do subcomando pseudoprimes
+fn parse_pseudoprimes(args: &ArgMatches) -> Operation {
+    let bound = match args.value_of("bound").map(str::parse::<u64>) {
+        Some(Ok(bound)) if bound <= PSEUDOPRIME_MAX_BOUND => bound,
+        _ => {
+            error!(
+                "Error trying to parse the bound: it must be a non-negative integer up to {}",
+                PSEUDOPRIME_MAX_BOUND
+            );
+            exit(1)
+        }
+    };
+    let base = match args.value_of("base").map(str::parse::<u64>) {
+        None => 2,
+        Some(Ok(base)) if base >= 2 => base,
+        _ => {
+            error!("Error trying to parse the base: it must be an integer greater than 1");
+            exit(1)
+        }
+    };
+    let kind = match args.value_of("kind") {
+        Some("fermat") => Pseudoprimes::Fermat { base },
+        Some("strong") => Pseudoprimes::Strong { base },
+        Some("carmichael") => Pseudoprimes::Carmichael,
+        _ => {
+            error!("Kind must be fermat, strong or carmichael");
+            exit(1)
+        }
+    };
+    if kind == Pseudoprimes::Carmichael && args.is_present("base") {
+        warn!("Carmichael numbers do not depend on a base; ignoring it");
+    }
+    info!("Search for {} up to {} chosen", kind, bound);
+    Operation::Pseudoprimes { kind, bound }
+}
 
+/// Trata os argumentos das operações de geração de números aleatórios e de primos
+fn parse_generation(args: &ArgMatches, parsedargs: &mut ParsedArgs) {
     let gen_flags = (args.is_present("rng"), args.is_present("prime"));
 
     match gen_flags {
@@ -69,7 +649,7 @@ pub fn init() -> ParsedArgs {
                         },
                         _ => warn!("Wrong method string, only m or f are valid; using Miller-Rabin as default")
                     }
-                    parsedargs.op = false;
+                    parsedargs.op = Operation::Prime;
                 }
                 None => warn!("No method string set; using Miller-Rabin as default"),
             }
@@ -103,31 +683,4 @@ pub fn init() -> ParsedArgs {
         },
         None => warn!("No number of operations given: default is 1"),
     }
-
-    match args.is_present("seed") {
-        true => match args.value_of("seed") {
-            Some(val) => match val.parse::<BigUint>() {
-                Ok(num) => {
-                    info!("Seed successfully pasred! Value: {}", num);
-                    parsedargs.seed = num;
-                }
-                Err(_) => error!("Error trying to parse seed value"),
-            },
-            None => error!("Seed flag is used but no value is given"),
-        },
-        false => {
-            parsedargs.seed = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_millis()
-                .to_biguint()
-                .unwrap();
-            warn!(
-                "No seed given, random used instead! Value: {}",
-                parsedargs.seed
-            );
-        }
-    }
-
-    parsedargs
 }
@@ -0,0 +1,445 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Instant;
+
+use log::info;
+
+use crate::backend::BigInteger;
+use crate::ecm::ecm;
+use crate::functions::{small_primes, small_rem, Tester};
+use crate::rand_gen::Mlcg;
+use crate::siqs::{siqs, SIQS_MAX_DIGITS};
+
+/// Limite dos primos removidos por divisão por tentativa; cofatores menores que o quadrado dele são primos
+const TRIAL_DIVISION_BOUND: u64 = 1 << 16;
+
+/// Limite B1 do método p-1: acha p quando p-1 só tem fatores primos (e potências) até ele
+const P_MINUS_1_BOUND: u64 = 100_000;
+
+/// Quantidade máxima de iterações de cada tentativa do rho, suficiente para fatores de até uns 40 bits
+const RHO_ITERATIONS: u64 = 1 << 22;
+
+/// Quantidade de tentativas do rho, cada uma com uma constante c diferente
+const RHO_ATTEMPTS: u64 = 3;
+
+/// Quantidade máxima de iterações do rho quando ele é seguido pelo ECM, para achar rápido os fatores de até uns 32 bits
+const RHO_QUICK_ITERATIONS: u64 = 1 << 16;
+
+/// Níveis (B1, curvas) do ECM, para fatores de 15, 20 e 25 dígitos (tabela do GMP-ECM)
+const ECM_LEVELS: [(u64, u64); 3] = [(2_000, 25), (11_000, 90), (50_000, 300)];
+
+/// Quantidade de diferenças acumuladas em um produto antes de cada mdc no rho
+const RHO_BATCH: u64 = 128;
+
+/// Métodos usados para separar os cofatores compostos que sobram da divisão por tentativa
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FactorMethod {
+    /// p-1, rho curto, ECM e, para números de até 100 dígitos, SIQS, nessa ordem
+    All,
+    /// Apenas o rho de Pollard (Brent)
+    Rho,
+    /// Apenas o p-1 de Pollard
+    PMinus1,
+    /// Apenas o ECM de Lenstra
+    Ecm,
+    /// Apenas o crivo quadrático auto-inicializável
+    Siqs,
+}
+
+impl FactorMethod {
+    /// Retorna o nome do método.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FactorMethod::All => "p-1, rho, ECM and SIQS",
+            FactorMethod::Rho => "Pollard rho",
+            FactorMethod::PMinus1 => "Pollard p-1",
+            FactorMethod::Ecm => "ECM",
+            FactorMethod::Siqs => "SIQS",
+        }
+    }
+}
+
+/// Configuração da fatoração: método usado e níveis (B1, curvas) do ECM
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FactorConfig {
+    method: FactorMethod,
+    ecm_levels: Vec<(u64, u64)>,
+}
+
+impl Default for FactorConfig {
+    fn default() -> Self {
+        FactorConfig {
+            method: FactorMethod::All,
+            ecm_levels: ECM_LEVELS.to_vec(),
+        }
+    }
+}
+
+impl FactorConfig {
+    /// Constrói a configuração padrão: todos os métodos, com o ECM indo até fatores de 25 dígitos.
+    pub fn new() -> Self {
+        FactorConfig::default()
+    }
+
+    /// Usa apenas o método _method_ depois da divisão por tentativa.
+    pub fn method(mut self, method: FactorMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Troca os níveis padrão do ECM por um único nível com limite _b1_ e _curves_ curvas.
+    pub fn ecm_level(mut self, b1: u64, curves: u64) -> Self {
+        self.ecm_levels = vec![(b1, curves)];
+        self
+    }
+}
+
+/// Fatoração de um número: fatores primos prováveis com expoentes e, se algum método falhar, os cofatores compostos que sobraram
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Factorization<T> {
+    /// Fatores primos (prováveis, pelo testador usado) e seus expoentes, em ordem crescente
+    pub factors: Vec<(T, u32)>,
+    /// Cofatores compostos que nenhum método conseguiu separar
+    pub unfactored: Vec<T>,
+}
+
+impl<T: BigInteger> Factorization<T> {
+    /// Checa se o número foi fatorado por completo.
+    pub fn is_complete(&self) -> bool {
+        self.unfactored.is_empty()
+    }
+}
+
+impl<T: BigInteger> fmt::Display for Factorization<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut terms: Vec<String> = self
+            .factors
+            .iter()
+            .map(|(p, e)| match e {
+                1 => p.to_string(),
+                _ => format!("{}^{}", p, e),
+            })
+            .collect();
+        terms.extend(self.unfactored.iter().map(|c| format!("{} (composite)", c)));
+        match terms.is_empty() {
+            true => write!(f, "1"),
+            false => write!(f, "{}", terms.join(" · ")),
+        }
+    }
+}
+
+/// Fatora _n_ por completo: remove os primos menores que 2^16 por divisão por tentativa e separa os cofatores restantes com os métodos de _config_ (por padrão o p-1 de Pollard, o rho de Pollard na variante de Brent e o ECM), recursivamente, até todos passarem em _tester_. As constantes do rho e das curvas são geradas a partir de _seed_, então a fatoração é reproduzível. Cofatores que nenhum método consegue separar ficam em [Factorization::unfactored].
+pub fn factor<T: BigInteger>(
+    n: &T,
+    config: &FactorConfig,
+    tester: Tester,
+    seed: &T,
+) -> Factorization<T> {
+    info!("Factoring {} with {}", n, config.method.name());
+    let now = Instant::now();
+    let (small, rest) = trial_division(n, TRIAL_DIVISION_BOUND);
+    let mut factors: BTreeMap<T, u32> = small
+        .into_iter()
+        .map(|(p, e)| (T::from_u64(p), e))
+        .collect();
+    let mut unfactored = Vec::new();
+    let mut seed_gen: Mlcg<T> = Mlcg::new_std(64, seed);
+    let bound_squared = T::from_u64(TRIAL_DIVISION_BOUND).pow(2);
+
+    let mut pending = vec![rest];
+    while let Some(m) = pending.pop() {
+        if m == T::one() {
+            continue;
+        }
+        if m < bound_squared || tester.test(&m, seed) {
+            *factors.entry(m).or_insert(0) += 1;
+            continue;
+        }
+        if let Some((root, k)) = perfect_power(&m) {
+            pending.extend(std::iter::repeat_n(root, k as usize));
+            continue;
+        }
+        match split(&m, config, &mut seed_gen) {
+            Some(d) => {
+                pending.push(m / d.clone());
+                pending.push(d);
+            }
+            None => unfactored.push(m),
+        }
+    }
+
+    info!(
+        "Factorization finished in {:.4}s: {} prime factors and {} unfactored composites",
+        now.elapsed().as_secs_f32(),
+        factors.len(),
+        unfactored.len()
+    );
+    Factorization {
+        factors: factors.into_iter().collect(),
+        unfactored,
+    }
+}
+
+/// Remove de _n_ os primos menores que _bound_, retornando os primos encontrados com seus expoentes e o cofator que sobrou
+pub fn trial_division<T: BigInteger>(n: &T, bound: u64) -> (Vec<(u64, u32)>, T) {
+    let mut rest = n.clone();
+    let mut found = Vec::new();
+    for p in small_primes(bound) {
+        if rest == T::one() {
+            break;
+        }
+        let mut exponent = 0;
+        while small_rem(&rest, p) == 0 {
+            rest = rest / T::from_u64(p);
+            exponent += 1;
+        }
+        if exponent > 0 {
+            found.push((p, exponent));
+        }
+    }
+    (found, rest)
+}
+
+/// Método p-1 de Pollard (estágio 1): calcula a = 3^M mod _n_, com M o produto das maiores potências de cada primo até _bound_, e retorna mdc(a - 1, n) se for um fator próprio. Acha um fator primo p quando p-1 é _bound_-potência-suave. A base 3 evita a falha da base 2 nos números de Fermat e de Mersenne, em que a ordem de 2 é a mesma módulo todos os fatores.
+pub fn pollard_p_minus_1<T: BigInteger>(n: &T, bound: u64) -> Option<T> {
+    let primes = small_primes(bound + 1);
+    let power = |p: u64| {
+        let mut power = p;
+        while power <= bound / p {
+            power *= p;
+        }
+        power
+    };
+    let base = T::from_u64(3) % n.clone();
+    let mut a = base.clone();
+    for &p in &primes {
+        a = a.modpow(&T::from_u64(power(p)), n);
+    }
+    let g = (a.clone() + n.clone() - T::one()).gcd(n);
+    if g > T::one() && g < *n {
+        return Some(g);
+    }
+    if g != *n {
+        return None;
+    }
+    // Todos os fatores apareceram juntos: refaz multiplicando o expoente por um primo de cada vez
+    let mut a = base;
+    for &p in &primes {
+        let exponent = T::from_u64(p);
+        let mut power = 1;
+        while power <= bound / p {
+            power *= p;
+            a = a.modpow(&exponent, n);
+            let g = (a.clone() + n.clone() - T::one()).gcd(n);
+            if g > T::one() {
+                return match g < *n {
+                    true => Some(g),
+                    false => None,
+                };
+            }
+        }
+    }
+    None
+}
+
+/// Rho de Pollard na variante de Brent: itera x ↦ x² + _c_ mod _n_ a partir de _x0_, procurando um ciclo módulo um fator de n pelo mdc com o produto acumulado das diferenças. Retorna um fator próprio, ou None se o ciclo fechar módulo n ou se passar de _iterations_ iterações.
+pub fn pollard_rho<T: BigInteger>(n: &T, c: &T, x0: &T, iterations: u64) -> Option<T> {
+    let f = |x: &T| (x.clone() * x.clone() + c.clone()) % n.clone();
+    let diff = |x: &T, y: &T| (x.clone() + n.clone() - y.clone()) % n.clone();
+    let (mut y, mut r, mut q, mut g) = (x0.clone() % n.clone(), 1u64, T::one(), T::one());
+    let (mut x, mut ys) = (y.clone(), y.clone());
+    while g == T::one() {
+        x = y.clone();
+        for _ in 0..r {
+            y = f(&y);
+        }
+        let mut k = 0;
+        while k < r && g == T::one() {
+            ys = y.clone();
+            for _ in 0..RHO_BATCH.min(r - k) {
+                y = f(&y);
+                q = q * diff(&x, &y) % n.clone();
+            }
+            g = q.gcd(n);
+            k += RHO_BATCH;
+        }
+        r *= 2;
+        if g == T::one() && r > iterations {
+            return None;
+        }
+    }
+    if g == *n {
+        // O produto do lote zerou: refaz o lote uma diferença por vez
+        loop {
+            ys = f(&ys);
+            g = diff(&x, &ys).gcd(n);
+            if g > T::one() {
+                break;
+            }
+        }
+    }
+    match g < *n {
+        true => Some(g),
+        false => None,
+    }
+}
+
+/// Checa se _m_ é uma potência perfeita r^k com k > 1, retornando r e k. Basta testar os expoentes primos até log₂ _m_, já que r^(ab) = (r^a)^b; a raiz de uma potência de expoente composto é separada de novo pela fatoração.
+pub fn perfect_power<T: BigInteger>(m: &T) -> Option<(T, u32)> {
+    if *m < T::from_u64(4) {
+        return None;
+    }
+    small_primes(m.bits() + 1).into_iter().find_map(|k| {
+        let root = nth_root(m, k as u32);
+        match root.pow(k as u32) == *m {
+            true => Some((root, k as u32)),
+            false => None,
+        }
+    })
+}
+
+/// Retorna a raiz _k_-ésima inteira de _m_ (o maior r com r^k ≤ m), pelo método de Newton a partir de uma potência de 2 maior que a raiz
+fn nth_root<T: BigInteger>(m: &T, k: u32) -> T {
+    let k_big = T::from_u64(k as u64);
+    let mut x = T::one().shl(m.bits().div_ceil(k as u64));
+    loop {
+        let y = (x.clone() * T::from_u64(k as u64 - 1) + m.clone() / x.pow(k - 1)) / k_big.clone();
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+/// Acha um fator próprio do composto _m_, que não é uma potência perfeita, com os métodos de _config_ e constantes sorteadas por _seed_gen_
+fn split<T: BigInteger>(m: &T, config: &FactorConfig, seed_gen: &mut Mlcg<T>) -> Option<T> {
+    let mut rho = |iterations, attempts| {
+        (0..attempts).find_map(|_| {
+            let c = seed_gen.rand() % m.clone();
+            let x0 = seed_gen.rand() % m.clone();
+            pollard_rho(m, &c, &x0, iterations)
+        })
+    };
+    match config.method {
+        FactorMethod::Rho => rho(RHO_ITERATIONS, RHO_ATTEMPTS),
+        FactorMethod::PMinus1 => pollard_p_minus_1(m, P_MINUS_1_BOUND),
+        FactorMethod::Ecm => run_ecm(m, &config.ecm_levels, seed_gen),
+        FactorMethod::Siqs => siqs(m, &seed_gen.rand()),
+        FactorMethod::All => {
+            let found =
+                pollard_p_minus_1(m, P_MINUS_1_BOUND).or_else(|| rho(RHO_QUICK_ITERATIONS, 1));
+            // Até 90 dígitos o SIQS é mais rápido que os níveis altos do ECM, que só procuram fatores pequenos
+            match m.to_string().len() as u64 <= SIQS_MAX_DIGITS {
+                true => found
+                    .or_else(|| run_ecm(m, &config.ecm_levels[..1], seed_gen))
+                    .or_else(|| siqs(m, &seed_gen.rand())),
+                false => found.or_else(|| run_ecm(m, &config.ecm_levels, seed_gen)),
+            }
+        }
+    }
+}
+
+/// Roda o ECM em cada nível (B1, curvas) de _levels_ até achar um fator
+fn run_ecm<T: BigInteger>(m: &T, levels: &[(u64, u64)], seed_gen: &mut Mlcg<T>) -> Option<T> {
+    levels
+        .iter()
+        .find_map(|&(b1, curves)| ecm(m, b1, curves, seed_gen))
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use super::*;
+
+    fn big(n: &str) -> BigUint {
+        n.parse().unwrap()
+    }
+
+    fn factors(n: &str, method: FactorMethod) -> Factorization<BigUint> {
+        let config = FactorConfig::new().method(method);
+        factor(
+            &big(n),
+            &config,
+            Tester::MillerRabin,
+            &BigUint::from(5429u32),
+        )
+    }
+
+    #[test]
+    fn detects_perfect_powers() {
+        let p = big("100000000000000000039");
+        let q = big("1000000000000000003");
+        for k in [2, 3, 5, 7] {
+            assert_eq!(perfect_power(&p.pow(k)), Some((p.clone(), k)));
+        }
+        // p⁴ = (p²)², e p² é separado de novo
+        assert_eq!(perfect_power(&p.pow(4)), Some((p.pow(2), 2)));
+        assert_eq!(perfect_power(&(p.pow(2) * q.clone())), None);
+        assert_eq!(perfect_power(&(p.pow(3) + BigUint::from(1u32))), None);
+        // 2^12 tem a raiz quadrada 2^6, que é separada de novo
+        assert_eq!(
+            perfect_power(&BigUint::from(4096u32)),
+            Some((BigUint::from(64u32), 2))
+        );
+        assert_eq!(perfect_power(&BigUint::from(3u32)), None);
+    }
+
+    #[test]
+    fn factors_prime_powers_with_every_method() {
+        let p = big("100000000000000000039");
+        let methods = [
+            FactorMethod::All,
+            FactorMethod::Rho,
+            FactorMethod::PMinus1,
+            FactorMethod::Ecm,
+            FactorMethod::Siqs,
+        ];
+        for method in methods.iter() {
+            // Nenhum dos métodos separa uma potência de primo: só a checagem de potência perfeita fatora p³
+            let cube = factors(&p.pow(3).to_string(), *method);
+            assert_eq!(cube.factors, vec![(p.clone(), 3)], "{}", method.name());
+            assert!(cube.is_complete());
+        }
+        // p²·q não é potência perfeita, e o rho separa q, de 30 bits
+        let q = big("1000000007");
+        let mixed = factors(&(p.pow(2) * q.clone()).to_string(), FactorMethod::All);
+        assert_eq!(mixed.factors, vec![(q, 1), (p, 2)]);
+        assert!(mixed.is_complete());
+    }
+
+    #[test]
+    fn factors_known_semiprimes() {
+        let cases = [
+            // Fatores de 30 e 40 bits, ao alcance do rho
+            (FactorMethod::Rho, "1000000007039000000273", "1000000007"),
+            // 8608456956238879741 - 1 = 2²·3·5·7²·11·13·...·47 é suave
+            (
+                FactorMethod::PMinus1,
+                "86084569562388797849031304768182866791",
+                "8608456956238879741",
+            ),
+            (
+                FactorMethod::Ecm,
+                "100000000003900000039000000001521",
+                "1000000000039",
+            ),
+            (
+                FactorMethod::Siqs,
+                "3000000000000148000000000001369",
+                "1000000000000037",
+            ),
+        ];
+        for (method, n, p) in cases.iter() {
+            let n = big(n);
+            let p = big(p);
+            let q = n.clone() / p.clone();
+            let found = factors(&n.to_string(), *method);
+            assert_eq!(found.factors, vec![(p, 1), (q, 1)], "{}", method.name());
+            assert_eq!(
+                found.to_string(),
+                format!("{} · {}", found.factors[0].0, found.factors[1].0)
+            );
+        }
+    }
+}
@@ -1,22 +1,549 @@
-use std::time::Instant;
+use std::f64::consts::LN_2;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use log::info;
-use num_bigint::BigUint;
 
+use crate::backend::BigInteger;
+use crate::chains::ChainKind;
 use crate::prime_test::*;
 use crate::rand_gen::*;
 
+/// Limite dos primos pequenos utilizados no crivo da busca por primos seguros
+const SAFE_PRIME_SIEVE_LIMIT: u64 = 2048;
+
+/// Teste de primalidade utilizado na busca por primos
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tester {
+    /// [miller_rabin_tester]
+    MillerRabin,
+    /// [fermat_tester]
+    Fermat,
+    /// [miller_rabin_tester_ct]
+    MillerRabinCt,
+    /// [fermat_tester_ct]
+    FermatCt,
+    /// [solovay_strassen_tester]
+    SolovayStrassen,
+    /// [aks_tester], determinístico e lento demais para a busca por primos grandes
+    Aks,
+}
+
+impl Tester {
+    /// Retorna o nome do teste, utilizado nos logs.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tester::MillerRabin => "Miller_rabin",
+            Tester::Fermat => "Fermat",
+            Tester::MillerRabinCt => "constant-time Miller_rabin",
+            Tester::FermatCt => "constant-time Fermat",
+            Tester::SolovayStrassen => "Solovay-Strassen",
+            Tester::Aks => "AKS",
+        }
+    }
+
+    /// Aplica o teste sobre _num_, usando _seed_ para a geração das testemunhas.
+    pub fn test<T: BigInteger>(&self, num: &T, seed: &T) -> bool {
+        match self {
+            Tester::MillerRabin => miller_rabin_tester(num, seed),
+            Tester::Fermat => fermat_tester(num, seed),
+            Tester::MillerRabinCt => miller_rabin_tester_ct(num, seed),
+            Tester::FermatCt => fermat_tester_ct(num, seed),
+            Tester::SolovayStrassen => solovay_strassen_tester(num, seed),
+            Tester::Aks => aks_tester(num),
+        }
+    }
+
+    /// Aplica o teste sobre _num_ com as mesmas testemunhas de [Tester::test], retornando o [Verdict] com o motivo de um composto ser rejeitado. As variantes de tempo constante usam a versão comum do teste, já que o veredito expõe as testemunhas.
+    pub fn verdict<T: BigInteger>(&self, num: &T, seed: &T) -> Verdict<T> {
+        match self {
+            Tester::MillerRabin | Tester::MillerRabinCt => miller_rabin_verdict(num, seed),
+            Tester::Fermat | Tester::FermatCt => fermat_verdict(num, seed),
+            Tester::SolovayStrassen => solovay_strassen_verdict(num, seed),
+            Tester::Aks => aks_verdict(num),
+        }
+    }
+}
+
+/// Sinal de cancelamento compartilhável entre threads, checado pela busca entre um candidato e outro
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Constrói um novo sinal, ainda não cancelado.
+    pub fn new() -> Self {
+        CancelToken::default()
+    }
+
+    /// Solicita o cancelamento de todas as buscas que utilizam este sinal.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Checa se o cancelamento foi solicitado.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Limites de uma busca por primos. Por padrão a busca não tem limites.
+#[derive(Clone, Debug, Default)]
+pub struct SearchLimits {
+    /// Instante a partir do qual a busca é interrompida
+    deadline: Option<Instant>,
+    /// Quantidade máxima de candidatos testados
+    max_candidates: Option<u64>,
+    /// Sinal de cancelamento externo
+    cancel: Option<CancelToken>,
+}
+
+impl SearchLimits {
+    /// Constrói limites vazios, equivalentes a uma busca sem limites.
+    pub fn new() -> Self {
+        SearchLimits::default()
+    }
+
+    /// Interrompe a busca ao atingir o instante _deadline_.
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Interrompe a busca após _timeout_ a partir de agora.
+    pub fn timeout(self, timeout: Duration) -> Self {
+        self.deadline(Instant::now() + timeout)
+    }
+
+    /// Interrompe a busca após testar _max_ candidatos.
+    pub fn max_candidates(mut self, max: u64) -> Self {
+        self.max_candidates = Some(max);
+        self
+    }
+
+    /// Interrompe a busca quando _token_ for cancelado.
+    pub fn cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Checa os limites antes de testar o próximo candidato, sendo _tested_ a quantidade já testada.
+    pub(crate) fn check(&self, tested: u64, start: Instant) -> Result<(), SearchError> {
+        if let Some(token) = &self.cancel {
+            if token.is_cancelled() {
+                return Err(SearchError::Cancelled { candidates: tested });
+            }
+        }
+        if let Some(max) = self.max_candidates {
+            if tested >= max {
+                return Err(SearchError::MaxCandidates { candidates: tested });
+            }
+        }
+        if let Some(deadline) = self.deadline {
+            if Instant::now() >= deadline {
+                return Err(SearchError::Timeout {
+                    candidates: tested,
+                    elapsed: start.elapsed(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Motivo pelo qual uma busca terminou sem encontrar um primo
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SearchError {
+    /// O prazo da busca foi atingido
+    Timeout { candidates: u64, elapsed: Duration },
+    /// A quantidade máxima de candidatos foi testada
+    MaxCandidates { candidates: u64 },
+    /// A busca foi cancelada externamente
+    Cancelled { candidates: u64 },
+    /// As condições não podem ser satisfeitas por nenhum primo do tipo buscado
+    Unsatisfiable(ConstraintError),
+}
+
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchError::Timeout {
+                candidates,
+                elapsed,
+            } => write!(
+                f,
+                "Prime search timed out after {} checks in {:.4}s",
+                candidates,
+                elapsed.as_secs_f32()
+            ),
+            SearchError::MaxCandidates { candidates } => write!(
+                f,
+                "Prime search gave up after reaching the limit of {} checks",
+                candidates
+            ),
+            SearchError::Cancelled { candidates } => {
+                write!(f, "Prime search cancelled after {} checks", candidates)
+            }
+            SearchError::Unsatisfiable(err) => write!(f, "Unsatisfiable prime conditions: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for SearchError {}
+
+/// Motivo pelo qual um conjunto de [PrimeConstraints] não pode ser satisfeito
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConstraintError {
+    /// Duas condições de resíduo são incompatíveis
+    IncompatibleResidues,
+    /// p ≡ a (mod m) com mdc(a, m) > 1, que só admite o próprio mdc como primo
+    NotCoprime,
+    /// O módulo combinado não é menor que a quantidade de candidatos com _size_ bits
+    ModulusTooLarge { size: u64 },
+    /// Toda solução de p ≡ a (mod m) tem p-1 com um fator em comum com o expoente público
+    ExponentNotCoprime,
+    /// O resíduo força um fator comum com m em algum dos primos que acompanham p (q = (p-1)/2 de um primo seguro ou os demais elementos de uma cadeia)
+    IncompatibleKind,
+    /// O expoente público é par, e então divide todo p-1, ou não é maior que 1
+    InvalidExponent,
+}
+
+impl fmt::Display for ConstraintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstraintError::IncompatibleResidues => {
+                write!(f, "the residue conditions are incompatible")
+            }
+            ConstraintError::NotCoprime => {
+                write!(f, "the residue and the modulus must be coprime")
+            }
+            ConstraintError::ModulusTooLarge { size } => write!(
+                f,
+                "the modulus leaves no room for candidates of {} bits",
+                size
+            ),
+            ConstraintError::ExponentNotCoprime => write!(
+                f,
+                "every prime with this residue has p-1 sharing a factor with the public exponent"
+            ),
+            ConstraintError::IncompatibleKind => write!(
+                f,
+                "no prime of the requested kind satisfies the residue condition"
+            ),
+            ConstraintError::InvalidExponent => {
+                write!(f, "the public exponent must be odd and greater than 1")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConstraintError {}
+
+/// Tipo de primo buscado, usado por [PrimeConstraints::check] para descartar resíduos que nenhum primo desse tipo satisfaz
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrimeKind {
+    /// Primo sem estrutura adicional, como os das buscas comuns e do Shawe-Taylor
+    Plain,
+    /// Primo seguro p = 2q + 1
+    Safe,
+    /// Primo forte de Gordon: os fatores r e s têm cerca de metade do tamanho de p e não restringem os resíduos módulo m
+    Strong,
+    /// Primeiro elemento de uma cadeia de Cunningham do tipo e comprimento dados
+    Chain(ChainKind, usize),
+}
+
+/// Condições adicionais para os primos encontrados pelas buscas, montadas no estilo builder. Por padrão não há nenhuma condição além do bit mais significativo ligado, que [Mlcg::rand] já garante.
+#[derive(Clone, Debug)]
+pub struct PrimeConstraints<T> {
+    /// Condição p ≡ a (mod m), guardada como (a, m)
+    residue: Option<(T, T)>,
+    /// Exige os dois bits mais significativos ligados, para que o produto de dois primos de _size_ bits tenha exatamente 2·_size_ bits
+    top_two_bits: bool,
+    /// Expoente público _e_ com o qual p-1 precisa ser coprimo
+    public_exponent: Option<T>,
+}
+
+impl<T: BigInteger> Default for PrimeConstraints<T> {
+    fn default() -> Self {
+        PrimeConstraints {
+            residue: None,
+            top_two_bits: false,
+            public_exponent: None,
+        }
+    }
+}
+
+impl<T: BigInteger> PrimeConstraints<T> {
+    /// Constrói um conjunto vazio de condições.
+    pub fn new() -> Self {
+        PrimeConstraints::default()
+    }
+
+    /// Exige p ≡ 3 (mod 4), ou seja, um primo de Blum. Retorna um erro caso seja incompatível com a condição de resíduo já configurada.
+    pub fn blum(self) -> Result<Self, ConstraintError> {
+        self.residue(T::from_u64(3), T::from_u64(4))
+    }
+
+    /// Exige p ≡ _a_ (mod _m_), com mdc(_a_, _m_) = 1 (senão nenhum primo maior que o mdc satisfaz a condição). Caso já exista uma condição de resíduo, as duas são combinadas pelo teorema chinês do resto, retornando um erro quando são incompatíveis.
+    pub fn residue(mut self, a: T, m: T) -> Result<Self, ConstraintError> {
+        let a = a % &m;
+        if a.gcd(&m) != T::one() {
+            return Err(ConstraintError::NotCoprime);
+        }
+        self.residue = match self.residue.take() {
+            None => Some((a, m)),
+            Some((a1, m1)) => Some(
+                combine_residues(&a1, &m1, &a, &m).ok_or(ConstraintError::IncompatibleResidues)?,
+            ),
+        };
+        Ok(self)
+    }
+
+    /// Checa se as condições podem ser satisfeitas por primos do tipo _kind_ com _size_ bits. O módulo do resíduo precisa ser menor que a quantidade de candidatos (2^(size-1), ou 2^(size-2) com os dois bits mais significativos ligados, e metade disso para primos seguros, em que é q que percorre o intervalo), para que todos os primos envolvidos sejam maiores que m. Assim cada um deles precisa ser coprimo com m: o resíduo não pode forçar um fator comum entre m e q = (p-1)/2 ou os demais elementos de uma cadeia, nem entre p-1 e o expoente público. Um expoente público par também é recusado, já que divide todo p-1 de um primo ímpar. Sem essa checagem a busca não termina.
+    pub fn check(&self, size: u64, kind: PrimeKind) -> Result<(), ConstraintError> {
+        if let Some(e) = &self.public_exponent {
+            if *e <= T::one() || e.is_even() {
+                return Err(ConstraintError::InvalidExponent);
+            }
+        }
+        let (a, m) = match &self.residue {
+            Some(residue) => residue,
+            None => return Ok(()),
+        };
+        let mut free_bits = match self.top_two_bits {
+            true => size.saturating_sub(2),
+            false => size.saturating_sub(1),
+        };
+        if kind == PrimeKind::Safe {
+            free_bits = free_bits.saturating_sub(1);
+        }
+        if *m >= T::one().shl(free_bits) {
+            return Err(ConstraintError::ModulusTooLarge { size });
+        }
+        let coprime = |x: T| x.gcd(m) == T::one();
+        let members_ok = match kind {
+            PrimeKind::Plain | PrimeKind::Strong => true,
+            // p-1 = 2q ≡ a-1 (mod m): mdc(a-1, m) divide 2q, e com q > m só pode ser 1 ou 2
+            PrimeKind::Safe => {
+                let g = (a.clone() + m - T::one()).gcd(m);
+                g == T::one() || g == T::from_u64(2)
+            }
+            PrimeKind::Chain(chain, length) => {
+                let mut member = a.clone();
+                (1..length).all(|_| {
+                    // Somar m evita o 0 - 1 do segundo tipo quando m = 1
+                    member = chain.next(&(member.clone() + m)) % m;
+                    coprime(member.clone())
+                })
+            }
+        };
+        if !members_ok {
+            return Err(ConstraintError::IncompatibleKind);
+        }
+        if let Some(e) = &self.public_exponent {
+            // p-1 ≡ a-1 (mod m), então mdc(a-1, m) divide p-1
+            let forced = (a.clone() + m - T::one()) % m;
+            if forced.gcd(m).gcd(e) != T::one() {
+                return Err(ConstraintError::ExponentNotCoprime);
+            }
+        }
+        Ok(())
+    }
+
+    /// Exige que os dois bits mais significativos estejam ligados.
+    pub fn top_two_bits(mut self) -> Self {
+        self.top_two_bits = true;
+        self
+    }
+
+    /// Exige gcd(p-1, _e_) = 1, para que _e_ possa ser usado como expoente público RSA.
+    pub fn coprime_to(mut self, e: T) -> Self {
+        self.public_exponent = Some(e);
+        self
+    }
+
+    /// Retorna a condição de resíduo combinada, caso exista.
+    pub fn residue_condition(&self) -> Option<&(T, T)> {
+        self.residue.as_ref()
+    }
+
+    /// Retorna o expoente público configurado, caso exista.
+    pub fn public_exponent(&self) -> Option<&T> {
+        self.public_exponent.as_ref()
+    }
+
+    /// Ajusta o candidato _candidate_ de _size_ bits para que satisfaça as condições de bits e de resíduo, quando possível sem mudar seu tamanho.
+    pub fn shape(&self, candidate: T, size: u64) -> T {
+        let mut c = candidate;
+        if self.top_two_bits && size >= 2 {
+            c = c | T::from_u64(3).shl(size - 2);
+        }
+        if let Some((a, m)) = &self.residue {
+            c = c.clone() - c % m.clone() + a.clone();
+            if c.bits() > size && c >= m.clone() {
+                c = c - m.clone();
+            }
+        }
+        c
+    }
+
+    /// Checa se _p_, de _size_ bits, satisfaz todas as condições, sem testar sua primalidade.
+    pub fn accepts(&self, p: &T, size: u64) -> bool {
+        let bits_ok = match self.top_two_bits && size >= 2 {
+            true => p.bits() == size && p.bit(size - 2),
+            false => p.bits() == size,
+        };
+        let residue_ok = match &self.residue {
+            Some((a, m)) => p.clone() % m.clone() == *a,
+            None => true,
+        };
+        let exponent_ok = match &self.public_exponent {
+            Some(e) => *p > T::zero() && (p.clone() - T::one()).gcd(e) == T::one(),
+            None => true,
+        };
+        bits_ok && residue_ok && exponent_ok
+    }
+}
+
+/// Combina as condições x ≡ _a1_ (mod _m1_) e x ≡ _a2_ (mod _m2_) em uma única condição módulo mmc(_m1_, _m2_), ou retorna None quando são incompatíveis
+pub fn combine_residues<T: BigInteger>(a1: &T, m1: &T, a2: &T, m2: &T) -> Option<(T, T)> {
+    let g = m1.gcd(m2);
+    if a1.clone() % g.clone() != a2.clone() % g.clone() {
+        return None;
+    }
+    let m2_g = m2.clone() / g.clone();
+    let lcm = m1.clone() * m2_g.clone();
+    // x = a1 + m1·k, com k ≡ (a2 - a1)/g · (m1/g)⁻¹ (mod m2/g)
+    let diff =
+        (a2.clone() % m2.clone() + m2.clone() - a1.clone() % m2.clone()) % m2.clone() / g.clone();
+    let inv = match m2_g == T::one() {
+        true => T::zero(),
+        false => (m1.clone() / g).mod_inverse(&m2_g)?,
+    };
+    let k = diff * inv % m2_g;
+    Some(((a1.clone() + m1.clone() * k) % lcm.clone(), lcm))
+}
+
+/// Estado de uma busca por primos em andamento, repassado aos observadores de progresso
+#[derive(Clone, Debug)]
+pub struct SearchProgress {
+    /// Tamanho em bits dos candidatos
+    pub size: u64,
+    /// Quantidade de candidatos testados até o momento
+    pub candidates: u64,
+    /// Quantidade esperada de candidatos testados até encontrar um primo
+    pub expected: f64,
+    /// Tempo decorrido desde o início da busca
+    pub elapsed: Duration,
+}
+
+impl SearchProgress {
+    /// Retorna a taxa de candidatos testados por segundo.
+    pub fn rate(&self) -> f64 {
+        self.candidates as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+
+    /// Retorna a quantidade esperada de candidatos até encontrar um primo.
+    pub fn expected_candidates(&self) -> f64 {
+        self.expected.max(1.0)
+    }
+
+    /// Estima o tempo restante até encontrar um primo, ou None caso a busca já tenha passado da quantidade esperada de candidatos.
+    pub fn estimated_remaining(&self) -> Option<Duration> {
+        let remaining = self.expected_candidates() - self.candidates as f64;
+        match remaining > 0.0 && self.candidates > 0 {
+            true => Some(Duration::from_secs_f64(remaining / self.rate())),
+            false => None,
+        }
+    }
+}
+
+/// Observador notificado a cada candidato testado durante a busca por primos, para exibir ou exportar métricas
+pub trait ProgressObserver {
+    /// Chamado após cada candidato testado.
+    fn on_progress(&mut self, progress: &SearchProgress);
+
+    /// Chamado uma vez ao final da busca, com _found_ indicando se um primo foi encontrado.
+    fn on_finish(&mut self, _progress: &SearchProgress, _found: bool) {}
+}
+
+/// Observador vazio, utilizado quando o progresso não é acompanhado
+impl ProgressObserver for () {
+    fn on_progress(&mut self, _progress: &SearchProgress) {}
+}
+
 /// Acha um provável número primo pelo teste de Fermat com o tamanho indicado com baseado na semente fornecida
-pub fn find_fermat(size: u64, seed: &BigUint) -> BigUint {
-    info!("Finding prime with {} bits with Fermat method", size);
+pub fn find_fermat<T: BigInteger>(size: u64, seed: &T) -> T {
+    find_unbounded(size, seed, Tester::Fermat)
+}
+
+/// Acha um provável número primo pelo teste de Miller-Rabin com o tamanho indicado com baseado na semente fornecida
+pub fn find_miller_rabin<T: BigInteger>(size: u64, seed: &T) -> T {
+    find_unbounded(size, seed, Tester::MillerRabin)
+}
+
+/// Versão de [find_fermat] que utiliza o teste de tempo constante, para primos que precisam permanecer secretos
+pub fn find_fermat_ct<T: BigInteger>(size: u64, seed: &T) -> T {
+    find_unbounded(size, seed, Tester::FermatCt)
+}
+
+/// Versão de [find_miller_rabin] que utiliza o teste de tempo constante, para primos que precisam permanecer secretos
+pub fn find_miller_rabin_ct<T: BigInteger>(size: u64, seed: &T) -> T {
+    find_unbounded(size, seed, Tester::MillerRabinCt)
+}
+
+/// Gera candidatos com o tamanho indicado a partir da semente fornecida até que um deles passe pelo teste _tester_ ou que algum dos limites _limits_ seja atingido
+pub fn find_prime<T: BigInteger>(
+    size: u64,
+    seed: &T,
+    tester: Tester,
+    limits: &SearchLimits,
+) -> Result<T, SearchError> {
+    find_prime_with_progress(
+        size,
+        seed,
+        tester,
+        &PrimeConstraints::new(),
+        limits,
+        &mut (),
+    )
+}
+
+/// Versão de [find_prime] que só aceita primos que satisfazem _constraints_ e notifica _observer_ a cada candidato testado
+pub fn find_prime_with_progress<T: BigInteger>(
+    size: u64,
+    seed: &T,
+    tester: Tester,
+    constraints: &PrimeConstraints<T>,
+    limits: &SearchLimits,
+    observer: &mut dyn ProgressObserver,
+) -> Result<T, SearchError> {
+    constraints
+        .check(size, PrimeKind::Plain)
+        .map_err(SearchError::Unsatisfiable)?;
+    info!(
+        "Finding prime with {} bits with {} method",
+        size,
+        tester.name()
+    );
     let now = Instant::now();
     let mut counter = 0;
-    let mut mlcg_gen: Mlcg = Mlcg::new_std(size, seed);
+    let mut mlcg_gen: Mlcg<T> = Mlcg::new_std(size, seed);
+    // Pelo teorema dos números primos a densidade de primos perto de 2^size é de aproximadamente 1/(size·ln 2)
+    let expected = size as f64 * LN_2;
 
     loop {
-        let num = mlcg_gen.rand();
+        if let Err(err) = limits.check(counter, now) {
+            observer.on_finish(&progress(size, expected, counter, now), false);
+            return Err(err);
+        }
+        let num = constraints.shape(mlcg_gen.rand(), size);
         counter += 1;
-        if fermat_tester(&num, seed) {
+        let found = constraints.accepts(&num, size) && tester.test(&num, seed);
+        observer.on_progress(&progress(size, expected, counter, now));
+        if found {
+            observer.on_finish(&progress(size, expected, counter, now), true);
             let elapsed_time = now.elapsed().as_secs_f32();
             info!(
                 "Found prime! Search stats: {} checks in {:.4}s ({:.4}s/check avg)",
@@ -24,30 +551,475 @@ pub fn find_fermat(size: u64, seed: &BigUint) -> BigUint {
                 elapsed_time,
                 elapsed_time / counter as f32
             );
-            return num;
+            return Ok(num);
         }
     }
 }
 
-/// Acha um provável número primo pelo teste de Miller-Rabin com o tamanho indicado com baseado na semente fornecida
-pub fn find_miller_rabin(size: u64, seed: &BigUint) -> BigUint {
-    info!("Finding prime with {} bits with Miller_rabin method", size);
+/// Acha um provável primo seguro p = 2q + 1, com q também primo, de _size_ bits a partir da semente fornecida, testando p e q com _tester_
+pub fn find_safe_prime<T: BigInteger>(
+    size: u64,
+    seed: &T,
+    tester: Tester,
+    limits: &SearchLimits,
+) -> Result<T, SearchError> {
+    find_safe_prime_with_progress(
+        size,
+        seed,
+        tester,
+        &PrimeConstraints::new(),
+        limits,
+        &mut (),
+    )
+}
+
+/// Versão de [find_safe_prime] que só aceita primos que satisfazem _constraints_ e notifica _observer_ a cada par de candidatos testado. Os valores de q são percorridos a partir de um ponto aleatório de 2 em 2, ou, com uma condição p ≡ a (mod m), apenas na classe de q ≡ (a-1)/2 que ela impõe, mantendo os restos de q pelos primos pequenos para descartar sem custo os candidatos em que p ou q tem um fator pequeno (q ≡ 0 ou q ≡ (r-1)/2 mod r).
+pub fn find_safe_prime_with_progress<T: BigInteger>(
+    size: u64,
+    seed: &T,
+    tester: Tester,
+    constraints: &PrimeConstraints<T>,
+    limits: &SearchLimits,
+    observer: &mut dyn ProgressObserver,
+) -> Result<T, SearchError> {
+    assert!(size >= 3, "Safe primes need at least 3 bits");
+    constraints
+        .check(size, PrimeKind::Safe)
+        .map_err(SearchError::Unsatisfiable)?;
+    info!(
+        "Finding safe prime with {} bits with {} method",
+        size,
+        tester.name()
+    );
     let now = Instant::now();
     let mut counter = 0;
-    let mut mlcg_gen: Mlcg = Mlcg::new_std(size, seed);
+    let mut mlcg_gen: Mlcg<T> = Mlcg::new_std(size - 1, seed);
+    // q é ímpar e, com p ≡ a (mod m), q ≡ (a-1)/2 (mod m) para m ímpar ou q ≡ (a-1)/2 (mod m/2) para m par
+    let two = T::from_u64(2);
+    let (q_res, step) = match constraints.residue_condition() {
+        None => (T::one(), two.clone()),
+        Some((a, m)) => {
+            let (q_a, q_m) = match small_rem(m, 2) {
+                1 => {
+                    let half = (m.clone() + T::one()) / two.clone();
+                    (
+                        (a.clone() + m.clone() - T::one()) * half % m.clone(),
+                        m.clone(),
+                    )
+                }
+                _ => {
+                    let half = m.clone() / two.clone();
+                    ((a.clone() - T::one()) / two.clone() % half.clone(), half)
+                }
+            };
+            combine_residues(&q_a, &q_m, &T::one(), &two).ok_or(SearchError::Unsatisfiable(
+                ConstraintError::IncompatibleKind,
+            ))?
+        }
+    };
+    let sieve: Vec<u64> = small_primes(SAFE_PRIME_SIEVE_LIMIT)
+        .into_iter()
+        .skip(1)
+        .filter(|r| 64 - r.leading_zeros() < (size - 1) as u32)
+        .collect();
+    let steps: Vec<u64> = sieve.iter().map(|r| small_rem(&step, *r)).collect();
+    // Para q ímpar, q e 2q + 1 são primos com probabilidade de aproximadamente 2C₂·(2/(size·ln 2))², e o crivo mantém apenas a fração ∏(1 - 2/r) dos candidatos; os primos que dividem o passo já são excluídos pelo resíduo
+    let survivors: f64 = sieve
+        .iter()
+        .zip(&steps)
+        .filter(|(_, step)| **step != 0)
+        .map(|(r, _)| 1.0 - 2.0 / *r as f64)
+        .product();
+    let expected = (size as f64 * LN_2).powi(2) / (4.0 * 1.32) * survivors;
+
+    let limit = T::one().shl(size - 1);
+    // Com os dois bits mais significativos de p ligados, os de q também precisam estar
+    let top = match constraints.top_two_bits {
+        true => T::from_u64(3).shl(size - 3),
+        false => T::zero(),
+    };
+    let draw = |q: T| {
+        let q = q.clone() - q % step.clone() + q_res.clone();
+        match q >= limit && q >= step {
+            true => q - step.clone(),
+            false => q,
+        }
+    };
+    let mut q = draw(mlcg_gen.rand() | top.clone());
+    let mut residues: Vec<u64> = sieve.iter().map(|r| small_rem(&q, *r)).collect();
 
     loop {
-        let num = mlcg_gen.rand();
+        if let Err(err) = limits.check(counter, now) {
+            observer.on_finish(&progress(size, expected, counter, now), false);
+            return Err(err);
+        }
+        if q >= limit {
+            q = draw(mlcg_gen.rand() | top.clone());
+            residues = sieve.iter().map(|r| small_rem(&q, *r)).collect();
+        }
+
+        let sieved = sieve
+            .iter()
+            .zip(&residues)
+            .all(|(r, res)| *res != 0 && *res != (r - 1) / 2);
+        if sieved {
+            counter += 1;
+            let p = q.shl(1) + T::one();
+            let found =
+                constraints.accepts(&p, size) && tester.test(&q, seed) && tester.test(&p, seed);
+            observer.on_progress(&progress(size, expected, counter, now));
+            if found {
+                observer.on_finish(&progress(size, expected, counter, now), true);
+                let elapsed_time = now.elapsed().as_secs_f32();
+                info!(
+                    "Found safe prime! Search stats: {} checks in {:.4}s ({:.4}s/check avg)",
+                    counter,
+                    elapsed_time,
+                    elapsed_time / counter as f32
+                );
+                return Ok(p);
+            }
+        }
+
+        q = q + step.clone();
+        for ((res, r), step) in residues.iter_mut().zip(&sieve).zip(&steps) {
+            *res = (*res + step) % r;
+        }
+    }
+}
+
+/// Primo forte no sentido de Gordon, junto dos fatores que garantem a propriedade
+#[derive(Clone, Debug)]
+pub struct StrongPrime<T> {
+    /// Primo forte
+    pub p: T,
+    /// Fator primo grande de p-1
+    pub r: T,
+    /// Fator primo grande de p+1
+    pub s: T,
+    /// Fator primo grande de r-1
+    pub t: T,
+}
+
+/// Acha um provável primo forte p de _size_ bits pelo algoritmo de Gordon: p-1 tem um fator primo grande r, p+1 tem um fator primo grande s e r-1 tem um fator primo grande t (ANSI X9.31). São gerados primos s e t com cerca de metade do tamanho de p, depois r = 2it + 1 primo, p₀ = 2(s^(r-2) mod r)s - 1 e finalmente p = p₀ + 2jrs primo, todos testados com _tester_. As condições _constraints_ são checadas sobre p.
+pub fn find_strong_prime<T: BigInteger>(
+    size: u64,
+    seed: &T,
+    tester: Tester,
+    constraints: &PrimeConstraints<T>,
+    limits: &SearchLimits,
+) -> Result<StrongPrime<T>, SearchError> {
+    assert!(size >= 64, "Strong primes need at least 64 bits");
+    constraints
+        .check(size, PrimeKind::Strong)
+        .map_err(SearchError::Unsatisfiable)?;
+    info!(
+        "Finding strong prime with {} bits with {} method",
+        size,
+        tester.name()
+    );
+    let now = Instant::now();
+    let mut seed_gen: Mlcg<T> = Mlcg::new_std(512, seed);
+
+    // r·s fica com cerca de size-24 bits, deixando espaço para muitos valores de j
+    let half = size / 2 - 12;
+    let s = find_prime(half, &seed_gen.rand(), tester, limits)?;
+    let t = find_prime(half - 12, &seed_gen.rand(), tester, limits)?;
+
+    let two = T::from_u64(2);
+    let two_t = two.clone() * t.clone();
+    let mut i = T::one().shl(10);
+    let mut counter = 0;
+    let r = loop {
+        limits.check(counter, now)?;
+        let r = two_t.clone() * i.clone() + T::one();
+        counter += 1;
+        if tester.test(&r, seed) {
+            break r;
+        }
+        i = i + T::one();
+    };
+
+    let p0 = two.clone() * s.modpow(&(r.clone() - two.clone()), &r) * s.clone() - T::one();
+    let step = two * r.clone() * s.clone();
+    let low = match constraints.top_two_bits {
+        true => T::from_u64(3).shl(size - 2),
+        false => T::one().shl(size - 1),
+    };
+    let high = T::one().shl(size);
+    let first_j = (low.clone() - p0.clone() + step.clone() - T::one()) / step.clone();
+    let start = (seed_gen.rand() % (high.clone() - low.clone())) + low;
+    let mut p =
+        p0.clone() + (start - p0.clone() + step.clone() - T::one()) / step.clone() * step.clone();
+
+    loop {
+        limits.check(counter, now)?;
+        if p >= high {
+            p = p0.clone() + first_j.clone() * step.clone();
+        }
         counter += 1;
-        if miller_rabin_tester(&num, seed) {
+        if constraints.accepts(&p, size) && tester.test(&p, seed) {
             let elapsed_time = now.elapsed().as_secs_f32();
             info!(
-                "Found prime! Search stats: {} checks in {:.4}s ({:.4}s/check avg)",
+                "Found strong prime! Search stats: {} checks in {:.4}s ({:.4}s/check avg)",
                 counter,
                 elapsed_time,
                 elapsed_time / counter as f32
             );
-            return num;
+            return Ok(StrongPrime { p, r, s, t });
+        }
+        p = p + step.clone();
+    }
+}
+
+/// Retorna todos os primos menores que _limit_, pelo crivo de Eratóstenes
+pub fn small_primes(limit: u64) -> Vec<u64> {
+    let mut composite = vec![false; limit as usize];
+    let mut primes = Vec::new();
+    for i in 2..limit as usize {
+        if !composite[i] {
+            primes.push(i as u64);
+            for j in (i * i..limit as usize).step_by(i) {
+                composite[j] = true;
+            }
+        }
+    }
+    primes
+}
+
+/// Retorna o resto de _x_ por _r_
+pub fn small_rem<T: BigInteger>(x: &T, r: u64) -> u64 {
+    (x.clone() % T::from_u64(r))
+        .to_limbs()
+        .first()
+        .copied()
+        .unwrap_or(0)
+}
+
+/// Monta o estado atual da busca para os observadores
+fn progress(size: u64, expected: f64, candidates: u64, start: Instant) -> SearchProgress {
+    SearchProgress {
+        size,
+        candidates,
+        expected,
+        elapsed: start.elapsed(),
+    }
+}
+
+/// Busca sem limites, que portanto sempre encontra um primo
+fn find_unbounded<T: BigInteger>(size: u64, seed: &T, tester: Tester) -> T {
+    find_prime(size, seed, tester, &SearchLimits::new()).expect("unbounded search cannot fail")
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use super::*;
+
+    fn big(n: u64) -> BigUint {
+        BigUint::from(n)
+    }
+
+    #[test]
+    fn residue_rejects_unsatisfiable_conditions() {
+        let constraints = PrimeConstraints::<BigUint>::new();
+        assert_eq!(
+            constraints.clone().residue(big(2), big(4)).unwrap_err(),
+            ConstraintError::NotCoprime
+        );
+        assert_eq!(
+            constraints
+                .clone()
+                .blum()
+                .unwrap()
+                .residue(big(1), big(4))
+                .unwrap_err(),
+            ConstraintError::IncompatibleResidues
+        );
+        let combined = constraints.blum().unwrap().residue(big(2), big(3)).unwrap();
+        assert_eq!(combined.residue_condition(), Some(&(big(11), big(12))));
+    }
+
+    #[test]
+    fn check_rejects_moduli_larger_than_the_range() {
+        let constraints = PrimeConstraints::new()
+            .residue(big(1), big(1000003))
+            .unwrap();
+        assert_eq!(
+            constraints.check(16, PrimeKind::Plain),
+            Err(ConstraintError::ModulusTooLarge { size: 16 })
+        );
+        assert_eq!(constraints.check(32, PrimeKind::Plain), Ok(()));
+
+        let constraints = PrimeConstraints::new().residue(big(1), big(20000)).unwrap();
+        assert_eq!(constraints.check(16, PrimeKind::Plain), Ok(()));
+        assert_eq!(
+            constraints.top_two_bits().check(16, PrimeKind::Plain),
+            Err(ConstraintError::ModulusTooLarge { size: 16 })
+        );
+    }
+
+    #[test]
+    fn check_rejects_residues_forcing_a_common_factor_with_e() {
+        let constraints = PrimeConstraints::new().residue(big(1), big(3)).unwrap();
+        assert_eq!(
+            constraints
+                .clone()
+                .coprime_to(big(3))
+                .check(64, PrimeKind::Plain),
+            Err(ConstraintError::ExponentNotCoprime)
+        );
+        assert_eq!(
+            constraints
+                .coprime_to(big(65537))
+                .check(64, PrimeKind::Plain),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn searches_honour_constraints() {
+        let constraints = PrimeConstraints::new()
+            .residue(big(5), big(12))
+            .unwrap()
+            .top_two_bits()
+            .coprime_to(big(3));
+        let p = find_prime_with_progress(
+            64,
+            &big(7),
+            Tester::MillerRabin,
+            &constraints,
+            &SearchLimits::new(),
+            &mut (),
+        )
+        .unwrap();
+        assert!(constraints.accepts(&p, 64));
+        assert_eq!(p.clone() % big(12), big(5));
+    }
+
+    #[test]
+    fn safe_search_walks_only_the_residue_class() {
+        // Com o módulo de 2^20 + 7, percorrer q de 2 em 2 testaria cerca de um milhão de vezes mais candidatos que o limite
+        let conditions = [(23, 24), (2, 5), (3, 4), (1_000_003, (1 << 20) + 7)];
+        for (a, m) in conditions.iter() {
+            let constraints = PrimeConstraints::new()
+                .residue(big(*a), big(*m))
+                .unwrap()
+                .top_two_bits();
+            let p = find_safe_prime_with_progress(
+                64,
+                &big(7),
+                Tester::MillerRabin,
+                &constraints,
+                &SearchLimits::new().max_candidates(20_000),
+                &mut (),
+            )
+            .unwrap();
+            assert!(constraints.accepts(&p, 64), "{}:{}", a, m);
+            let q = (p.clone() - big(1)) / big(2);
+            assert!(Tester::MillerRabin.test(&q, &big(7)));
+            assert!(Tester::MillerRabin.test(&p, &big(7)));
+        }
+    }
+
+    #[test]
+    fn test_matches_verdict() {
+        let seed = big(12345);
+        let testers = [
+            Tester::MillerRabin,
+            Tester::Fermat,
+            Tester::MillerRabinCt,
+            Tester::FermatCt,
+            Tester::SolovayStrassen,
+            Tester::Aks,
+        ];
+        for tester in testers.iter() {
+            // O AKS é lento demais em modo de depuração para a faixa toda, que inclui os números de Carmichael 561 e 1105
+            let bound = if *tester == Tester::Aks { 200 } else { 1200 };
+            for n in 0..bound {
+                assert_eq!(
+                    tester.test(&big(n), &seed),
+                    tester.verdict(&big(n), &seed).is_probable_prime(),
+                    "{} on {}",
+                    tester.name(),
+                    n
+                );
+            }
         }
     }
+
+    #[test]
+    fn check_rejects_residues_the_kind_cannot_reach() {
+        let residue = |a, m| {
+            PrimeConstraints::<BigUint>::new()
+                .residue(big(a), big(m))
+                .unwrap()
+        };
+        let first = |length| PrimeKind::Chain(ChainKind::First, length);
+        let second = |length| PrimeKind::Chain(ChainKind::Second, length);
+
+        // p ≡ 1 (mod 4) deixaria q = (p-1)/2 par; todo primo seguro maior que 5 é de Blum
+        assert_eq!(
+            residue(1, 4).check(32, PrimeKind::Safe),
+            Err(ConstraintError::IncompatibleKind)
+        );
+        assert_eq!(residue(1, 4).check(32, PrimeKind::Plain), Ok(()));
+        assert_eq!(residue(1, 4).check(64, PrimeKind::Strong), Ok(()));
+        let blum = PrimeConstraints::<BigUint>::new().blum().unwrap();
+        assert_eq!(blum.check(32, PrimeKind::Safe), Ok(()));
+        assert_eq!(
+            residue(1, 3).check(32, PrimeKind::Safe),
+            Err(ConstraintError::IncompatibleKind)
+        );
+        // 7 ≡ 1 (mod 3) tornaria q múltiplo de 3; todo primo seguro maior que 7 é ≡ 23 (mod 24)
+        assert_eq!(
+            residue(7, 24).check(32, PrimeKind::Safe),
+            Err(ConstraintError::IncompatibleKind)
+        );
+        assert_eq!(residue(23, 24).check(32, PrimeKind::Safe), Ok(()));
+
+        assert_eq!(residue(1, 3).check(32, first(1)), Ok(()));
+        assert_eq!(
+            residue(1, 3).check(32, first(2)),
+            Err(ConstraintError::IncompatibleKind)
+        );
+        assert_eq!(residue(2, 3).check(32, first(5)), Ok(()));
+        // 2·2 - 1 = 3 e 2·3 - 1 ≡ 0 (mod 5)
+        assert_eq!(
+            residue(2, 3).check(32, second(2)),
+            Err(ConstraintError::IncompatibleKind)
+        );
+        assert_eq!(
+            residue(3, 5).check(32, second(2)),
+            Err(ConstraintError::IncompatibleKind)
+        );
+        assert_eq!(residue(1, 3).check(32, second(4)), Ok(()));
+
+        // Em um primo seguro é q, com um bit a menos, que percorre o intervalo
+        assert_eq!(residue(3, 20000).check(16, PrimeKind::Plain), Ok(()));
+        assert_eq!(
+            residue(3, 20000).check(16, PrimeKind::Safe),
+            Err(ConstraintError::ModulusTooLarge { size: 16 })
+        );
+    }
+
+    #[test]
+    fn safe_search_rejects_unreachable_residues_instead_of_hanging() {
+        let constraints = PrimeConstraints::new().residue(big(1), big(4)).unwrap();
+        let limits = SearchLimits::new().max_candidates(100_000);
+        assert_eq!(
+            find_safe_prime_with_progress(
+                32,
+                &big(7),
+                Tester::MillerRabin,
+                &constraints,
+                &limits,
+                &mut ()
+            ),
+            Err(SearchError::Unsatisfiable(
+                ConstraintError::IncompatibleKind
+            ))
+        );
+    }
 }
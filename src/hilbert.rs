@@ -0,0 +1,368 @@
+use std::f64::consts::{LN_2, PI};
+
+use num_bigint::{BigInt, Sign};
+
+use crate::backend::BigInteger;
+
+/// Forma quadrática binária (a, b, c), de discriminante b² - 4ac
+pub type Form = (i64, i64, i64);
+
+/// Discriminante fundamental negativo D com as formas reduzidas, uma para cada classe de ideais
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Discriminant {
+    /// Valor de D, negativo
+    pub d: i64,
+    /// Formas reduzidas de discriminante D
+    pub forms: Vec<Form>,
+}
+
+impl Discriminant {
+    /// Retorna o número de classes h(D), grau do polinômio de classe de Hilbert.
+    pub fn class_number(&self) -> usize {
+        self.forms.len()
+    }
+}
+
+/// Retorna os discriminantes fundamentais D < 0 com |D| ≤ _max_abs_ e número de classes até _max_class_number_, ordenados pelo número de classes e depois por |D|. As formas reduzidas (|b| ≤ a ≤ c, com b ≥ 0 se |b| = a ou a = c) são enumeradas uma única vez para todos os discriminantes.
+pub fn discriminants(max_abs: i64, max_class_number: usize) -> Vec<Discriminant> {
+    let mut forms: Vec<Vec<Form>> = vec![Vec::new(); max_abs as usize + 1];
+    let mut a = 1;
+    while 3 * a * a <= max_abs {
+        for b in 0..=a {
+            let mut c = a;
+            while 4 * a * c - b * b <= max_abs {
+                let abs_d = (4 * a * c - b * b) as usize;
+                if gcd(gcd(a, b), c) == 1 {
+                    forms[abs_d].push((a, b, c));
+                    if b > 0 && b < a && a < c {
+                        forms[abs_d].push((a, -b, c));
+                    }
+                }
+                c += 1;
+            }
+        }
+        a += 1;
+    }
+    let mut table: Vec<Discriminant> = forms
+        .into_iter()
+        .enumerate()
+        .filter(|(abs_d, forms)| {
+            is_fundamental(*abs_d as i64) && !forms.is_empty() && forms.len() <= max_class_number
+        })
+        .map(|(abs_d, forms)| Discriminant {
+            d: -(abs_d as i64),
+            forms,
+        })
+        .collect();
+    table.sort_by_key(|disc| (disc.class_number(), -disc.d));
+    table
+}
+
+/// Calcula o polinômio de classe de Hilbert H_D(x) = ∏ (x - j(τ)), com τ = (-b + √D)/2a para cada forma reduzida, e retorna os coeficientes inteiros do grau 0 ao h(D). Os valores de j são calculados numericamente em ponto fixo, com precisão estimada pelo tamanho dos coeficientes e aumentada até o arredondamento ficar confiável.
+pub fn hilbert_class_polynomial(disc: &Discriminant) -> Vec<BigInt> {
+    let abs_d = -disc.d;
+    // log2|j(τ)| ≈ π√|D|/a; o maior coeficiente é limitado pela soma desses tamanhos
+    let estimate: f64 = disc
+        .forms
+        .iter()
+        .map(|&(a, _, _)| PI * (abs_d as f64).sqrt() / (a as f64) / LN_2 + 12.0)
+        .sum();
+    let mut prec = estimate as u64 + 64;
+    loop {
+        if let Some(poly) = hilbert_with_precision(disc, prec) {
+            return poly;
+        }
+        prec += prec / 2;
+    }
+}
+
+/// Reduz os coeficientes de _poly_ módulo _n_.
+pub fn reduce<T: BigInteger>(poly: &[BigInt], n: &T) -> Vec<T> {
+    poly.iter()
+        .map(|coef| {
+            let r = T::from_bytes_be(&coef.magnitude().to_bytes_be()) % n.clone();
+            match coef.sign() == Sign::Minus && r != T::zero() {
+                true => n.clone() - r,
+                false => r,
+            }
+        })
+        .collect()
+}
+
+/// Checa se -_abs_d_ é discriminante fundamental: D ≡ 1 (mod 4) livre de quadrados, ou D = 4m com m ≡ 2, 3 (mod 4) livre de quadrados
+fn is_fundamental(abs_d: i64) -> bool {
+    match abs_d % 4 {
+        3 => is_squarefree(abs_d),
+        0 => matches!((abs_d / 4) % 4, 1 | 2) && is_squarefree(abs_d / 4),
+        _ => false,
+    }
+}
+
+/// Checa se _n_ não tem fatores quadrados
+fn is_squarefree(n: i64) -> bool {
+    let mut p = 2;
+    while p * p <= n {
+        if n % (p * p) == 0 {
+            return false;
+        }
+        p += 1;
+    }
+    true
+}
+
+/// Máximo divisor comum de inteiros pequenos
+fn gcd(a: i64, b: i64) -> i64 {
+    match b {
+        0 => a.abs(),
+        _ => gcd(b, a % b),
+    }
+}
+
+/// Número complexo em ponto fixo, com as partes multiplicadas por 2^prec
+#[derive(Clone, Debug)]
+struct Complex {
+    re: BigInt,
+    im: BigInt,
+}
+
+/// Aritmética em ponto fixo com _prec_ bits fracionários
+struct Fixed {
+    prec: u64,
+}
+
+impl Fixed {
+    fn one(&self) -> BigInt {
+        BigInt::from(1) << self.prec
+    }
+
+    /// Produto truncado em direção a zero, para que as séries terminem também com valores negativos
+    fn mul(&self, a: &BigInt, b: &BigInt) -> BigInt {
+        let product = a * b;
+        match product.sign() {
+            Sign::Minus => -((-product) >> self.prec),
+            _ => product >> self.prec,
+        }
+    }
+
+    fn div(&self, a: &BigInt, b: &BigInt) -> BigInt {
+        (a << self.prec) / b
+    }
+
+    fn cmul(&self, a: &Complex, b: &Complex) -> Complex {
+        Complex {
+            re: self.mul(&a.re, &b.re) - self.mul(&a.im, &b.im),
+            im: self.mul(&a.re, &b.im) + self.mul(&a.im, &b.re),
+        }
+    }
+
+    fn cdiv(&self, a: &Complex, b: &Complex) -> Complex {
+        let norm = self.mul(&b.re, &b.re) + self.mul(&b.im, &b.im);
+        let re = self.mul(&a.re, &b.re) + self.mul(&a.im, &b.im);
+        let im = self.mul(&a.im, &b.re) - self.mul(&a.re, &b.im);
+        Complex {
+            re: self.div(&re, &norm),
+            im: self.div(&im, &norm),
+        }
+    }
+
+    /// π pela fórmula de Machin: π = 16·arctan(1/5) - 4·arctan(1/239)
+    fn pi(&self) -> BigInt {
+        16 * self.arctan_inv(5) - 4 * self.arctan_inv(239)
+    }
+
+    /// arctan(1/_k_) pela série de Taylor
+    fn arctan_inv(&self, k: i64) -> BigInt {
+        let mut power = self.one() / k;
+        let (mut sum, mut n) = (BigInt::from(0), 0i64);
+        while power != BigInt::from(0) {
+            let term = &power / (2 * n + 1);
+            match n % 2 {
+                0 => sum += term,
+                _ => sum -= term,
+            }
+            power /= k * k;
+            n += 1;
+        }
+        sum
+    }
+
+    /// e^_x_ para x ≥ 0, com redução do argumento por 2^k seguida de k quadrados
+    fn exp(&self, x: &BigInt) -> BigInt {
+        let k = (x >> self.prec).bits() + 1;
+        let y = x >> k;
+        let (mut sum, mut term, mut n) = (self.one(), self.one(), 1);
+        while term != BigInt::from(0) {
+            term = self.mul(&term, &y) / n;
+            sum += &term;
+            n += 1;
+        }
+        for _ in 0..k {
+            sum = self.mul(&sum, &sum);
+        }
+        sum
+    }
+
+    /// e^(iθ) pela série de Taylor, para |θ| ≤ π
+    fn expi(&self, theta: &BigInt) -> Complex {
+        let mut result = Complex {
+            re: self.one(),
+            im: BigInt::from(0),
+        };
+        let (mut term, mut n) = (self.one(), 1);
+        while term != BigInt::from(0) {
+            term = self.mul(&term, theta) / n;
+            match n % 4 {
+                0 => result.re += &term,
+                1 => result.im += &term,
+                2 => result.re -= &term,
+                _ => result.im -= &term,
+            }
+            n += 1;
+        }
+        result
+    }
+}
+
+/// Calcula H_D com _prec_ bits de precisão, ou None se o arredondamento dos coeficientes não for confiável
+fn hilbert_with_precision(disc: &Discriminant, prec: u64) -> Option<Vec<BigInt>> {
+    let fixed = Fixed { prec };
+    let pi = fixed.pi();
+    let sqrt_d = BigInt::from_biguint(
+        Sign::Plus,
+        (BigInt::from(-disc.d) << (2 * prec)).magnitude().sqrt(),
+    );
+    let pi_sqrt_d = fixed.mul(&pi, &sqrt_d);
+
+    let mut poly = vec![Complex {
+        re: fixed.one(),
+        im: BigInt::from(0),
+    }];
+    for &(a, b, _) in &disc.forms {
+        let j = j_invariant(&fixed, &(&pi_sqrt_d / a), &(&pi * b / a));
+        // poly · (x - j)
+        let mut next = vec![
+            Complex {
+                re: BigInt::from(0),
+                im: BigInt::from(0),
+            };
+            poly.len() + 1
+        ];
+        for (i, coef) in poly.iter().enumerate() {
+            let product = fixed.cmul(coef, &j);
+            next[i].re -= product.re;
+            next[i].im -= product.im;
+            next[i + 1].re += &coef.re;
+            next[i + 1].im += &coef.im;
+        }
+        poly = next;
+    }
+
+    let tolerance = BigInt::from(1) << (prec - 20);
+    let half = BigInt::from(1) << (prec - 1);
+    poly.iter()
+        .map(|coef| {
+            let rounded = match coef.re.sign() {
+                Sign::Minus => -((-&coef.re + &half) >> prec),
+                _ => (&coef.re + &half) >> prec,
+            };
+            let error = &coef.re - (&rounded << prec);
+            match error.magnitude() < tolerance.magnitude()
+                && coef.im.magnitude() < tolerance.magnitude()
+            {
+                true => Some(rounded),
+                false => None,
+            }
+        })
+        .collect()
+}
+
+/// Calcula j(τ) = E4(q)³ / (q·∏(1 - qⁿ)²⁴), com q = e^(2πiτ) = e^(-_x_)·e^(-i_theta_)
+fn j_invariant(fixed: &Fixed, x: &BigInt, theta: &BigInt) -> Complex {
+    let exp_x = fixed.exp(x);
+    let rotation = fixed.expi(theta);
+    let inv_q = Complex {
+        re: fixed.mul(&exp_x, &rotation.re),
+        im: fixed.mul(&exp_x, &rotation.im),
+    };
+    let exp_minus_x = fixed.div(&fixed.one(), &exp_x);
+    let q = Complex {
+        re: fixed.mul(&exp_minus_x, &rotation.re),
+        im: -fixed.mul(&exp_minus_x, &rotation.im),
+    };
+
+    let mut eta = Complex {
+        re: fixed.one(),
+        im: BigInt::from(0),
+    };
+    let mut e4 = eta.clone();
+    let mut qn = q.clone();
+    let mut n = 1i64;
+    while qn.re != BigInt::from(0) || qn.im != BigInt::from(0) {
+        let factor = Complex {
+            re: fixed.one() - &qn.re,
+            im: -qn.im.clone(),
+        };
+        eta = fixed.cmul(&eta, &factor);
+        let sigma3: i64 = (1..=n).filter(|d| n % d == 0).map(|d| d * d * d).sum();
+        e4.re += &qn.re * 240 * sigma3;
+        e4.im += &qn.im * 240 * sigma3;
+        qn = fixed.cmul(&qn, &q);
+        n += 1;
+    }
+
+    let eta2 = fixed.cmul(&eta, &eta);
+    let eta4 = fixed.cmul(&eta2, &eta2);
+    let eta8 = fixed.cmul(&eta4, &eta4);
+    let eta16 = fixed.cmul(&eta8, &eta8);
+    let eta24 = fixed.cmul(&eta16, &eta8);
+    let e4_cubed = fixed.cmul(&fixed.cmul(&e4, &e4), &e4);
+    fixed.cmul(&fixed.cdiv(&e4_cubed, &eta24), &inv_q)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn polynomial(d: i64) -> Vec<BigInt> {
+        let disc = discriminants(-d, 8)
+            .into_iter()
+            .find(|disc| disc.d == d)
+            .unwrap();
+        hilbert_class_polynomial(&disc)
+    }
+
+    fn coefficients(values: &[&str]) -> Vec<BigInt> {
+        values.iter().map(|value| value.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn matches_known_class_polynomials() {
+        assert_eq!(polynomial(-3), coefficients(&["0", "1"]));
+        assert_eq!(polynomial(-4), coefficients(&["-1728", "1"]));
+        assert_eq!(polynomial(-7), coefficients(&["3375", "1"]));
+        // j((1 + √-163)/2) = -640320³
+        assert_eq!(polynomial(-163), coefficients(&["262537412640768000", "1"]));
+        assert_eq!(
+            polynomial(-15),
+            coefficients(&["-121287375", "191025", "1"])
+        );
+        // A raiz real é j((1 + √-23)/2) ≈ -3493225,7, então o coeficiente de x² é positivo
+        assert_eq!(
+            polynomial(-23),
+            coefficients(&["12771880859375", "-5151296875", "3491750", "1"])
+        );
+    }
+
+    #[test]
+    fn lists_reduced_forms() {
+        let table = discriminants(100, 3);
+        let d23 = table.iter().find(|disc| disc.d == -23).unwrap();
+        assert_eq!(d23.forms, vec![(1, 1, 6), (2, 1, 3), (2, -1, 3)]);
+        // -12 e -16 não são fundamentais; h(-71) = 7 passa do limite
+        assert!(table
+            .iter()
+            .all(|disc| disc.d != -12 && disc.d != -16 && disc.d != -71));
+        let class_numbers: Vec<usize> = table.iter().map(|disc| disc.class_number()).collect();
+        assert!(class_numbers.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+}
@@ -1,4 +1,24 @@
+pub mod asn1;
+pub mod backend;
+pub mod certificate;
+pub mod chains;
+pub mod constant_time;
+pub mod dsa;
+pub mod ecm;
+pub mod ecpp;
 pub mod environment;
+pub mod factor;
 pub mod functions;
+pub mod hilbert;
+pub mod lucas;
+pub mod modular;
+pub mod pem;
 pub mod prime_test;
+pub mod progress;
+pub mod provable;
+pub mod pseudoprime;
 pub mod rand_gen;
+pub mod rsa;
+pub mod sha256;
+pub mod siqs;
+pub mod special;
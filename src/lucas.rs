@@ -0,0 +1,305 @@
+use crate::backend::BigInteger;
+use crate::prime_test::jacobi;
+
+/// Termos U_k e V_k da sequência de Lucas de parâmetros (P, Q), junto de Q^k, todos módulo n
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LucasTerms<T> {
+    pub u: T,
+    pub v: T,
+    pub qk: T,
+}
+
+/// Aritmética módulo _n_ ímpar com os parâmetros P, Q e D = P² - 4Q já reduzidos
+struct Lucas<'a, T> {
+    n: &'a T,
+    p: T,
+    q: T,
+    d: T,
+}
+
+impl<'a, T: BigInteger> Lucas<'a, T> {
+    fn new(p: i64, q: i64, n: &'a T) -> Self {
+        Lucas {
+            n,
+            p: signed_mod(p, n),
+            q: signed_mod(q, n),
+            d: signed_mod(p * p - 4 * q, n),
+        }
+    }
+
+    fn add_mod(&self, a: &T, b: &T) -> T {
+        (a.clone() + b) % self.n
+    }
+
+    fn sub_mod(&self, a: &T, b: &T) -> T {
+        (a.clone() + self.n - b) % self.n
+    }
+
+    fn mul_mod(&self, a: &T, b: &T) -> T {
+        a.clone() * b % self.n
+    }
+
+    /// _a_/2 módulo n ímpar
+    fn half(&self, a: &T) -> T {
+        match a.is_even() {
+            true => a.shr(1),
+            false => (a.clone() + self.n).shr(1),
+        }
+    }
+
+    /// Dobra o índice: U_2k = U_k·V_k, V_2k = V_k² - 2Q^k e Q^2k = (Q^k)²
+    fn double(&self, t: &LucasTerms<T>) -> LucasTerms<T> {
+        let twice_qk = self.add_mod(&t.qk, &t.qk);
+        LucasTerms {
+            u: self.mul_mod(&t.u, &t.v),
+            v: self.sub_mod(&self.mul_mod(&t.v, &t.v), &twice_qk),
+            qk: self.mul_mod(&t.qk, &t.qk),
+        }
+    }
+
+    /// Avança o índice em um: U_k+1 = (P·U_k + V_k)/2, V_k+1 = (D·U_k + P·V_k)/2 e Q^(k+1) = Q·Q^k
+    fn increment(&self, t: &LucasTerms<T>) -> LucasTerms<T> {
+        LucasTerms {
+            u: self.half(&self.add_mod(&self.mul_mod(&self.p, &t.u), &t.v)),
+            v: self.half(&self.add_mod(&self.mul_mod(&self.d, &t.u), &self.mul_mod(&self.p, &t.v))),
+            qk: self.mul_mod(&self.q, &t.qk),
+        }
+    }
+
+    /// Calcula os termos de índice _k_ percorrendo os bits de k, do mais significativo ao menos
+    fn terms(&self, k: &T) -> LucasTerms<T> {
+        let mut t = LucasTerms {
+            u: T::zero(),
+            v: T::from_u64(2) % self.n,
+            qk: T::one() % self.n,
+        };
+        for i in (0..k.bits()).rev() {
+            t = self.double(&t);
+            if k.bit(i) {
+                t = self.increment(&t);
+            }
+        }
+        t
+    }
+}
+
+/// Calcula U_k, V_k e Q^k módulo _n_ ímpar para a sequência de Lucas de parâmetros (_p_, _q_), definida por U_0 = 0, U_1 = 1, V_0 = 2, V_1 = P e X_k+1 = P·X_k - Q·X_k-1, usando as fórmulas de duplicação sobre os bits de _k_. Requer mdc(D, n) = 1, com D = P² - 4Q, para as divisões por 2 serem exatas.
+pub fn lucas_sequence<T: BigInteger>(p: i64, q: i64, k: &T, n: &T) -> LucasTerms<T> {
+    Lucas::new(p, q, n).terms(k)
+}
+
+/// Escolhe os parâmetros de Selfridge (método A) para _n_ ímpar: o primeiro D da sequência 5, -7, 9, -11, ... com (D/n) = -1, com P = 1 e Q = (1 - D)/4. Retorna (D, P, Q), ou None se n for um quadrado perfeito (para o qual esse D não existe) ou se algum D tiver um fator em comum com n, o que prova que n é composto.
+pub fn selfridge_parameters<T: BigInteger>(n: &T) -> Option<(i64, i64, i64)> {
+    let root = n.isqrt();
+    if root.clone() * root == *n {
+        return None;
+    }
+    let mut d: i64 = 5;
+    loop {
+        match jacobi_signed(d, n) {
+            -1 => return Some((d, 1, (1 - d) / 4)),
+            0 if T::from_u64(d.unsigned_abs()) != *n => return None,
+            _ => {}
+        }
+        d = match d > 0 {
+            true => -(d + 2),
+            false => -d + 2,
+        };
+    }
+}
+
+/// Teste de Lucas com os parâmetros de Selfridge: todo primo ímpar n satisfaz U_(n+1) ≡ 0 (mod n) quando (D/n) = -1. Os compostos que passam são os pseudoprimos de Lucas (323, 377, 1159, ...).
+pub fn lucas_tester<T: BigInteger>(num: &T) -> bool {
+    if let Some(result) = trivial_check(num) {
+        return result;
+    }
+    let (_, p, q) = match selfridge_parameters(num) {
+        Some(params) => params,
+        None => return false,
+    };
+    let terms = lucas_sequence(p, q, &(num.clone() + T::one()), num);
+    terms.u == T::zero()
+}
+
+/// Teste forte de Lucas com os parâmetros de Selfridge: com n + 1 = d·2^s e d ímpar, um primo satisfaz U_d ≡ 0 ou V_(d·2^r) ≡ 0 (mod n) para algum 0 ≤ r < s. É o análogo do Miller-Rabin para sequências de Lucas e a metade de Lucas do teste BPSW; o menor pseudoprimo forte de Lucas é 5459.
+pub fn strong_lucas_tester<T: BigInteger>(num: &T) -> bool {
+    if let Some(result) = trivial_check(num) {
+        return result;
+    }
+    let (_, p, q) = match selfridge_parameters(num) {
+        Some(params) => params,
+        None => return false,
+    };
+    let (d, s) = split_power_of_two(num.clone() + T::one());
+    let lucas = Lucas::new(p, q, num);
+    let mut terms = lucas.terms(&d);
+    if terms.u == T::zero() {
+        return true;
+    }
+    for _ in 0..s {
+        if terms.v == T::zero() {
+            return true;
+        }
+        terms = lucas.double(&terms);
+    }
+    false
+}
+
+/// Teste extraforte de Lucas (Mo e Jones, com os parâmetros de Baillie): Q = 1 e P o primeiro de 3, 4, 5, ... com D = P² - 4 e (D/n) = -1. Com n + 1 = d·2^s, um primo satisfaz U_d ≡ 0 e V_d ≡ ±2, ou V_(d·2^r) ≡ 0 para algum 0 ≤ r < s - 1. Como Q = 1, não precisa acompanhar Q^k, e os pseudoprimos extrafortes são um subconjunto dos fortes para esses parâmetros.
+pub fn extra_strong_lucas_tester<T: BigInteger>(num: &T) -> bool {
+    if let Some(result) = trivial_check(num) {
+        return result;
+    }
+    let root = num.isqrt();
+    if root.clone() * root == *num {
+        return false;
+    }
+    let mut p: i64 = 3;
+    loop {
+        let d = p * p - 4;
+        match jacobi_signed(d, num) {
+            -1 => break,
+            0 if T::from_u64(d as u64) != *num => return false,
+            _ => p += 1,
+        }
+    }
+    let (d, s) = split_power_of_two(num.clone() + T::one());
+    let lucas = Lucas::new(p, 1, num);
+    let mut terms = lucas.terms(&d);
+    let two = T::from_u64(2);
+    let minus_two = num.clone() - two.clone();
+    if terms.u == T::zero() && (terms.v == two || terms.v == minus_two) {
+        return true;
+    }
+    for _ in 0..s.saturating_sub(1) {
+        if terms.v == T::zero() {
+            return true;
+        }
+        terms = lucas.double(&terms);
+    }
+    false
+}
+
+/// Resolve os casos que dispensam a sequência: n < 2, 2 e os pares
+fn trivial_check<T: BigInteger>(num: &T) -> Option<bool> {
+    match () {
+        _ if *num < T::from_u64(2) => Some(false),
+        _ if *num == T::from_u64(2) => Some(true),
+        _ if num.is_even() => Some(false),
+        _ => None,
+    }
+}
+
+/// Escreve _m_ como d·2^s, com d ímpar
+fn split_power_of_two<T: BigInteger>(mut m: T) -> (T, u64) {
+    let mut s = 0;
+    while m.is_even() {
+        m = m.shr(1);
+        s += 1;
+    }
+    (m, s)
+}
+
+/// Símbolo de Jacobi (_d_/_n_) para _d_ com sinal: (-1/n) = -1 se n ≡ 3 (mod 4)
+fn jacobi_signed<T: BigInteger>(d: i64, n: &T) -> i32 {
+    let symbol = jacobi(&T::from_u64(d.unsigned_abs()), n);
+    match d < 0 && n.bit(1) {
+        true => -symbol,
+        false => symbol,
+    }
+}
+
+/// Reduz _x_ com sinal módulo _n_
+fn signed_mod<T: BigInteger>(x: i64, n: &T) -> T {
+    let r = T::from_u64(x.unsigned_abs()) % n.clone();
+    match x < 0 && r != T::zero() {
+        true => n.clone() - r,
+        false => r,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use super::*;
+    use crate::functions::small_primes;
+
+    /// Compostos que passam em cada teste: os de Lucas até 10000 e os fortes e extrafortes até 20000 (OEIS A217120, A217255 e A217719)
+    const LUCAS_PSEUDOPRIMES: [u64; 9] = [323, 377, 1159, 1829, 3827, 5459, 5777, 9071, 9179];
+    const STRONG_PSEUDOPRIMES: [u64; 5] = [5459, 5777, 10877, 16109, 18971];
+    const EXTRA_STRONG_PSEUDOPRIMES: [u64; 4] = [989, 3239, 5777, 10877];
+
+    #[test]
+    fn sequences_match_the_recurrence() {
+        let n = BigUint::from(1_000_000_007u64);
+        let m = 1_000_000_007i128;
+        // Fibonacci e Lucas (1, -1), Pell (2, -1), 2^k - 1 e 2^k + 1 (3, 2) e um Q grande (5, 3)
+        for &(p, q) in [(1i64, -1i64), (2, -1), (3, 2), (5, 3)].iter() {
+            let (mut u, mut v, mut qk) = (0i128, 2i128, 1i128);
+            let (mut u_next, mut v_next) = (1i128, p as i128);
+            for k in 0u64..200 {
+                let terms = lucas_sequence(p, q, &BigUint::from(k), &n);
+                assert_eq!(
+                    terms,
+                    LucasTerms {
+                        u: BigUint::from(u as u64),
+                        v: BigUint::from(v as u64),
+                        qk: BigUint::from(qk as u64),
+                    },
+                    "({}, {}) at k = {}",
+                    p,
+                    q,
+                    k
+                );
+                let step =
+                    |x: i128, next: i128| (next, (p as i128 * next - q as i128 * x).rem_euclid(m));
+                (u, u_next) = step(u, u_next);
+                (v, v_next) = step(v, v_next);
+                qk = (qk * q as i128).rem_euclid(m);
+            }
+        }
+        // F_10 = 55, L_10 = 123, e P_10 = 2378 na sequência de Pell
+        let ten = BigUint::from(10u32);
+        assert_eq!(lucas_sequence(1, -1, &ten, &n).u, BigUint::from(55u32));
+        assert_eq!(lucas_sequence(1, -1, &ten, &n).v, BigUint::from(123u32));
+        assert_eq!(lucas_sequence(2, -1, &ten, &n).u, BigUint::from(2378u32));
+    }
+
+    #[test]
+    fn selfridge_parameters_skip_to_the_first_non_residue() {
+        let params = |n: u64| selfridge_parameters(&BigUint::from(n));
+        // (5/7) = -1; módulo 11, 5 e 9 são resíduos, -7 ≡ 4 também e -11 é o próprio n
+        assert_eq!(params(7), Some((5, 1, -1)));
+        assert_eq!(params(11), Some((13, 1, -3)));
+        assert_eq!(params(5459), Some((-7, 1, 2)));
+        assert_eq!(params(49), None);
+        assert_eq!(params(35), None);
+    }
+
+    /// Confere _tester_ em todo n < _bound_: só os primos e os _pseudoprimes_ podem passar
+    fn check(name: &str, tester: fn(&BigUint) -> bool, pseudoprimes: &[u64], bound: u64) {
+        let primes = small_primes(bound);
+        for n in 0..bound {
+            let expected = primes.binary_search(&n).is_ok() || pseudoprimes.contains(&n);
+            assert_eq!(tester(&BigUint::from(n)), expected, "{} on {}", name, n);
+        }
+    }
+
+    #[test]
+    fn testers_match_known_pseudoprimes() {
+        check("Lucas", lucas_tester, &LUCAS_PSEUDOPRIMES, 10_000);
+        check(
+            "strong Lucas",
+            strong_lucas_tester,
+            &STRONG_PSEUDOPRIMES,
+            20_000,
+        );
+        check(
+            "extra-strong Lucas",
+            extra_strong_lucas_tester,
+            &EXTRA_STRONG_PSEUDOPRIMES,
+            20_000,
+        );
+    }
+}
@@ -1,17 +1,33 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::exit;
 use std::time::Instant;
 
-use log::info;
+use log::{error, info, warn};
 
-use ine5429_primes::{environment, functions::*, rand_gen::*};
+use ine5429_primes::backend::Int;
+use ine5429_primes::certificate::{certify, parse_certificates, Certificate};
+use ine5429_primes::chains::find_cunningham_chain;
+use ine5429_primes::dsa::{
+    generate_domain_parameters, validate_domain_parameters, DomainParameters,
+};
+use ine5429_primes::environment::{self, KeyOutput, Operation, ParsedArgs};
+use ine5429_primes::factor::factor;
+use ine5429_primes::provable::find_provable_prime;
+use ine5429_primes::pseudoprime::{pseudoprimes, run_testers};
+use ine5429_primes::rsa::{generate_rsa_key, RsaPrivateKey};
+use ine5429_primes::special::test_special_form;
+use ine5429_primes::{functions::*, progress::TerminalProgress, rand_gen::*};
 
 fn main() {
     let args = environment::init();
 
     info!("Arguments successfully parsed");
 
-    match args.op {
+    match &args.op {
         // Gerar números aleatórios
-        true => {
+        Operation::Rng => {
             match args.method {
                 // MLCG
                 true => {
@@ -19,7 +35,7 @@ fn main() {
                     let mut mlcg_gen = Mlcg::new_std(args.size, &args.seed);
                     info!(
                         "MLCG initialization time: {:.4}ms",
-                        now.elapsed().as_secs_f64() * 1000 as f64
+                        now.elapsed().as_secs_f64() * 1000_f64
                     );
                     for i in 0..args.n {
                         info!("{}º: {}", i+1, mlcg_gen.rand());
@@ -28,7 +44,7 @@ fn main() {
                     info!(
                         "Total time for generating {} numbers: {:.4}ms ({:.4}ms/number avg)",
                         args.n,
-                        elapsed * 1000 as f64,
+                        elapsed * 1000_f64,
                         elapsed * (1000 / args.n) as f64
                     );
                 }
@@ -39,7 +55,7 @@ fn main() {
                         LaggedFibonacci::new_std(args.size, &args.seed, std::ops::Mul::mul);
                     info!(
                         "Lagged Fibonacci initialization time: {:.4}ms",
-                        now.elapsed().as_secs_f64() * 1000 as f64
+                        now.elapsed().as_secs_f64() * 1000_f64
                     );
                     for i in 0..args.n {
                         info!("{}º: {}", i+1, lf_gen.rand());
@@ -48,48 +64,345 @@ fn main() {
                     info!(
                         "Total time for generating {} numbers: {:.4}ms ({:.4}ms/number avg)",
                         args.n,
-                        elapsed * 1000 as f64,
+                        elapsed * 1000_f64,
                         elapsed * (1000 / args.n) as f64
                     );
                 }
             }
         }
         // Gerar primos
-        false => {
+        Operation::Prime => {
             let now = Instant::now();
             let mut seed_gen = Mlcg::new_std(512, &args.seed);
             info!(
                 "MLCG for seed generation initialization time: {:.4}ms",
-                now.elapsed().as_secs_f64() * 1000 as f64
+                now.elapsed().as_secs_f64() * 1000_f64
             );
-            match args.method {
-                // Miller-Rabin
-                true => {
-                    for i in 0..args.n {
-                        info!("{}º: {}", i+1, find_miller_rabin(args.size, &seed_gen.rand()));
-                    }
-                    let elapsed = now.elapsed().as_secs_f64();
-                    info!(
-                        "Total time for finding {} primes: {:.4}s ({:.4}s/number avg)",
-                        args.n,
-                        elapsed,
-                        elapsed / args.n as f64
+            let tester = match (args.method, args.constant_time) {
+                (true, false) => Tester::MillerRabin,
+                (true, true) => Tester::MillerRabinCt,
+                (false, false) => Tester::Fermat,
+                (false, true) => Tester::FermatCt,
+            };
+            let limits = search_limits(&args, now);
+            let mut bar = TerminalProgress::new();
+            let mut quiet = ();
+            let observer: &mut dyn ProgressObserver = match bar.as_mut() {
+                Some(bar) => bar,
+                None => &mut quiet,
+            };
+            for i in 0..args.n {
+                let seed = seed_gen.rand();
+                let constraints = &args.constraints;
+                let found = match (args.safe, args.strong, args.provable, args.chain) {
+                    (_, _, _, Some((kind, length))) => find_cunningham_chain(
+                        args.size,
+                        length,
+                        kind,
+                        &seed,
+                        tester,
+                        constraints,
+                        &limits,
                     )
-                },
-                // Fermat
-                false => {
-                    for i in 0..args.n {
-                        info!("{}º: {}", i+1, find_fermat(args.size, &seed_gen.rand()));
+                    .map(|chain| {
+                        for (i, member) in chain.members.iter().enumerate() {
+                            println!("Chain member {}: {}", i + 1, member);
+                        }
+                        println!();
+                        chain.members[0].clone()
+                    }),
+                    (_, _, true, _) => find_provable_prime(args.size, &seed, constraints, &limits)
+                        .map(|proven| {
+                            let prime = proven.prime.clone();
+                            println!("{}\n", Certificate::from(proven));
+                            prime
+                        }),
+                    (_, true, _, _) => {
+                        find_strong_prime(args.size, &seed, tester, constraints, &limits).map(
+                            |strong| {
+                                info!("r: {}", strong.r);
+                                info!("s: {}", strong.s);
+                                info!("t: {}", strong.t);
+                                strong.p
+                            },
+                        )
+                    }
+                    (true, _, _, _) => find_safe_prime_with_progress(
+                        args.size,
+                        &seed,
+                        tester,
+                        constraints,
+                        &limits,
+                        observer,
+                    ),
+                    _ => find_prime_with_progress(
+                        args.size,
+                        &seed,
+                        tester,
+                        constraints,
+                        &limits,
+                        observer,
+                    ),
+                };
+                let found = match (found, args.certificate) {
+                    (Ok(prime), true) => match certify(&prime, tester, &seed) {
+                        Some(certificate) => {
+                            println!("{}\n", certificate);
+                            Ok(prime)
+                        }
+                        None => {
+                            error!("Could not certify {}: no certificate was found", prime);
+                            exit(1)
+                        }
+                    },
+                    (found, _) => found,
+                };
+                match found {
+                    Ok(prime) => info!("{}º: {}", i+1, prime),
+                    Err(err) => {
+                        error!("{}", err);
+                        exit(1)
                     }
-                    let elapsed = now.elapsed().as_secs_f64();
-                    info!(
-                        "Total time for finding {} primes: {:.4}s ({:.4}s/number avg)",
-                        args.n,
-                        elapsed,
-                        elapsed / args.n as f64
-                    )
                 }
             }
+            let elapsed = now.elapsed().as_secs_f64();
+            info!(
+                "Total time for finding {} primes: {:.4}s ({:.4}s/number avg)",
+                args.n,
+                elapsed,
+                elapsed / args.n as f64
+            )
         }
+        // Gerar chave RSA
+        Operation::RsaKeygen {
+            bits,
+            e,
+            output,
+            out,
+        } => {
+            let now = Instant::now();
+            let limits = search_limits(&args, now);
+            let mut bar = TerminalProgress::new();
+            let mut quiet = ();
+            let observer: &mut dyn ProgressObserver = match bar.as_mut() {
+                Some(bar) => bar,
+                None => &mut quiet,
+            };
+            // Os fatores de uma chave RSA são secretos: os testes em tempo constante são sempre usados
+            let found = generate_rsa_key(
+                *bits,
+                e,
+                &args.seed,
+                Tester::MillerRabinCt,
+                &args.constraints,
+                &limits,
+                observer,
+            );
+            let key = match found {
+                Ok(key) => key,
+                Err(err) => {
+                    error!("{}", err);
+                    exit(1)
+                }
+            };
+            info!(
+                "Total time for generating the key: {:.4}s",
+                now.elapsed().as_secs_f64()
+            );
+            if let Err(err) = write_key(&key, *output, out.as_deref()) {
+                error!("Error trying to write the key: {}", err);
+                exit(1)
+            }
+        }
+        // Gerar parâmetros de domínio DSA/DH
+        Operation::DomainParameters { l, n, index } => {
+            let now = Instant::now();
+            let limits = search_limits(&args, now);
+            match generate_domain_parameters(
+                *l,
+                *n,
+                *index,
+                &args.seed,
+                Tester::MillerRabin,
+                &limits,
+            ) {
+                Ok(params) => println!("{}", params),
+                Err(err) => {
+                    error!("{}", err);
+                    exit(1)
+                }
+            }
+            info!(
+                "Total time for generating the domain parameters: {:.4}s",
+                now.elapsed().as_secs_f64()
+            )
+        }
+        // Validar parâmetros de domínio DSA/DH
+        Operation::ValidateDomainParameters { path } => {
+            let params = match fs::read_to_string(path)
+                .ok()
+                .and_then(|text| DomainParameters::<Int>::parse(&text))
+            {
+                Some(params) => params,
+                None => {
+                    error!(
+                        "Error trying to read domain parameters from {}",
+                        path.display()
+                    );
+                    exit(1)
+                }
+            };
+            match validate_domain_parameters(&params, Tester::MillerRabin, &args.seed) {
+                Ok(()) => info!("Domain parameters are valid"),
+                Err(err) => {
+                    error!("Invalid domain parameters: {}", err);
+                    exit(1)
+                }
+            }
+        }
+        // Construir certificado de primalidade
+        Operation::Certify { n } => match certify(n, Tester::MillerRabin, &args.seed) {
+            Some(certificate) => println!("{}", certificate),
+            None => {
+                error!(
+                    "Could not certify {}: it is composite or no certificate was found",
+                    n
+                );
+                exit(1)
+            }
+        },
+        // Verificar certificados de primalidade
+        Operation::VerifyCertificate { path } => {
+            let certificates = match fs::read_to_string(path)
+                .ok()
+                .and_then(|text| parse_certificates::<Int>(&text))
+            {
+                Some(certificates) if !certificates.is_empty() => certificates,
+                _ => {
+                    error!("Error trying to read certificates from {}", path.display());
+                    exit(1)
+                }
+            };
+            for certificate in &certificates {
+                let prime = certificate.prime().unwrap();
+                match certificate.verify() {
+                    Ok(()) => info!("{} is prime", prime),
+                    Err(err) => {
+                        error!("Invalid certificate for {}: {}", prime, err);
+                        exit(1)
+                    }
+                }
+            }
+        }
+        // Fatorar números
+        Operation::Factor { numbers, config } => {
+            for n in numbers {
+                let factorization = factor(n, config, Tester::MillerRabin, &args.seed);
+                if !factorization.is_complete() {
+                    warn!("Could not fully factor {}", n);
+                }
+                println!("{}: {}", n, factorization);
+            }
+        }
+        // Testar a primalidade de números
+        Operation::Test { numbers, tester } => {
+            for n in numbers {
+                println!("{}: {}", n, tester.verdict(n, &args.seed));
+            }
+        }
+        // Testar um número de forma especial
+        Operation::Special { form } => {
+            let now = Instant::now();
+            let prime = test_special_form::<Int>(form);
+            info!(
+                "{} test finished in {:.4}s",
+                form.test_name(),
+                now.elapsed().as_secs_f64()
+            );
+            match prime {
+                true => println!("{}: prime", form),
+                false => println!("{}: composite", form),
+            }
+        }
+        // Listar pseudoprimos e mostrar como os testes se saem neles
+        Operation::Pseudoprimes { kind, bound } => {
+            let now = Instant::now();
+            let numbers = pseudoprimes(kind, *bound);
+            info!(
+                "Search for {} finished in {:.4}s",
+                kind,
+                now.elapsed().as_secs_f64()
+            );
+            let outcomes = run_testers::<Int>(&numbers, &args.seed);
+            let verdict = |fooled: bool| match fooled {
+                true => "fooled",
+                false => "rejects",
+            };
+            for outcome in &outcomes {
+                println!(
+                    "{}: fermat_tester {}, miller_rabin_tester {}",
+                    outcome.n,
+                    verdict(outcome.fermat),
+                    verdict(outcome.miller_rabin)
+                );
+            }
+            println!(
+                "{} {} up to {}: fermat_tester fooled by {}, miller_rabin_tester fooled by {}",
+                outcomes.len(),
+                kind,
+                bound,
+                outcomes.iter().filter(|outcome| outcome.fermat).count(),
+                outcomes.iter().filter(|outcome| outcome.miller_rabin).count()
+            );
+        }
+    }
+}
+
+/// Escreve a chave privada _key_ e a chave pública correspondente no formato _output_, em _out_ e _out_.pub ou na saída padrão
+fn write_key(key: &RsaPrivateKey<Int>, output: KeyOutput, out: Option<&Path>) -> io::Result<()> {
+    let public = key.public_key();
+    let (private, public) = match output {
+        KeyOutput::Text => (key.to_string().into_bytes(), Vec::new()),
+        KeyOutput::Pem(format) => (
+            key.to_pem(format).into_bytes(),
+            public.to_pem(format).into_bytes(),
+        ),
+        KeyOutput::Der(format) => (key.to_der(format), public.to_der(format)),
+    };
+    match out {
+        Some(path) => {
+            fs::write(path, private)?;
+            info!("Private key written to {}", path.display());
+            if !public.is_empty() {
+                let mut public_path = path.as_os_str().to_owned();
+                public_path.push(".pub");
+                fs::write(&public_path, public)?;
+                info!(
+                    "Public key written to {}",
+                    Path::new(&public_path).display()
+                );
+            }
+        }
+        None => {
+            let mut stdout = io::stdout();
+            stdout.write_all(&private)?;
+            if output == KeyOutput::Text {
+                writeln!(stdout)?;
+            }
+            stdout.write_all(&public)?;
+        }
+    }
+    Ok(())
+}
+
+/// Monta os limites das buscas por primos a partir dos argumentos, contando o tempo a partir de _start_
+fn search_limits(args: &ParsedArgs, start: Instant) -> SearchLimits {
+    let mut limits = SearchLimits::new();
+    if let Some(timeout) = args.timeout {
+        limits = limits.deadline(start + timeout);
+    }
+    if let Some(max) = args.max_candidates {
+        limits = limits.max_candidates(max);
     }
+    limits
 }
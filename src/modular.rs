@@ -0,0 +1,509 @@
+use num_bigint::BigUint;
+
+use crate::backend::BigInteger;
+use crate::constant_time as ct;
+
+/// Tamanho da janela utilizada na exponenciação modular
+const WINDOW_BITS: usize = 4;
+
+/// Contexto de aritmética modular de Montgomery para um módulo ímpar fixo. Guarda as constantes pré-calculadas (_-n⁻¹ mod 2⁶⁴_, _R mod n_ e _R² mod n_) e buffers de tamanho fixo, de forma que todas as rodadas de um teste sobre o mesmo candidato reutilizem a mesma memória.
+pub struct Montgomery {
+    /// Módulo original
+    modulus: BigUint,
+    /// Limbs de 64 bits do módulo, do menos significativo para o mais significativo
+    n: Vec<u64>,
+    /// Valor de -n⁻¹ mod 2⁶⁴, utilizado na redução
+    n0_inv: u64,
+    /// R² mod n, utilizado para converter valores para a forma de Montgomery
+    r2: Vec<u64>,
+    /// R mod n, que corresponde a 1 na forma de Montgomery
+    one: Vec<u64>,
+    /// n - (R mod n), que corresponde a n-1 na forma de Montgomery
+    minus_one: Vec<u64>,
+    /// Buffer auxiliar para as multiplicações, com dois limbs a mais que o módulo
+    scratch: Vec<u64>,
+    /// Buffer auxiliar para as operações in-place, com o dobro de limbs do módulo mais um
+    product: Vec<u64>,
+}
+
+impl Montgomery {
+    /// Constrói um novo contexto para o módulo _modulus_, que precisa ser ímpar e maior que 1.
+    pub fn new(modulus: &BigUint) -> Self {
+        assert!(
+            modulus.bit(0) && *modulus > BigUint::new(vec![1]),
+            "Montgomery modulus must be odd and greater than 1"
+        );
+
+        let n = modulus.to_u64_digits();
+        let s = n.len();
+
+        let mut inv: u64 = 1;
+        for _ in 0..6 {
+            inv = inv.wrapping_mul(2u64.wrapping_sub(n[0].wrapping_mul(inv)));
+        }
+
+        let r = (BigUint::new(vec![1]) << (64 * s)) % modulus;
+        let r2 = (BigUint::new(vec![1]) << (128 * s)) % modulus;
+
+        Montgomery {
+            modulus: modulus.clone(),
+            n0_inv: inv.wrapping_neg(),
+            r2: Montgomery::limbs(&r2, s),
+            one: Montgomery::limbs(&r, s),
+            minus_one: Montgomery::limbs(&(modulus - r), s),
+            scratch: vec![0; s + 2],
+            product: vec![0; 2 * s + 1],
+            n,
+        }
+    }
+
+    /// Constrói um novo contexto em tempo constante a partir dos limbs do módulo _n_, que precisa ser ímpar e maior que 1. As constantes R mod n e R² mod n são obtidas por duplicações modulares com subtração por máscara, sem divisões de tamanho variável.
+    pub fn new_ct(n: &[u64]) -> Self {
+        let s = n.len();
+
+        let mut inv: u64 = 1;
+        for _ in 0..6 {
+            inv = inv.wrapping_mul(2u64.wrapping_sub(n[0].wrapping_mul(inv)));
+        }
+
+        let mut r = vec![0; s];
+        r[0] = 1;
+        for _ in 0..64 * s {
+            Montgomery::double_ct(&mut r, n);
+        }
+        let mut r2 = r.clone();
+        for _ in 0..64 * s {
+            Montgomery::double_ct(&mut r2, n);
+        }
+        let mut minus_one = vec![0; s];
+        ct::sub(n, &r, &mut minus_one);
+
+        Montgomery {
+            modulus: BigUint::from_limbs(n),
+            n0_inv: inv.wrapping_neg(),
+            r2,
+            one: r,
+            minus_one,
+            scratch: vec![0; s + 2],
+            product: vec![0; 2 * s + 1],
+            n: n.to_vec(),
+        }
+    }
+
+    /// Retorna o módulo do contexto.
+    pub fn modulus(&self) -> &BigUint {
+        &self.modulus
+    }
+
+    /// Retorna a representação de 1 na forma de Montgomery.
+    pub fn one(&self) -> Vec<u64> {
+        self.one.clone()
+    }
+
+    /// Checa se _a_, na forma de Montgomery, corresponde a 1.
+    pub fn is_one(&self, a: &[u64]) -> bool {
+        a == self.one.as_slice()
+    }
+
+    /// Checa se _a_, na forma de Montgomery, corresponde a n-1.
+    pub fn is_minus_one(&self, a: &[u64]) -> bool {
+        a == self.minus_one.as_slice()
+    }
+
+    /// Converte _x_ para a forma de Montgomery (x·R mod n).
+    pub fn to_mont(&mut self, x: &BigUint) -> Vec<u64> {
+        let reduced = Montgomery::limbs(&(x % &self.modulus), self.n.len());
+        let r2 = self.r2.clone();
+        let mut out = vec![0; self.n.len()];
+        self.mul(&reduced, &r2, &mut out);
+        out
+    }
+
+    /// Converte _a_ da forma de Montgomery de volta para um inteiro comum.
+    pub fn from_mont(&mut self, a: &[u64]) -> BigUint {
+        let mut unit = vec![0; self.n.len()];
+        unit[0] = 1;
+        let mut out = vec![0; self.n.len()];
+        self.mul(a, &unit, &mut out);
+        BigUint::from_limbs(&out)
+    }
+
+    /// Multiplica _a_ e _b_, ambos na forma de Montgomery, escrevendo a·b·R⁻¹ mod n em _out_ (CIOS).
+    pub fn mul(&mut self, a: &[u64], b: &[u64], out: &mut [u64]) {
+        let overflow = self.reduce_product(a, b);
+        let s = self.n.len();
+        out[..s].copy_from_slice(&self.scratch[..s]);
+        if overflow || !Montgomery::less_than(out, &self.n) {
+            Montgomery::sub_in_place(out, &self.n);
+        }
+    }
+
+    /// Versão de tempo constante de [Montgomery::mul]: a subtração final é sempre calculada e o resultado é escolhido por máscara.
+    pub fn mul_ct(&mut self, a: &[u64], b: &[u64], out: &mut [u64]) {
+        let overflow = self.reduce_product(a, b);
+        let s = self.n.len();
+        let borrow = ct::sub(&self.scratch[..s], &self.n, &mut out[..s]);
+        let keep = ct::mask(!overflow) & ct::mask(borrow);
+        ct::assign(keep, &mut out[..s], &self.scratch[..s]);
+    }
+
+    /// Eleva _a_ ao quadrado em tempo constante, reaproveitando [Montgomery::mul_ct].
+    pub fn square_ct(&mut self, a: &mut [u64]) {
+        let s = self.n.len();
+        let mut product = std::mem::take(&mut self.product);
+        self.mul_ct(a, a, &mut product[..s]);
+        a.copy_from_slice(&product[..s]);
+        self.product = product;
+    }
+
+    /// Converte _x_, dado em limbs e menor que o módulo, para a forma de Montgomery em tempo constante.
+    pub fn to_mont_ct(&mut self, x: &[u64]) -> Vec<u64> {
+        let r2 = self.r2.clone();
+        let mut out = vec![0; self.n.len()];
+        self.mul_ct(x, &r2, &mut out);
+        out
+    }
+
+    /// Calcula base^exp mod n em tempo constante, com _base_ já na forma de Montgomery e _exp_ dado em limbs. São processadas sempre _exp_bits_ bits do expoente em janelas fixas, com multiplicação em todas as janelas e leitura da tabela por máscara.
+    pub fn pow_mont_ct(&mut self, base: &[u64], exp: &[u64], exp_bits: usize) -> Vec<u64> {
+        let s = self.n.len();
+        let mut table: Vec<Vec<u64>> = Vec::with_capacity(1 << WINDOW_BITS);
+        table.push(self.one.clone());
+        table.push(base.to_vec());
+        for i in 2..(1 << WINDOW_BITS) {
+            let mut next = vec![0; s];
+            self.mul_ct(&table[i - 1], &table[1], &mut next);
+            table.push(next);
+        }
+
+        let mut acc = self.one.clone();
+        let mut entry = vec![0; s];
+        for w in (0..exp_bits.div_ceil(WINDOW_BITS)).rev() {
+            for _ in 0..WINDOW_BITS {
+                self.square_ct(&mut acc);
+            }
+            ct::lookup(&table, Montgomery::window(exp, w * WINDOW_BITS), &mut entry);
+            let mut product = std::mem::take(&mut self.product);
+            self.mul_ct(&acc, &entry, &mut product[..s]);
+            acc.copy_from_slice(&product[..s]);
+            self.product = product;
+        }
+        acc
+    }
+
+    /// Versão de tempo constante de [Montgomery::is_one].
+    pub fn ct_is_one(&self, a: &[u64]) -> bool {
+        ct::eq(a, &self.one)
+    }
+
+    /// Versão de tempo constante de [Montgomery::is_minus_one].
+    pub fn ct_is_minus_one(&self, a: &[u64]) -> bool {
+        ct::eq(a, &self.minus_one)
+    }
+
+    /// Calcula 2·_x_ mod _n_ in-place, para _x_ < _n_, em tempo constante.
+    fn double_ct(x: &mut [u64], n: &[u64]) {
+        let mut carry = 0;
+        for limb in x.iter_mut() {
+            let next = *limb >> 63;
+            *limb = (*limb << 1) | carry;
+            carry = next;
+        }
+        let mut reduced = vec![0; x.len()];
+        let borrow = ct::sub(x, n, &mut reduced);
+        ct::assign(ct::mask(carry == 1) | ct::mask(!borrow), x, &reduced);
+    }
+
+    /// Calcula a·b·R⁻¹ mod n no buffer auxiliar, com o resultado menor que 2n, e retorna se houve estouro do limb mais significativo.
+    fn reduce_product(&mut self, a: &[u64], b: &[u64]) -> bool {
+        let s = self.n.len();
+        let n = &self.n;
+        let t = &mut self.scratch[..s + 2];
+        for limb in t.iter_mut() {
+            *limb = 0;
+        }
+        for &bi in &b[..s] {
+            let mut carry: u64 = 0;
+            for (tj, &aj) in t[..s].iter_mut().zip(&a[..s]) {
+                let sum = *tj as u128 + aj as u128 * bi as u128 + carry as u128;
+                *tj = sum as u64;
+                carry = (sum >> 64) as u64;
+            }
+            let sum = t[s] as u128 + carry as u128;
+            t[s] = sum as u64;
+            t[s + 1] = (sum >> 64) as u64;
+
+            let m = t[0].wrapping_mul(self.n0_inv) as u128;
+            let sum = t[0] as u128 + m * n[0] as u128;
+            let mut carry = (sum >> 64) as u64;
+            for (j, &nj) in n.iter().enumerate().skip(1) {
+                let sum = t[j] as u128 + m * nj as u128 + carry as u128;
+                t[j - 1] = sum as u64;
+                carry = (sum >> 64) as u64;
+            }
+            let sum = t[s] as u128 + carry as u128;
+            t[s - 1] = sum as u64;
+            t[s] = t[s + 1] + (sum >> 64) as u64;
+        }
+
+        t[s] != 0
+    }
+
+    /// Eleva _a_, na forma de Montgomery, ao quadrado sem alocar memória nova. Aproveita a simetria dos produtos cruzados (SOS), realizando cerca de metade das multiplicações de [Montgomery::mul].
+    pub fn square(&mut self, a: &mut [u64]) {
+        let s = self.n.len();
+        let n = &self.n;
+        let t = &mut self.product[..2 * s + 1];
+        for limb in t.iter_mut() {
+            *limb = 0;
+        }
+
+        for i in 0..s {
+            let ai = a[i] as u128;
+            let mut carry: u64 = 0;
+            for (tij, &aj) in t[2 * i + 1..i + s].iter_mut().zip(&a[i + 1..s]) {
+                let sum = *tij as u128 + ai * aj as u128 + carry as u128;
+                *tij = sum as u64;
+                carry = (sum >> 64) as u64;
+            }
+            t[i + s] = carry;
+        }
+
+        let mut top = 0;
+        for limb in t[..2 * s].iter_mut() {
+            let next = *limb >> 63;
+            *limb = (*limb << 1) | top;
+            top = next;
+        }
+
+        let mut carry: u64 = 0;
+        for i in 0..s {
+            let sq = a[i] as u128 * a[i] as u128;
+            let sum = t[2 * i] as u128 + (sq as u64) as u128 + carry as u128;
+            t[2 * i] = sum as u64;
+            let sum = t[2 * i + 1] as u128 + (sq >> 64) + (sum >> 64);
+            t[2 * i + 1] = sum as u64;
+            carry = (sum >> 64) as u64;
+        }
+
+        for i in 0..s {
+            let m = t[i].wrapping_mul(self.n0_inv) as u128;
+            let mut carry: u64 = 0;
+            for (tij, &nj) in t[i..i + s].iter_mut().zip(n.iter()) {
+                let sum = *tij as u128 + m * nj as u128 + carry as u128;
+                *tij = sum as u64;
+                carry = (sum >> 64) as u64;
+            }
+            let mut k = i + s;
+            while carry != 0 {
+                let (sum, over) = t[k].overflowing_add(carry);
+                t[k] = sum;
+                carry = over as u64;
+                k += 1;
+            }
+        }
+
+        let overflow = t[2 * s] != 0;
+        a[..s].copy_from_slice(&t[s..2 * s]);
+        if overflow || !Montgomery::less_than(a, n) {
+            Montgomery::sub_in_place(a, n);
+        }
+    }
+
+    /// Calcula base^exp mod n e retorna o resultado na forma de Montgomery, usando janelas fixas de [WINDOW_BITS] bits.
+    pub fn pow_mont(&mut self, base: &BigUint, exp: &BigUint) -> Vec<u64> {
+        let s = self.n.len();
+        let mut table: Vec<Vec<u64>> = Vec::with_capacity(1 << WINDOW_BITS);
+        table.push(self.one.clone());
+        table.push(self.to_mont(base));
+        for i in 2..(1 << WINDOW_BITS) {
+            let mut next = vec![0; s];
+            self.mul(&table[i - 1], &table[1], &mut next);
+            table.push(next);
+        }
+
+        let mut acc = self.one.clone();
+        let bits = exp.bits() as usize;
+        let windows = bits.div_ceil(WINDOW_BITS);
+        let digits = exp.to_u64_digits();
+
+        for w in (0..windows).rev() {
+            for _ in 0..WINDOW_BITS {
+                self.square(&mut acc);
+            }
+            let index = Montgomery::window(&digits, w * WINDOW_BITS);
+            if index != 0 {
+                self.mul_in_place(&mut acc, &table[index]);
+            }
+        }
+        acc
+    }
+
+    /// Calcula base^exp mod n como um inteiro comum.
+    pub fn modpow(&mut self, base: &BigUint, exp: &BigUint) -> BigUint {
+        let result = self.pow_mont(base, exp);
+        self.from_mont(&result)
+    }
+
+    /// Multiplica _a_ por _b_ e guarda o resultado em _a_.
+    fn mul_in_place(&mut self, a: &mut [u64], b: &[u64]) {
+        let s = self.n.len();
+        let mut product = std::mem::take(&mut self.product);
+        self.mul(a, b, &mut product[..s]);
+        a.copy_from_slice(&product[..s]);
+        self.product = product;
+    }
+
+    /// Extrai [WINDOW_BITS] bits do expoente a partir da posição _pos_.
+    fn window(digits: &[u64], pos: usize) -> usize {
+        let limb = pos / 64;
+        let offset = pos % 64;
+        let mask = (1u64 << WINDOW_BITS) - 1;
+        let low = digits.get(limb).copied().unwrap_or(0) >> offset;
+        let high = if offset + WINDOW_BITS > 64 {
+            digits.get(limb + 1).copied().unwrap_or(0) << (64 - offset)
+        } else {
+            0
+        };
+        ((low | high) & mask) as usize
+    }
+
+    /// Converte _x_ em um vetor de exatamente _s_ limbs.
+    fn limbs(x: &BigUint, s: usize) -> Vec<u64> {
+        let mut digits = x.to_u64_digits();
+        digits.resize(s, 0);
+        digits
+    }
+
+    /// Checa se _a_ < _b_, considerando vetores de mesmo tamanho.
+    fn less_than(a: &[u64], b: &[u64]) -> bool {
+        for i in (0..a.len()).rev() {
+            if a[i] != b[i] {
+                return a[i] < b[i];
+            }
+        }
+        false
+    }
+
+    /// Subtrai _b_ de _a_ in-place, ignorando o empréstimo final.
+    fn sub_in_place(a: &mut [u64], b: &[u64]) {
+        let mut borrow = false;
+        for i in 0..a.len() {
+            let (diff, b1) = a[i].overflowing_sub(b[i]);
+            let (diff, b2) = diff.overflowing_sub(borrow as u64);
+            a[i] = diff;
+            borrow = b1 | b2;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rand_gen::Mlcg;
+
+    /// Módulos de um e de vários limbs, incluindo o menor possível e os logo abaixo de 2^(64k)
+    fn moduli() -> Vec<BigUint> {
+        let one = BigUint::new(vec![1]);
+        let mut moduli: Vec<BigUint> = [3u64, 5, 0xFFFF_FFFF, 0xFFFF_FFFF_FFFF_FFC5, u64::MAX]
+            .iter()
+            .map(|&n| BigUint::from(n))
+            .collect();
+        for k in 1..=4 {
+            moduli.push((one.clone() << (64 * k)) - BigUint::from(1u32));
+            moduli.push((one.clone() << (64 * k)) + BigUint::from(13u32));
+        }
+        let mut gen: Mlcg<BigUint> = Mlcg::new_std(200, &BigUint::from(5429u32));
+        moduli.extend((0..4).map(|_| gen.rand() | one.clone()));
+        moduli
+    }
+
+    /// Valores de teste módulo _n_: 0, 1, n-1, valores sorteados e um maior que n
+    fn values(n: &BigUint, gen: &mut Mlcg<BigUint>) -> Vec<BigUint> {
+        let one = BigUint::new(vec![1]);
+        let mut values = vec![BigUint::default(), one.clone(), n - &one];
+        values.extend((0..4).map(|_| gen.rand() % n));
+        values.push(n + BigUint::from(7u32));
+        values
+    }
+
+    #[test]
+    fn arithmetic_matches_biguint() {
+        let mut gen: Mlcg<BigUint> = Mlcg::new_std(256, &BigUint::from(7u32));
+        for n in moduli() {
+            let s = n.to_u64_digits().len();
+            let mut mont = Montgomery::new(&n);
+            let mut mont_ct = Montgomery::new_ct(&Montgomery::limbs(&n, s));
+            let values = values(&n, &mut gen);
+            for a in &values {
+                let a_mont = mont.to_mont(a);
+                let a_ct = mont_ct.to_mont_ct(&Montgomery::limbs(&(a % &n), s));
+                assert_eq!(a_ct, a_mont, "{} mod {}", a, n);
+                assert_eq!(mont.from_mont(&a_mont), a % &n);
+
+                let square = (a * a) % &n;
+                let mut sq = a_mont.clone();
+                mont.square(&mut sq);
+                assert_eq!(mont.from_mont(&sq), square, "{}² mod {}", a, n);
+                let mut sq_ct = a_ct.clone();
+                mont_ct.square_ct(&mut sq_ct);
+                assert_eq!(mont.from_mont(&sq_ct), square, "{}² mod {} (ct)", a, n);
+
+                for b in &values {
+                    let b_mont = mont.to_mont(b);
+                    let product = (a * b) % &n;
+                    let mut out = vec![0; s];
+                    mont.mul(&a_mont, &b_mont, &mut out);
+                    assert_eq!(mont.from_mont(&out), product, "{}·{} mod {}", a, b, n);
+                    mont_ct.mul_ct(&a_mont, &b_mont, &mut out);
+                    assert_eq!(mont.from_mont(&out), product, "{}·{} mod {} (ct)", a, b, n);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn exponentiation_matches_biguint() {
+        let mut gen: Mlcg<BigUint> = Mlcg::new_std(256, &BigUint::from(11u32));
+        for n in moduli() {
+            let s = n.to_u64_digits().len();
+            let mut mont = Montgomery::new(&n);
+            let mut mont_ct = Montgomery::new_ct(&Montgomery::limbs(&n, s));
+            let one = BigUint::new(vec![1]);
+            let mut exponents = vec![BigUint::default(), one.clone(), BigUint::from(2u32)];
+            exponents.push(&n - &one);
+            exponents.extend((0..3).map(|_| gen.rand()));
+            for base in values(&n, &mut gen) {
+                let base_ct = mont_ct.to_mont_ct(&Montgomery::limbs(&(&base % &n), s));
+                for exp in &exponents {
+                    let expected = base.modpow(exp, &n);
+                    assert_eq!(
+                        mont.modpow(&base, exp),
+                        expected,
+                        "{}^{} mod {}",
+                        base,
+                        exp,
+                        n
+                    );
+                    let pow = mont.pow_mont(&base, exp);
+                    assert_eq!(mont.from_mont(&pow), expected);
+
+                    // O expoente em tempo constante é processado com uma quantidade fixa de bits, maior que o necessário
+                    let exp_limbs = exp.to_u64_digits();
+                    for exp_bits in [exp.bits() as usize, 64 * exp_limbs.len().max(1) + 8] {
+                        let pow = mont_ct.pow_mont_ct(&base_ct, &exp_limbs, exp_bits);
+                        assert_eq!(
+                            mont.from_mont(&pow),
+                            expected,
+                            "{}^{} mod {} (ct, {} bits)",
+                            base,
+                            exp,
+                            n,
+                            exp_bits
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use crate::asn1::DecodeError;
+
+/// Alfabeto do base64 padrão (RFC 4648)
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Quantidade de caracteres por linha no corpo de um PEM (RFC 7468)
+const LINE_WIDTH: usize = 64;
+
+/// Codifica _data_ em base64 com padding.
+pub fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, byte)| acc | (*byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => out.push(BASE64_ALPHABET[(group >> (18 - 6 * i)) as usize & 0x3f] as char),
+                false => out.push('='),
+            }
+        }
+    }
+    out
+}
+
+/// Decodifica _text_ em base64, ignorando espaços em branco.
+pub fn base64_decode(text: &str) -> Result<Vec<u8>, DecodeError> {
+    let symbols: Vec<u8> = text.bytes().filter(|c| !c.is_ascii_whitespace()).collect();
+    if !symbols.len().is_multiple_of(4) {
+        return Err(DecodeError::InvalidPem(
+            "base64 length is not a multiple of 4",
+        ));
+    }
+    let mut out = Vec::with_capacity(symbols.len() / 4 * 3);
+    for (n, chunk) in symbols.chunks(4).enumerate() {
+        let last = n == symbols.len() / 4 - 1;
+        let padding = chunk.iter().rev().take_while(|c| **c == b'=').count();
+        if padding > 2 || (padding > 0 && !last) {
+            return Err(DecodeError::InvalidPem("misplaced base64 padding"));
+        }
+        let mut group = 0u32;
+        for (i, c) in chunk[..4 - padding].iter().enumerate() {
+            let value = BASE64_ALPHABET
+                .iter()
+                .position(|symbol| symbol == c)
+                .ok_or(DecodeError::InvalidPem("invalid base64 character"))?;
+            group |= (value as u32) << (18 - 6 * i);
+        }
+        out.extend_from_slice(&group.to_be_bytes()[1..4 - padding]);
+    }
+    Ok(out)
+}
+
+/// Envolve _der_ em uma armadura PEM com o rótulo _label_.
+pub fn encode(label: &str, der: &[u8]) -> String {
+    let body = base64_encode(der);
+    let mut out = format!("-----BEGIN {}-----\n", label);
+    for line in body.as_bytes().chunks(LINE_WIDTH) {
+        out.push_str(std::str::from_utf8(line).unwrap());
+        out.push('\n');
+    }
+    out.push_str(&format!("-----END {}-----\n", label));
+    out
+}
+
+/// Lê o primeiro bloco PEM de _text_, retornando o rótulo e o conteúdo DER.
+pub fn decode(text: &str) -> Result<(String, Vec<u8>), DecodeError> {
+    let mut lines = text.lines().map(str::trim);
+    let label = lines
+        .find_map(|line| {
+            line.strip_prefix("-----BEGIN ")
+                .and_then(|rest| rest.strip_suffix("-----"))
+        })
+        .ok_or(DecodeError::InvalidPem("missing BEGIN line"))?
+        .to_string();
+    let end = format!("-----END {}-----", label);
+    let mut body = String::new();
+    for line in lines {
+        if line == end {
+            return Ok((label, base64_decode(&body)?));
+        }
+        body.push_str(line);
+    }
+    Err(DecodeError::InvalidPem("missing END line"))
+}
@@ -1,46 +1,134 @@
-use num_bigint::{BigUint, ToBigUint};
+use std::fmt;
+
+use crate::backend::{BigInteger, ModularContext};
 
 // TODO: Revisar o método e documentar
 
+use crate::constant_time as ct;
+use crate::modular::Montgomery;
 use crate::rand_gen::Mlcg;
 
-/// Aplica a checagem de Miller-Rabin para determinar se o numero é primo
-pub fn miller_rabin_tester(num: &BigUint, seed: &BigUint) -> bool {
-    let mut gen = Mlcg::new_mersene_from_seed(16807.to_biguint().unwrap(), 31, 32, seed);
-    // TODO: Paralelizar as checagens
-    for _ in 0..20 {
-        if !miller_rabin_witness(num, gen.rand()) {
-            return false;
+/// Quantidade de rodadas (testemunhas) de cada teste
+const ROUNDS: u32 = 20;
+
+/// Resultado detalhado de um teste de primalidade, dizendo por que um composto foi rejeitado
+#[derive(Clone, Debug, PartialEq)]
+pub enum Verdict<T> {
+    /// n < 2, que não é primo nem composto
+    Trivial,
+    /// Composto, com um fator pequeno achado na checagem básica
+    SmallFactor { factor: T },
+    /// Composto: witness^(n-1) ≢ 1 (mod n), contrariando o pequeno teorema de Fermat
+    FermatWitness { witness: T },
+    /// Composto: witness^((n-1)/2) ≢ (witness/n) (mod n), contrariando o critério de Euler
+    EulerWitness { witness: T },
+    /// Composto: root² ≡ 1 com root ≢ ±1 (mod n), achada a partir de _witness_; mdc(root - 1, n) é um fator próprio
+    SquareRoot { witness: T, root: T, factor: T },
+    /// Composto: (X + witness)^n ≢ X^n + witness (mod X^r - 1, n), a congruência do teste AKS
+    PolynomialWitness { witness: T, r: u64 },
+    /// Primo provável depois de _rounds_ rodadas, com probabilidade de erro até _error_bound_ (0 para primos pequenos, decididos sem rodadas). As testemunhas múltiplas de n são descartadas e não contam como rodadas.
+    ProbablePrime { rounds: u32, error_bound: f64 },
+}
+
+impl<T: BigInteger> Verdict<T> {
+    /// Checa se o número passou no teste.
+    pub fn is_probable_prime(&self) -> bool {
+        matches!(self, Verdict::ProbablePrime { .. })
+    }
+
+    /// Retorna o fator revelado pelo teste, se houver.
+    pub fn factor(&self) -> Option<&T> {
+        match self {
+            Verdict::SmallFactor { factor } | Verdict::SquareRoot { factor, .. } => Some(factor),
+            _ => None,
         }
     }
-    true
 }
 
-/// Checagem de Miller-Rabin para determinar se o número é um forte candidato a primo (75%)
-fn miller_rabin_witness(num: &BigUint, wit: BigUint) -> bool {
-    if basic_non_prime_check(&num) {
-        return false;
+impl<T: BigInteger> fmt::Display for Verdict<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Trivial => write!(f, "neither prime nor composite"),
+            Verdict::SmallFactor { factor } => write!(f, "composite, divisible by {}", factor),
+            Verdict::FermatWitness { witness } => {
+                write!(f, "composite, Fermat witness {}^(n-1) ≢ 1 (mod n)", witness)
+            }
+            Verdict::EulerWitness { witness } => write!(
+                f,
+                "composite, Euler witness {0}^((n-1)/2) ≢ ({0}/n) (mod n)",
+                witness
+            ),
+            Verdict::SquareRoot {
+                witness,
+                root,
+                factor,
+            } => write!(
+                f,
+                "composite, {} is a nontrivial square root of 1 (witness {}), revealing the factor {}",
+                root, witness, factor
+            ),
+            Verdict::PolynomialWitness { witness, r } => write!(
+                f,
+                "composite, (X + {0})^n ≢ X^n + {0} (mod X^{1} - 1, n)",
+                witness, r
+            ),
+            Verdict::ProbablePrime { rounds: 0, .. } => write!(f, "prime"),
+            Verdict::ProbablePrime {
+                rounds,
+                error_bound,
+            } => write!(
+                f,
+                "probable prime after {} rounds (error probability ≤ 2^-{:.0})",
+                rounds,
+                -error_bound.log2()
+            ),
+        }
     }
-    
+}
+
+/// Aplica a checagem de Miller-Rabin para determinar se o numero é primo
+pub fn miller_rabin_tester<T: BigInteger>(num: &T, seed: &T) -> bool {
+    if let Some(verdict) = basic_verdict(num) {
+        return verdict.is_probable_prime();
+    }
+
+    let mut gen = Mlcg::new_mersene_from_seed(T::from_u64(16807), 31, 32, seed);
+    let mut ctx = T::Context::new(num);
+
     let mut s = 0;
-    let mut d = &num.clone() - BigUint::new(vec![1]);
+    let mut d = num.clone() - T::one();
 
-    while &d & BigUint::new(vec![1]) == BigUint::default() {
+    while d.is_even() {
         s += 1;
-        d >>= 1;
+        d = d.shr(1);
+    }
+
+    // TODO: Paralelizar as checagens
+    for _ in 0..ROUNDS {
+        let wit = gen.rand() % num;
+        if wit == T::zero() {
+            continue;
+        }
+        if !miller_rabin_witness(&mut ctx, &d, s, wit) {
+            return false;
+        }
     }
+    true
+}
 
-    let mut witmodpow = wit.modpow(&d, &num);
+/// Checagem de Miller-Rabin para determinar se o número é um forte candidato a primo (75%), com num-1 = d·2^s
+fn miller_rabin_witness<T: BigInteger>(ctx: &mut T::Context, d: &T, mut s: u64, wit: T) -> bool {
+    let mut witmodpow = ctx.pow(&wit, d);
 
-    if witmodpow == BigUint::new(vec![1]) {
+    if ctx.is_one(&witmodpow) {
         return true;
     }
 
     while s > 0 {
-        if witmodpow == num - BigUint::new(vec![1]) {
+        if ctx.is_minus_one(&witmodpow) {
             return true;
         } else {
-            witmodpow = &witmodpow * &witmodpow % num;
+            ctx.square(&mut witmodpow);
             s -= 1
         }
     }
@@ -48,28 +136,669 @@ fn miller_rabin_witness(num: &BigUint, wit: BigUint) -> bool {
 }
 
 /// Aplica a checagem de Fermat para determinar se o numero é primo
-pub fn fermat_tester(num: &BigUint, seed: &BigUint) -> bool {
-    let mut gen = Mlcg::new_mersene_from_seed(16807.to_biguint().unwrap(), 31, 32, seed);
+pub fn fermat_tester<T: BigInteger>(num: &T, seed: &T) -> bool {
+    if let Some(verdict) = basic_verdict(num) {
+        return verdict.is_probable_prime();
+    }
+
+    let mut gen = Mlcg::new_mersene_from_seed(T::from_u64(16807), 31, 32, seed);
+    let mut ctx = T::Context::new(num);
+    let exp = num.clone() - T::one();
+
     // TODO: Paralelizar as checagens
-    for _ in 0..20 {
-        if !fermat_witness(num, gen.rand()) {
+    for _ in 0..ROUNDS {
+        let wit = gen.rand() % num;
+        if wit == T::zero() {
+            continue;
+        }
+        if !fermat_witness::<T>(&mut ctx, &exp, wit) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Checagem de Fermat para determinar se o número é um forte candidato a primo, com _exp_ = num-1
+fn fermat_witness<T: BigInteger>(ctx: &mut T::Context, exp: &T, wit: T) -> bool {
+    let witmodpow = ctx.pow(&wit, exp);
+    ctx.is_one(&witmodpow)
+}
+
+/// Aplica a checagem de Solovay-Strassen para determinar se o número é primo
+pub fn solovay_strassen_tester<T: BigInteger>(num: &T, seed: &T) -> bool {
+    if let Some(verdict) = basic_verdict(num) {
+        return verdict.is_probable_prime();
+    }
+
+    let mut gen = Mlcg::new_mersene_from_seed(T::from_u64(16807), 31, 32, seed);
+    let mut ctx = T::Context::new(num);
+    let exp = (num.clone() - T::one()).shr(1);
+
+    for _ in 0..ROUNDS {
+        // Reduzida como no veredito: uma testemunha múltipla de num daria (wit/num) = 0 mesmo com num primo
+        let wit = gen.rand() % num;
+        if wit == T::zero() {
+            continue;
+        }
+        if !solovay_strassen_witness(&mut ctx, num, &exp, wit) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Checagem de Solovay-Strassen (critério de Euler) para determinar se o número é um forte candidato a primo (50%), com _exp_ = (num-1)/2
+fn solovay_strassen_witness<T: BigInteger>(ctx: &mut T::Context, num: &T, exp: &T, wit: T) -> bool {
+    let witmodpow = ctx.pow(&wit, exp);
+    match jacobi(&wit, num) {
+        1 => ctx.is_one(&witmodpow),
+        -1 => ctx.is_minus_one(&witmodpow),
+        _ => false,
+    }
+}
+
+/// Versão de [miller_rabin_tester] que retorna o [Verdict], com as mesmas testemunhas: uma testemunha que leva a 1 por um valor diferente de ±1 revela uma raiz quadrada não trivial de 1 (e um fator), e uma que nem chega a 1 é uma testemunha de Fermat. A probabilidade de erro é de até 4^-20.
+pub fn miller_rabin_verdict<T: BigInteger>(num: &T, seed: &T) -> Verdict<T> {
+    if let Some(verdict) = basic_verdict(num) {
+        return verdict;
+    }
+
+    let mut gen = Mlcg::new_mersene_from_seed(T::from_u64(16807), 31, 32, seed);
+    let minus_one = num.clone() - T::one();
+
+    let mut s = 0;
+    let mut d = minus_one.clone();
+    while d.is_even() {
+        s += 1;
+        d = d.shr(1);
+    }
+
+    let mut rounds = 0;
+    for _ in 0..ROUNDS {
+        let witness = gen.rand() % num;
+        if witness == T::zero() {
+            continue;
+        }
+        rounds += 1;
+        let mut x = witness.modpow(&d, num);
+        if x == T::one() || x == minus_one {
+            continue;
+        }
+        let mut passed = false;
+        for i in 1..=s {
+            let square = x.clone() * &x % num;
+            if square == T::one() {
+                let factor = (x.clone() - T::one()).gcd(num);
+                return Verdict::SquareRoot {
+                    witness,
+                    root: x,
+                    factor,
+                };
+            }
+            if square == minus_one && i < s {
+                passed = true;
+                break;
+            }
+            x = square;
+        }
+        if !passed {
+            return Verdict::FermatWitness { witness };
+        }
+    }
+    Verdict::ProbablePrime {
+        rounds,
+        error_bound: 0.25f64.powi(rounds as i32),
+    }
+}
+
+/// Versão de [fermat_tester] que retorna o [Verdict], com as mesmas testemunhas. A probabilidade de erro de 2^-20 vale só para compostos que não são números de Carmichael, que passam por qualquer testemunha coprima com eles.
+pub fn fermat_verdict<T: BigInteger>(num: &T, seed: &T) -> Verdict<T> {
+    if let Some(verdict) = basic_verdict(num) {
+        return verdict;
+    }
+
+    let mut gen = Mlcg::new_mersene_from_seed(T::from_u64(16807), 31, 32, seed);
+    let exp = num.clone() - T::one();
+
+    let mut rounds = 0;
+    for _ in 0..ROUNDS {
+        let witness = gen.rand() % num;
+        if witness == T::zero() {
+            continue;
+        }
+        rounds += 1;
+        if witness.modpow(&exp, num) != T::one() {
+            return Verdict::FermatWitness { witness };
+        }
+    }
+    Verdict::ProbablePrime {
+        rounds,
+        error_bound: 0.5f64.powi(rounds as i32),
+    }
+}
+
+/// Versão de [solovay_strassen_tester] que retorna o [Verdict], com as mesmas testemunhas. Uma testemunha com (a/n) = 0 tem um fator em comum com n. A probabilidade de erro é de até 2^-20.
+pub fn solovay_strassen_verdict<T: BigInteger>(num: &T, seed: &T) -> Verdict<T> {
+    if let Some(verdict) = basic_verdict(num) {
+        return verdict;
+    }
+
+    let mut gen = Mlcg::new_mersene_from_seed(T::from_u64(16807), 31, 32, seed);
+    let minus_one = num.clone() - T::one();
+    let exp = minus_one.shr(1);
+
+    let mut rounds = 0;
+    for _ in 0..ROUNDS {
+        let witness = gen.rand() % num;
+        if witness == T::zero() {
+            continue;
+        }
+        rounds += 1;
+        let expected = match jacobi(&witness, num) {
+            1 => T::one(),
+            -1 => minus_one.clone(),
+            _ => {
+                return Verdict::SmallFactor {
+                    factor: witness.gcd(num),
+                }
+            }
+        };
+        if witness.modpow(&exp, num) != expected {
+            return Verdict::EulerWitness { witness };
+        }
+    }
+    Verdict::ProbablePrime {
+        rounds,
+        error_bound: 0.5f64.powi(rounds as i32),
+    }
+}
+
+/// Calcula o símbolo de Jacobi (_a_/_n_) para _n_ ímpar pelo algoritmo binário, que só usa deslocamentos, subtrações e comparações: as potências de 2 de a saem pela segunda lei suplementar ((2/n) = -1 se n ≡ 3, 5 mod 8), e com a e n ímpares a troca pela reciprocidade quadrática é seguida de a ← a - n.
+pub fn jacobi<T: BigInteger>(a: &T, n: &T) -> i32 {
+    assert!(!n.is_even(), "the Jacobi symbol needs an odd n");
+    let (mut a, mut n) = match a.bits() > n.bits() {
+        true => (a.clone() % n, n.clone()),
+        false => (a.clone(), n.clone()),
+    };
+    let mut result = 1;
+    while a != T::zero() {
+        let zeros = (0..).find(|&i| a.bit(i)).unwrap();
+        a = a.shr(zeros);
+        if zeros % 2 == 1 && matches!(low_bits(&n, 3), 3 | 5) {
+            result = -result;
+        }
+        if a < n {
+            std::mem::swap(&mut a, &mut n);
+            if low_bits(&a, 2) == 3 && low_bits(&n, 2) == 3 {
+                result = -result;
+            }
+        }
+        a = a - n.clone();
+    }
+    match n == T::one() {
+        true => result,
+        false => 0,
+    }
+}
+
+/// Teste AKS (Agrawal, Kayal e Saxena), determinístico e de tempo polinomial, com o limite de Lenstra para as testemunhas. Prático só para números pequenos e médios (um primo de 16 bits leva menos de meio segundo e um de 30 bits algumas dezenas de segundos), serve de referência para os testes probabilísticos.
+pub fn aks_tester<T: BigInteger>(num: &T) -> bool {
+    aks_verdict(num).is_probable_prime()
+}
+
+/// Versão de [aks_tester] que retorna o [Verdict]. Os passos são: rejeitar potências perfeitas a^b (b > 1); achar o menor r com ord_r(n) > log²n, rejeitando n se algum valor até r tiver um fator em comum com ele (e aceitando se n ≤ r); e checar (X + a)^n ≡ X^n + a (mod X^r - 1, n) para a = 1, ..., ⌊√φ(r)·log n⌋. Um primo é reportado como primo sem rodadas, já que o teste não erra.
+///
+/// A versão de Lenstra e Pomerance, que troca X^r - 1 por polinômios construídos a partir de períodos gaussianos e reduz o expoente da complexidade de 7,5 para 6, não é implementada: a construção desses polinômios é bem mais complexa e, para os tamanhos em que o AKS é viável, o ganho não compensa.
+pub fn aks_verdict<T: BigInteger>(num: &T) -> Verdict<T> {
+    if let Some(verdict) = basic_verdict(num) {
+        return verdict;
+    }
+    if let Some(root) = perfect_power_root(num) {
+        return Verdict::SmallFactor { factor: root };
+    }
+
+    let log = log2(num);
+    let mut r: u64 = 2;
+    loop {
+        if *num <= T::from_u64(r) {
+            return Verdict::ProbablePrime {
+                rounds: 0,
+                error_bound: 0.0,
+            };
+        }
+        let residue = low_limb(&(num.clone() % T::from_u64(r)));
+        let common = gcd_u64(residue, r);
+        if common > 1 {
+            return Verdict::SmallFactor {
+                factor: T::from_u64(common),
+            };
+        }
+        if multiplicative_order(residue, r, log * log).is_none() {
+            break;
+        }
+        r += 1;
+    }
+
+    let bound = ((euler_phi(r) as f64).sqrt() * log).floor() as u64;
+    let ring = PolynomialRing::new(num, r);
+    let power = low_limb(&(num.clone() % T::from_u64(r))) as usize;
+    for a in 1..=bound {
+        let a = T::from_u64(a) % num;
+        let mut expected = vec![T::zero(); r as usize];
+        expected[power] = T::one();
+        expected[0] = (expected[0].clone() + &a) % num;
+        if ring.binomial_power(&a) != expected {
+            return Verdict::PolynomialWitness { witness: a, r };
+        }
+    }
+    Verdict::ProbablePrime {
+        rounds: 0,
+        error_bound: 0.0,
+    }
+}
+
+/// Anel Z_n[X]/(X^r - 1), com os polinômios guardados como vetores de r coeficientes em [0, n)
+struct PolynomialRing<'a, T> {
+    n: &'a T,
+    r: usize,
+    /// Bytes por coeficiente na substituição de Kronecker, o bastante para a soma dos r produtos de coeficientes
+    slot: usize,
+}
+
+impl<'a, T: BigInteger> PolynomialRing<'a, T> {
+    fn new(n: &'a T, r: u64) -> Self {
+        let bits = 2 * n.bits() + 64 - r.leading_zeros() as u64;
+        PolynomialRing {
+            n,
+            r: r as usize,
+            slot: bits.div_ceil(8) as usize,
+        }
+    }
+
+    /// Eleva _a_ ao quadrado pela substituição de Kronecker: o polinômio vira um único inteiro, com os coeficientes em blocos de _slot_ bytes, e um só produto de inteiros dá todos os coeficientes, depois reduzidos por X^r = 1 e módulo n
+    fn square(&self, a: &[T]) -> Vec<T> {
+        let packed = self.pack(a);
+        self.unpack(packed.clone() * packed)
+    }
+
+    fn pack(&self, a: &[T]) -> T {
+        let mut bytes = Vec::with_capacity(a.len() * self.slot);
+        for coefficient in a.iter().rev() {
+            bytes.extend(coefficient.to_bytes_be_padded(self.slot).unwrap());
+        }
+        T::from_bytes_be(&bytes)
+    }
+
+    fn unpack(&self, product: T) -> Vec<T> {
+        let bytes = product.to_bytes_be();
+        let mut result = vec![T::zero(); self.r];
+        for (i, block) in bytes.rchunks(self.slot).enumerate() {
+            let index = i % self.r;
+            result[index] = result[index].clone() + T::from_bytes_be(block);
+        }
+        result
+            .into_iter()
+            .map(|coefficient| coefficient % self.n)
+            .collect()
+    }
+
+    /// Multiplica _p_ por X + _a_, o que só desloca os coeficientes e soma a·p
+    fn mul_linear(&self, p: &[T], a: &T) -> Vec<T> {
+        (0..self.r)
+            .map(|i| {
+                let shifted = p[(i + self.r - 1) % self.r].clone();
+                (a.clone() * &p[i] + shifted) % self.n
+            })
+            .collect()
+    }
+
+    /// Calcula (X + _a_)^n percorrendo os bits de n
+    fn binomial_power(&self, a: &T) -> Vec<T> {
+        let mut result = vec![T::zero(); self.r];
+        result[0] = T::one();
+        for i in (0..self.n.bits()).rev() {
+            result = self.square(&result);
+            if self.n.bit(i) {
+                result = self.mul_linear(&result, a);
+            }
+        }
+        result
+    }
+}
+
+/// Retorna a raiz a de _n_ = a^b, com b > 1, se n for uma potência perfeita
+fn perfect_power_root<T: BigInteger>(n: &T) -> Option<T> {
+    (2..n.bits()).find_map(|b| {
+        let root = integer_root(n, b as u32);
+        match root.pow(b as u32) == *n {
+            true => Some(root),
+            false => None,
+        }
+    })
+}
+
+/// Raiz _k_-ésima inteira de _n_ (arredondada para baixo) pelo método de Newton, partindo de 2^⌈bits/k⌉, que está acima da raiz
+fn integer_root<T: BigInteger>(n: &T, k: u32) -> T {
+    let k_big = T::from_u64(k as u64);
+    let mut x = T::one().shl(n.bits().div_ceil(k as u64));
+    loop {
+        let y = (x.clone() * T::from_u64(k as u64 - 1) + n.clone() / x.pow(k - 1)) / k_big.clone();
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+/// Logaritmo na base 2 de _n_, a partir dos 64 bits mais significativos
+fn log2<T: BigInteger>(n: &T) -> f64 {
+    let shift = n.bits().saturating_sub(64);
+    shift as f64 + (low_limb(&n.shr(shift)) as f64).log2()
+}
+
+/// Retorna o limb menos significativo de _n_
+fn low_limb<T: BigInteger>(n: &T) -> u64 {
+    n.to_limbs().first().copied().unwrap_or(0)
+}
+
+fn gcd_u64(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    a
+}
+
+/// Ordem multiplicativa de _a_ módulo _r_, se for no máximo _limit_
+fn multiplicative_order(a: u64, r: u64, limit: f64) -> Option<u64> {
+    let mut power = a % r;
+    let mut order = 1;
+    while power != 1 {
+        if order as f64 > limit {
+            return None;
+        }
+        power = power * a % r;
+        order += 1;
+    }
+    match order as f64 > limit {
+        true => None,
+        false => Some(order),
+    }
+}
+
+/// Função totiente de Euler de _r_, por divisão por tentativa
+fn euler_phi(mut r: u64) -> u64 {
+    let mut phi = r;
+    let mut p = 2;
+    while p * p <= r {
+        if r.is_multiple_of(p) {
+            phi -= phi / p;
+            while r.is_multiple_of(p) {
+                r /= p;
+            }
+        }
+        p += 1;
+    }
+    if r > 1 {
+        phi -= phi / r;
+    }
+    phi
+}
+
+/// Versão de tempo constante de [miller_rabin_tester], para candidatos secretos (como fatores RSA). Dentro de cada rodada não há desvios dependentes do candidato: a decomposição num-1 = d·2^s, a exponenciação e as size-1 elevações ao quadrado são sempre feitas por completo. O teste retorna assim que uma rodada falha, já que candidatos compostos são descartados e não precisam ser protegidos.
+pub fn miller_rabin_tester_ct<T: BigInteger>(num: &T, seed: &T) -> bool {
+    // Como em basic_verdict; a comparação só depende da quantidade de limbs, que é pública
+    if *num < T::from_u64(5) {
+        return *num > T::one() && *num != T::from_u64(4);
+    }
+    let n = num.to_limbs();
+    if !basic_prime_check_ct(&n) {
+        return false;
+    }
+
+    let bits = num.bits();
+    let mut gen = Mlcg::new_mersene_from_seed(T::from_u64(16807), 31, 32, seed);
+    let mut ctx = Montgomery::new_ct(&n);
+
+    let mut n_minus_one = n.clone();
+    n_minus_one[0] ^= 1;
+    let s = ct::trailing_zeros(&n_minus_one);
+    let d = ct::shr(&n_minus_one, s, 64 - bits.leading_zeros());
+
+    for _ in 0..ROUNDS {
+        let wit = witness_ct(&mut gen, bits, n.len());
+        if !miller_rabin_witness_ct(&mut ctx, &d, s, &wit, bits) {
             return false;
         }
     }
     true
 }
 
-/// Checagem de Fermat para determinar se o número é um forte candidato a primo
-fn fermat_witness(num: &BigUint, wit: BigUint) -> bool {
-    if basic_non_prime_check(&num) {
+/// Checagem de Miller-Rabin em tempo constante, com num-1 = d·2^s e _bits_ o tamanho público do candidato
+fn miller_rabin_witness_ct(
+    ctx: &mut Montgomery,
+    d: &[u64],
+    s: u64,
+    wit: &[u64],
+    bits: u64,
+) -> bool {
+    let base = ctx.to_mont_ct(wit);
+    let mut witmodpow = ctx.pow_mont_ct(&base, d, bits as usize);
+    let mut passed = ctx.ct_is_one(&witmodpow) | ctx.ct_is_minus_one(&witmodpow);
+
+    for i in 1..bits {
+        ctx.square_ct(&mut witmodpow);
+        passed |= ctx.ct_is_minus_one(&witmodpow) & ct::lt(i, s);
+    }
+    passed
+}
+
+/// Versão de tempo constante de [fermat_tester], com as mesmas garantias de [miller_rabin_tester_ct].
+pub fn fermat_tester_ct<T: BigInteger>(num: &T, seed: &T) -> bool {
+    // Como em basic_verdict; a comparação só depende da quantidade de limbs, que é pública
+    if *num < T::from_u64(5) {
+        return *num > T::one() && *num != T::from_u64(4);
+    }
+    let n = num.to_limbs();
+    if !basic_prime_check_ct(&n) {
         return false;
     }
 
-    wit.modpow(&(num - BigUint::new(vec![1])), num) == BigUint::new(vec![1])
+    let bits = num.bits();
+    let mut gen = Mlcg::new_mersene_from_seed(T::from_u64(16807), 31, 32, seed);
+    let mut ctx = Montgomery::new_ct(&n);
+
+    let mut exp = n.clone();
+    exp[0] ^= 1;
+
+    for _ in 0..ROUNDS {
+        let wit = witness_ct(&mut gen, bits, n.len());
+        let base = ctx.to_mont_ct(&wit);
+        let witmodpow = ctx.pow_mont_ct(&base, &exp, bits as usize);
+        if !ctx.ct_is_one(&witmodpow) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Gera a próxima testemunha para os testes de tempo constante no intervalo [2, 2^(_bits_-2) + 1], sempre menor que o candidato de _bits_ ≥ 3 bits (que tem o bit mais significativo ligado) e nunca 0 ou 1.
+fn witness_ct<T: BigInteger>(gen: &mut Mlcg<T>, bits: u64, len: usize) -> Vec<u64> {
+    let mask = T::one().shl(bits - 2) - T::one();
+    ct::limbs(&((gen.rand() & mask) + T::from_u64(2)), len)
+}
+
+/// Versão de tempo constante da checagem básica para num ≥ 5: num precisa ser ímpar e não divisível por 3 (equivalente a num²-1 ser múltiplo de 24), calculado sem divisões de tamanho variável.
+fn basic_prime_check_ct(n: &[u64]) -> bool {
+    (n[0] & 1 == 1) & !ct::is_zero(ct::rem3(n))
+}
+
+/// Retorna os _count_ bits menos significativos de _n_
+fn low_bits<T: BigInteger>(n: &T, count: u64) -> u64 {
+    (0..count).filter(|&i| n.bit(i)).map(|i| 1 << i).sum()
+}
+
+/// Veredito da checagem básica: n < 2, os primos 2 e 3, ou um composto divisível por 2 ou 3
+fn basic_verdict<T: BigInteger>(num: &T) -> Option<Verdict<T>> {
+    if *num < T::from_u64(2) {
+        return Some(Verdict::Trivial);
+    }
+    if *num < T::from_u64(4) {
+        return Some(Verdict::ProbablePrime {
+            rounds: 0,
+            error_bound: 0.0,
+        });
+    }
+    [2, 3]
+        .iter()
+        .find(|&&p| num.clone() % T::from_u64(p) == T::zero())
+        .map(|&p| Verdict::SmallFactor {
+            factor: T::from_u64(p),
+        })
 }
 
-/// Checa se o número é par ou se num²-1 não é multiplo de 24, condições que impossibilitam a primalidade
-fn basic_non_prime_check(num: &BigUint) -> bool {
-    (num % BigUint::new(vec![2]) == BigUint::default())
-        | ((num * num - BigUint::new(vec![1])) % 24.to_biguint().unwrap() != BigUint::default())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::BigUint;
+
+    fn is_prime(n: u64) -> bool {
+        n >= 2
+            && (2..)
+                .take_while(|p| p * p <= n)
+                .all(|p| !n.is_multiple_of(p))
+    }
+
+    #[test]
+    fn testers_classify_small_numbers() {
+        let seed = BigUint::from(12345u32);
+        let testers: [fn(&BigUint, &BigUint) -> bool; 5] = [
+            miller_rabin_tester,
+            fermat_tester,
+            solovay_strassen_tester,
+            miller_rabin_tester_ct,
+            fermat_tester_ct,
+        ];
+        for n in 0..2000u64 {
+            for tester in testers.iter() {
+                assert_eq!(tester(&BigUint::from(n), &seed), is_prime(n), "{}", n);
+            }
+        }
+    }
+
+    #[test]
+    fn solovay_strassen_tester_matches_verdict() {
+        let seed = BigUint::from(12345u32);
+        for n in 5..2000u64 {
+            let num = BigUint::from(n);
+            assert_eq!(
+                solovay_strassen_tester(&num, &seed),
+                solovay_strassen_verdict(&num, &seed).is_probable_prime(),
+                "{}",
+                n
+            );
+        }
+    }
+
+    /// Símbolo de Legendre pelo critério de Euler: a^((p-1)/2) mod p vale 1, p-1 ou 0
+    fn legendre(a: u64, p: u64) -> i32 {
+        let power = BigUint::from(a).modpow(&BigUint::from((p - 1) / 2), &BigUint::from(p));
+        match power {
+            _ if power == BigUint::default() => 0,
+            _ if power == BigUint::from(1u32) => 1,
+            _ => -1,
+        }
+    }
+
+    /// Símbolo de Jacobi pela definição, como produto dos símbolos de Legendre dos fatores primos de _n_
+    fn jacobi_reference(a: u64, mut n: u64) -> i32 {
+        let mut result = 1;
+        let mut p = 3;
+        while n > 1 {
+            while n.is_multiple_of(p) {
+                result *= legendre(a, p);
+                n /= p;
+            }
+            p += 2;
+        }
+        result
+    }
+
+    #[test]
+    fn jacobi_matches_euler_criterion_for_primes() {
+        for p in (3..400u64).filter(|&p| is_prime(p)) {
+            // a percorre também valores maiores que p, incluindo os múltiplos e os pares
+            for a in 0..3 * p {
+                assert_eq!(
+                    jacobi(&BigUint::from(a), &BigUint::from(p)),
+                    legendre(a, p),
+                    "({}/{})",
+                    a,
+                    p
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn jacobi_matches_legendre_products_for_composites() {
+        for n in (1..300u64).step_by(2).filter(|&n| !is_prime(n)) {
+            for a in 0..2 * n + 3 {
+                assert_eq!(
+                    jacobi(&BigUint::from(a), &BigUint::from(n)),
+                    jacobi_reference(a, n),
+                    "({}/{})",
+                    a,
+                    n
+                );
+            }
+        }
+        // a com muito mais bits que n passa pela redução inicial
+        let huge = (BigUint::from(1u32) << 100) + BigUint::from(7u32);
+        assert_eq!(jacobi(&huge, &BigUint::from(9907u32)), 1);
+        assert_eq!(jacobi(&BigUint::from(1001u32), &BigUint::from(9907u32)), -1);
+        assert_eq!(jacobi(&BigUint::from(19u32), &BigUint::from(45u32)), 1);
+        assert_eq!(jacobi(&BigUint::from(8u32), &BigUint::from(21u32)), -1);
+        assert_eq!(jacobi(&BigUint::default(), &BigUint::from(1u32)), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "the Jacobi symbol needs an odd n")]
+    fn jacobi_rejects_even_moduli() {
+        jacobi(&BigUint::from(3u32), &BigUint::from(10u32));
+    }
+
+    #[test]
+    fn verdicts_count_only_the_rounds_performed() {
+        let seed = BigUint::from(12345u32);
+        type VerdictFn = fn(&BigUint, &BigUint) -> Verdict<BigUint>;
+        let verdicts: [(VerdictFn, f64); 3] = [
+            (miller_rabin_verdict, 0.25),
+            (fermat_verdict, 0.5),
+            (solovay_strassen_verdict, 0.5),
+        ];
+        // Com n pequeno várias testemunhas sorteadas são múltiplas de n
+        let mut skipped = 0;
+        for n in (5..200u64).filter(|&n| is_prime(n)) {
+            let num = BigUint::from(n);
+            let mut gen = Mlcg::new_mersene_from_seed(BigUint::from(16807u32), 31, 32, &seed);
+            let expected = (0..ROUNDS)
+                .filter(|_| gen.rand() % &num != BigUint::default())
+                .count() as u32;
+            skipped += ROUNDS - expected;
+            for (verdict, error) in verdicts.iter() {
+                assert_eq!(
+                    verdict(&num, &seed),
+                    Verdict::ProbablePrime {
+                        rounds: expected,
+                        error_bound: error.powi(expected as i32),
+                    },
+                    "{}",
+                    n
+                );
+            }
+        }
+        assert!(skipped > 0);
+    }
 }
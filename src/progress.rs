@@ -0,0 +1,69 @@
+use std::io::{stderr, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+use crate::functions::{ProgressObserver, SearchProgress};
+
+/// Largura da barra de progresso em caracteres
+const BAR_WIDTH: usize = 30;
+
+/// Intervalo mínimo entre duas atualizações da barra
+const REFRESH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Barra de progresso escrita no stderr para buscas longas. Como a busca termina a qualquer momento, a barra mostra a fração de candidatos testados em relação à quantidade esperada pelo teorema dos números primos.
+pub struct TerminalProgress {
+    /// Instante da última atualização da barra
+    last_draw: Option<Instant>,
+}
+
+impl TerminalProgress {
+    /// Constrói uma nova barra caso o stderr seja um terminal.
+    pub fn new() -> Option<Self> {
+        match stderr().is_terminal() {
+            true => Some(TerminalProgress { last_draw: None }),
+            false => None,
+        }
+    }
+
+    /// Desenha a barra com o estado _progress_ na linha atual do terminal.
+    fn draw(&self, progress: &SearchProgress) {
+        let fraction = (progress.candidates as f64 / progress.expected_candidates()).min(0.99);
+        let filled = (fraction * BAR_WIDTH as f64) as usize;
+        let eta = match progress.estimated_remaining() {
+            Some(eta) => format!("{:.1}s", eta.as_secs_f64()),
+            None => "?".to_string(),
+        };
+        let mut err = stderr();
+        let _ = write!(
+            err,
+            "\r\x1b[2K[{}{}] {:>3.0}% {} checks, {:.2} checks/s, elapsed {:.1}s, ETA {}",
+            "#".repeat(filled),
+            "-".repeat(BAR_WIDTH - filled),
+            fraction * 100.0,
+            progress.candidates,
+            progress.rate(),
+            progress.elapsed.as_secs_f64(),
+            eta
+        );
+        let _ = err.flush();
+    }
+}
+
+impl ProgressObserver for TerminalProgress {
+    fn on_progress(&mut self, progress: &SearchProgress) {
+        let due = match self.last_draw {
+            Some(last) => last.elapsed() >= REFRESH_INTERVAL,
+            None => true,
+        };
+        if due {
+            self.draw(progress);
+            self.last_draw = Some(Instant::now());
+        }
+    }
+
+    fn on_finish(&mut self, _progress: &SearchProgress, _found: bool) {
+        let mut err = stderr();
+        let _ = write!(err, "\r\x1b[2K");
+        let _ = err.flush();
+        self.last_draw = None;
+    }
+}
@@ -0,0 +1,292 @@
+use std::fmt;
+use std::time::Instant;
+
+use log::info;
+
+use crate::backend::BigInteger;
+use crate::functions::{PrimeConstraints, PrimeKind, SearchError, SearchLimits};
+use crate::rand_gen::Mlcg;
+use crate::sha256::{sha256, DIGEST_LEN};
+
+/// Tamanho da saída da função de hash, em bits
+const OUTLEN: u64 = DIGEST_LEN as u64 * 8;
+
+/// Tamanho em bytes das seeds de entrada sorteadas para o Shawe-Taylor
+const INPUT_SEED_LEN: usize = 32;
+
+/// Maior tamanho, em bits, dos primos gerados diretamente e provados por divisão por tentativa
+const TRIAL_DIVISION_BITS: u64 = 32;
+
+/// Passo de uma prova de primalidade: _prime_ - 1 = 2·t·_factor_, com _factor_ primo maior que √_prime_ - 1 e _witness_ satisfazendo o critério de Pocklington
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofStep<T> {
+    /// Primo provado neste passo
+    pub prime: T,
+    /// Fator primo de prime-1, provado no passo anterior
+    pub factor: T,
+    /// Base a com a^(prime-1) ≡ 1 e mdc(a^((prime-1)/factor) - 1, prime) = 1
+    pub witness: T,
+}
+
+impl<T: BigInteger> ProofStep<T> {
+    /// Checa o critério de Pocklington para o passo, supondo que _factor_ é primo.
+    pub fn verify(&self) -> bool {
+        let n1 = self.prime.clone() - T::one();
+        let cofactor = n1.clone() / self.factor.clone();
+        let sqrt_bound = (self.factor.clone() + T::one()) * (self.factor.clone() + T::one());
+        if self.factor <= T::one()
+            || n1.clone() % self.factor.clone() != T::zero()
+            || sqrt_bound <= self.prime
+        {
+            return false;
+        }
+        let z = self.witness.modpow(&cofactor, &self.prime);
+        z.modpow(&self.factor, &self.prime) == T::one() && (z + n1).gcd(&self.prime) == T::one()
+    }
+}
+
+/// Primo provado pelo método de Shawe-Taylor, com a cadeia de certificados que comprova a primalidade
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProvablePrime<T> {
+    /// Primo provado
+    pub prime: T,
+    /// Primo pequeno, provado por divisão por tentativa, que inicia a cadeia
+    pub base: u64,
+    /// Passos de Pocklington, do menor para o maior primo
+    pub steps: Vec<ProofStep<T>>,
+    /// Seed de entrada do Shawe-Taylor, que permite refazer a geração
+    pub seed: Vec<u8>,
+}
+
+impl<T: BigInteger> ProvablePrime<T> {
+    /// Checa toda a cadeia: a base é prima por divisão por tentativa, cada passo usa como fator o primo do passo anterior e satisfaz o critério de Pocklington, e o último primo é _prime_.
+    pub fn verify(&self) -> bool {
+        let mut proven = T::from_u64(self.base);
+        if !is_prime_u64(self.base) {
+            return false;
+        }
+        for step in &self.steps {
+            if step.factor != proven || !step.verify() {
+                return false;
+            }
+            proven = step.prime.clone();
+        }
+        proven == self.prime
+    }
+}
+
+impl<T: BigInteger> fmt::Display for ProvablePrime<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is prime by trial division", self.base)?;
+        for step in &self.steps {
+            write!(
+                f,
+                "\n{} is prime by Pocklington with factor {} and witness {}",
+                step.prime, step.factor, step.witness
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Gera um primo de _length_ bits com prova de primalidade pelo procedimento de Shawe-Taylor do FIPS 186-4 (C.6) com SHA-256, a partir de _input_seed_. Primos de até 32 bits são sorteados e provados por divisão por tentativa; os maiores são construídos como c = 2·t·c₀ + 1 a partir de um primo provado c₀ com pouco mais da metade do tamanho, e aceitos pelo critério de Pocklington. Retorna None nos casos (raros) em que o procedimento falha para essa seed.
+pub fn shawe_taylor<T: BigInteger>(length: u64, input_seed: &[u8]) -> Option<ProvablePrime<T>> {
+    assert!(length >= 2, "Provable primes need at least 2 bits");
+    let mut state = SeedState::new(input_seed);
+    let (base, steps) = st_random_prime::<T>(length, &mut state)?;
+    let prime = match steps.last() {
+        Some(step) => step.prime.clone(),
+        None => T::from_u64(base),
+    };
+    Some(ProvablePrime {
+        prime,
+        base,
+        steps,
+        seed: input_seed.to_vec(),
+    })
+}
+
+/// Acha um primo provado de _size_ bits pelo Shawe-Taylor que satisfaça _constraints_, sorteando seeds de entrada a partir de _seed_ até algum ser aceito.
+pub fn find_provable_prime<T: BigInteger>(
+    size: u64,
+    seed: &T,
+    constraints: &PrimeConstraints<T>,
+    limits: &SearchLimits,
+) -> Result<ProvablePrime<T>, SearchError> {
+    constraints
+        .check(size, PrimeKind::Plain)
+        .map_err(SearchError::Unsatisfiable)?;
+    info!(
+        "Finding provable prime with {} bits with Shawe-Taylor method",
+        size
+    );
+    let now = Instant::now();
+    let mut counter = 0;
+    let mut seed_gen: Mlcg<T> = Mlcg::new_std(INPUT_SEED_LEN as u64 * 8, seed);
+
+    loop {
+        limits.check(counter, now)?;
+        let input_seed = seed_gen.rand().to_bytes_be_padded(INPUT_SEED_LEN).unwrap();
+        counter += 1;
+        if let Some(proven) = shawe_taylor::<T>(size, &input_seed) {
+            if constraints.accepts(&proven.prime, size) {
+                let elapsed_time = now.elapsed().as_secs_f32();
+                info!(
+                    "Found provable prime! Search stats: {} seeds in {:.4}s ({:.4}s/seed avg)",
+                    counter,
+                    elapsed_time,
+                    elapsed_time / counter as f32
+                );
+                return Ok(proven);
+            }
+        }
+    }
+}
+
+/// Estado da prime_seed do Shawe-Taylor, tratada como inteiro de tamanho fixo
+struct SeedState {
+    /// Valor atual da prime_seed, em big-endian
+    value: Vec<u8>,
+    /// Contador de candidatos testados (prime_gen_counter)
+    counter: u64,
+}
+
+impl SeedState {
+    fn new(input_seed: &[u8]) -> Self {
+        SeedState {
+            value: input_seed.to_vec(),
+            counter: 0,
+        }
+    }
+
+    /// Retorna Hash(prime_seed + _offset_)
+    fn hash(&self, offset: u64) -> [u8; DIGEST_LEN] {
+        sha256(&self.offset(offset))
+    }
+
+    /// Soma _amount_ à prime_seed
+    fn advance(&mut self, amount: u64) {
+        self.value = self.offset(amount);
+    }
+
+    /// Retorna prime_seed + _offset_ módulo 2^seedlen
+    fn offset(&self, offset: u64) -> Vec<u8> {
+        let mut bytes = self.value.clone();
+        let mut carry = offset as u128;
+        for byte in bytes.iter_mut().rev() {
+            carry += *byte as u128;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        bytes
+    }
+
+    /// Concatena Hash(prime_seed + i) para i de 0 a _iterations_, como inteiro, e avança a prime_seed
+    fn hash_integer<T: BigInteger>(&mut self, iterations: u64) -> T {
+        let mut x = T::zero();
+        for i in 0..=iterations {
+            x = x + T::from_bytes_be(&self.hash(i)).shl(i * OUTLEN);
+        }
+        self.advance(iterations + 1);
+        x
+    }
+}
+
+/// ST_Random_Prime do FIPS 186-4 (C.6): retorna o primo inicial e os passos de Pocklington até o primo de _length_ bits
+fn st_random_prime<T: BigInteger>(
+    length: u64,
+    state: &mut SeedState,
+) -> Option<(u64, Vec<ProofStep<T>>)> {
+    if length <= TRIAL_DIVISION_BITS {
+        loop {
+            let (h0, h1) = (state.hash(0), state.hash(1));
+            let c = h0[DIGEST_LEN - 8..]
+                .iter()
+                .zip(&h1[DIGEST_LEN - 8..])
+                .fold(0u64, |acc, (a, b)| (acc << 8) | (a ^ b) as u64);
+            let top = 1u64 << (length - 1);
+            let c = (top + c % top) | 1;
+            state.counter += 1;
+            state.advance(2);
+            if is_prime_u64(c) {
+                return Some((c, Vec::new()));
+            }
+            if state.counter > 4 * length {
+                return None;
+            }
+        }
+    }
+
+    let (base, mut steps) = st_random_prime::<T>(length.div_ceil(2) + 1, state)?;
+    let c0 = match steps.last() {
+        Some(step) => step.prime.clone(),
+        None => T::from_u64(base),
+    };
+    let iterations = length.div_ceil(OUTLEN) - 1;
+    let old_counter = state.counter;
+    let top = T::one().shl(length - 1);
+    let x = top.clone() + state.hash_integer::<T>(iterations) % top.clone();
+    let two_c0 = c0.shl(1);
+    let mut t = (x + two_c0.clone() - T::one()) / two_c0.clone();
+    loop {
+        if two_c0.clone() * t.clone() + T::one() > T::one().shl(length) {
+            t = (top.clone() + two_c0.clone() - T::one()) / two_c0.clone();
+        }
+        let c = two_c0.clone() * t.clone() + T::one();
+        state.counter += 1;
+        let a = T::from_u64(2) + state.hash_integer::<T>(iterations) % (c.clone() - T::from_u64(3));
+        let z = a.modpow(&t.shl(1), &c);
+        if (z.clone() + c.clone() - T::one()).gcd(&c) == T::one() && z.modpow(&c0, &c) == T::one() {
+            steps.push(ProofStep {
+                prime: c,
+                factor: c0,
+                witness: a,
+            });
+            return Some((base, steps));
+        }
+        if state.counter >= 4 * length + old_counter {
+            return None;
+        }
+        t = t + T::one();
+    }
+}
+
+/// Checa a primalidade de _n_ por divisão por tentativa, suficiente para os primos de até 32 bits da base da cadeia
+pub(crate) fn is_prime_u64(n: u64) -> bool {
+    if n < 4 {
+        return n >= 2;
+    }
+    if n.is_multiple_of(2) {
+        return false;
+    }
+    let mut d = 3;
+    // d * d passaria de 2^64 para n perto do máximo, como a base de um certificado lido de um arquivo
+    while d <= n / d {
+        if n.is_multiple_of(d) {
+            return false;
+        }
+        d += 2;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trial_division_handles_squares_and_large_inputs() {
+        assert!(!is_prime_u64(0) && !is_prime_u64(1));
+        assert!(is_prime_u64(2) && is_prime_u64(3));
+        assert!(!is_prime_u64(4) && !is_prime_u64(9));
+        // Quadrados de primos: o último divisor testado é a própria raiz
+        assert!(!is_prime_u64(65521 * 65521));
+        assert!(!is_prime_u64(65537 * 65537));
+        assert!(is_prime_u64(4_294_967_291));
+        assert!(is_prime_u64(4_294_967_311));
+        assert!(!is_prime_u64(65537 * 65539));
+        // Compostos perto de 2^64, com fatores pequenos
+        assert!(!is_prime_u64(u64::MAX));
+        assert!(!is_prime_u64(18_446_744_073_709_551_613));
+    }
+}
@@ -0,0 +1,162 @@
+use std::fmt;
+
+use crate::backend::BigInteger;
+use crate::prime_test::{fermat_tester, miller_rabin_tester};
+
+/// Maior limite aceito por [pseudoprimes]: o crivo usa um bit por número
+pub const PSEUDOPRIME_MAX_BOUND: u64 = 100_000_000;
+
+/// Família de pseudoprimos enumerada por [pseudoprimes]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pseudoprimes {
+    /// Compostos n com base^(n-1) ≡ 1 (mod n)
+    Fermat { base: u64 },
+    /// Compostos ímpares que passam pelo Miller-Rabin com a base _base_
+    Strong { base: u64 },
+    /// Compostos que são pseudoprimos de Fermat para toda base coprima com eles
+    Carmichael,
+}
+
+impl fmt::Display for Pseudoprimes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pseudoprimes::Fermat { base } => write!(f, "Fermat pseudoprimes to base {}", base),
+            Pseudoprimes::Strong { base } => write!(f, "strong pseudoprimes to base {}", base),
+            Pseudoprimes::Carmichael => write!(f, "Carmichael numbers"),
+        }
+    }
+}
+
+/// Resultado dos testes probabilísticos do projeto sobre um pseudoprimo: true quando o teste foi enganado e aceitou o número
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TesterOutcome {
+    pub n: u64,
+    pub fermat: bool,
+    pub miller_rabin: bool,
+}
+
+/// Enumera os pseudoprimos da família _kind_ até _bound_ (no máximo [PSEUDOPRIME_MAX_BOUND]). A compostura vem de um crivo de Eratóstenes; os números de Carmichael são os pseudoprimos de Fermat na base 2 que satisfazem o critério de Korselt, já que todo número de Carmichael é ímpar e passa pela base 2.
+pub fn pseudoprimes(kind: &Pseudoprimes, bound: u64) -> Vec<u64> {
+    assert!(bound <= PSEUDOPRIME_MAX_BOUND, "bound too large");
+    let composite = composite_sieve(bound);
+    let is_composite = |n: u64| composite[(n / 64) as usize] >> (n % 64) & 1 == 1;
+    (4..=bound)
+        .filter(|&n| is_composite(n))
+        .filter(|&n| match *kind {
+            Pseudoprimes::Fermat { base } => is_fermat_probable_prime(n, base),
+            Pseudoprimes::Strong { base } => n % 2 == 1 && is_strong_probable_prime(n, base),
+            Pseudoprimes::Carmichael => n % 2 == 1 && is_fermat_probable_prime(n, 2) && korselt(n),
+        })
+        .collect()
+}
+
+/// Checa se base^(_n_-1) ≡ 1 (mod _n_)
+pub fn is_fermat_probable_prime(n: u64, base: u64) -> bool {
+    pow_mod(base, n - 1, n) == 1 % n
+}
+
+/// Checa se _n_ ímpar passa pelo Miller-Rabin com a base _base_: com n-1 = d·2^s, base^d ≡ 1 ou base^(d·2^r) ≡ -1 (mod n) para algum 0 ≤ r < s
+pub fn is_strong_probable_prime(n: u64, base: u64) -> bool {
+    let s = (n - 1).trailing_zeros();
+    let mut x = pow_mod(base, (n - 1) >> s, n);
+    if x == 1 % n || x == n - 1 {
+        return true;
+    }
+    for _ in 1..s {
+        x = mul_mod(x, x, n);
+        if x == n - 1 {
+            return true;
+        }
+    }
+    false
+}
+
+/// Critério de Korselt: _n_ composto é um número de Carmichael se e só se é livre de quadrados e p - 1 divide n - 1 para todo primo p que divide n. Os fatores são achados por divisão por tentativa.
+pub fn korselt(n: u64) -> bool {
+    let mut rest = n;
+    let mut factors = 0;
+    let mut p = 2;
+    while p <= rest / p {
+        if rest.is_multiple_of(p) {
+            rest /= p;
+            if rest.is_multiple_of(p) || !(n - 1).is_multiple_of(p - 1) {
+                return false;
+            }
+            factors += 1;
+        }
+        p += 1;
+    }
+    if rest > 1 {
+        if rest == n || !(n - 1).is_multiple_of(rest - 1) {
+            return false;
+        }
+        factors += 1;
+    }
+    factors >= 2
+}
+
+/// Aplica [fermat_tester] e [miller_rabin_tester], com a semente _seed_, sobre cada número de _numbers_
+pub fn run_testers<T: BigInteger>(numbers: &[u64], seed: &T) -> Vec<TesterOutcome> {
+    numbers
+        .iter()
+        .map(|&n| {
+            let num = T::from_u64(n);
+            TesterOutcome {
+                n,
+                fermat: fermat_tester(&num, seed),
+                miller_rabin: miller_rabin_tester(&num, seed),
+            }
+        })
+        .collect()
+}
+
+/// Crivo de Eratóstenes até _bound_, com um bit por número ligado para os compostos
+fn composite_sieve(bound: u64) -> Vec<u64> {
+    let mut composite = vec![0u64; (bound / 64 + 1) as usize];
+    let mut p = 2;
+    while p * p <= bound {
+        if composite[(p / 64) as usize] >> (p % 64) & 1 == 0 {
+            for multiple in (p * p..=bound).step_by(p as usize) {
+                composite[(multiple / 64) as usize] |= 1 << (multiple % 64);
+            }
+        }
+        p += 1;
+    }
+    composite
+}
+
+fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    (a as u128 * b as u128 % m as u128) as u64
+}
+
+fn pow_mod(base: u64, mut exp: u64, m: u64) -> u64 {
+    let mut base = base % m;
+    let mut result = 1 % m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exp >>= 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn korselt_recognizes_carmichael_numbers() {
+        for n in [561, 1105, 1729, 41041, 825265] {
+            assert!(korselt(n), "{}", n);
+        }
+        // Primos, pseudoprimos de Fermat que não são de Carmichael e números com fator quadrado
+        for n in [7, 341, 2047, 45, 1729 * 7] {
+            assert!(!korselt(n), "{}", n);
+        }
+        // 1199137 · 2398273 · 3597409, da forma (6k+1)(12k+1)(18k+1), perto de 2^64
+        assert!(korselt(10_345_637_057_649_571_009));
+        assert!(!korselt(u64::MAX));
+    }
+}
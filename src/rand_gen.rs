@@ -1,31 +1,31 @@
-use std::ops::{BitAnd, BitOr};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use num_bigint::{BigUint, ToBigUint};
+use crate::backend::BigInteger;
+use crate::special::lucas_lehmer;
 
 /// Multiplicative linear congruential generator, também conhecido como Park-Miller RNG. Calcula novos valores por meio da fórmula s = mu*s % mo, sendo _s_ o ultimo valor gerado (ou inicialmente a semente), _mu_ um multiplicador e _mo_ o modulo do gerador. Recomenda-se que _mu_ e _mo_ sejam pelo menos coprimos.
-pub struct Mlcg {
+pub struct Mlcg<T: BigInteger> {
     /// Corresponde ao último numero gerado pela estrutura.
-    state: BigUint,
+    state: T,
     /// Semente do gerador
-    seed: BigUint,
+    seed: T,
     /// Fator que multiplica o valor atual na geração do próximo.
-    mult_factor: BigUint,
+    mult_factor: T,
     /// Valor pelo qual é realizada a divisão modular na geração do próximo valor.
-    mod_factor: BigUint,
+    mod_factor: T,
     /// Tamanho dos valores gerados
     size: u64,
 }
 
-impl Mlcg {
+impl<T: BigInteger> Mlcg<T> {
     /// Constrói um novo MLCG com multiplicador = _mu_, modulo = _mo_, semente gerada com base no horário local e tamanho de _size_ bits.
-    pub fn new(mu: BigUint, mo: BigUint, size: u64) -> Self {
-        let seed: BigUint = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis()
-            .to_biguint()
-            .unwrap();
+    pub fn new(mu: T, mo: T, size: u64) -> Self {
+        let seed = T::from_u64(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64,
+        );
 
         Mlcg {
             state: Mlcg::warm_up(&seed, &mu, &mo),
@@ -37,9 +37,9 @@ impl Mlcg {
     }
 
     /// Constrói um novo MLCG com multiplicador = _mu_, modulo = _mo_, semente = _s_ e tamanho de _size_ bits.
-    pub fn new_from_seed(mu: BigUint, mo: BigUint, size: u64, s: &BigUint) -> Self {
+    pub fn new_from_seed(mu: T, mo: T, size: u64, s: &T) -> Self {
         Mlcg {
-            state: Mlcg::warm_up(&s, &mu, &mo),
+            state: Mlcg::warm_up(s, &mu, &mo),
             seed: s.clone(),
             mult_factor: mu,
             mod_factor: mo,
@@ -48,14 +48,14 @@ impl Mlcg {
     }
 
     /// Constrói um novo MLCG com multiplicador = _mu_, modulo = 2^_mer_-1, semente gerada com base no horário local e tamanho de _size_ bits.
-    pub fn new_mersene(mu: BigUint, mer: u32, size: u64) -> Self {
-        let seed: BigUint = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis()
-            .to_biguint()
-            .unwrap();
-        let mo = BigUint::pow(&2.to_biguint().unwrap(), mer) - 1.to_biguint().unwrap();
+    pub fn new_mersene(mu: T, mer: u32, size: u64) -> Self {
+        let seed = T::from_u64(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64,
+        );
+        let mo = T::from_u64(2).pow(mer) - T::one();
 
         Mlcg {
             state: Mlcg::warm_up(&seed, &mu, &mo),
@@ -67,11 +67,11 @@ impl Mlcg {
     }
 
     /// Constrói um novo MLCG com multiplicador = _mu_, modulo = 2^_mer_-1, semente = _s_ e tamanho de _size_ bits.
-    pub fn new_mersene_from_seed(mu: BigUint, mer: u32, size: u64, s: &BigUint) -> Self {
-        let mo = BigUint::pow(&2.to_biguint().unwrap(), mer) - 1.to_biguint().unwrap();
+    pub fn new_mersene_from_seed(mu: T, mer: u32, size: u64, s: &T) -> Self {
+        let mo = T::from_u64(2).pow(mer) - T::one();
 
         Mlcg {
-            state: Mlcg::warm_up(&s, &mu, &mo),
+            state: Mlcg::warm_up(s, &mu, &mo),
             seed: s.clone(),
             mult_factor: mu,
             mod_factor: mo,
@@ -80,77 +80,87 @@ impl Mlcg {
     }
 
     /// Constrói um novo MLCG com valores padronizados para modulo e multiplicador, semente = _s_ e tamanho de _size_ bits.
-    pub fn new_std(size: u64, s: &BigUint) -> Mlcg {
+    pub fn new_std(size: u64, s: &T) -> Self {
         //TODO: Achar raizes primitivas de 2^4253-1, como candidato a bom argumento de multiplicador
-        Mlcg::new_mersene_from_seed(16807.to_biguint().unwrap(), 4253, size, s)
+        Mlcg::new_mersene_from_seed(T::from_u64(16807), 4253, size, s)
     }
 
     /// Retorna o último valor pseudo aleatório gerado pela estrutura.
-    pub fn value(&self) -> BigUint {
+    pub fn value(&self) -> T {
         self.state.clone()
     }
 
     /// Retorna a semente da estrutura.
-    pub fn seed(&self) -> BigUint {
+    pub fn seed(&self) -> T {
         self.seed.clone()
     }
 
+    /// Checa, pelo teste de Lucas-Lehmer, se o módulo do gerador é um primo de Mersenne 2^p - 1, condição para que o período chegue a mo - 1 (com o multiplicador sendo uma raiz primitiva). Retorna false também se o módulo não tiver a forma 2^p - 1.
+    pub fn has_mersenne_prime_modulus(&self) -> bool {
+        let power = self.mod_factor.clone() + T::one();
+        let p = power.bits() - 1;
+        power == T::one().shl(p) && p <= u32::MAX as u64 && lucas_lehmer::<T>(p as u32)
+    }
+
     /// Retorna o pŕoximo valor pseudo aleatório calculado pela estrutura.
-    fn calculate_next(&mut self) -> BigUint {
-        self.state = (&self.state * &self.mult_factor) % &self.mod_factor;
+    fn calculate_next(&mut self) -> T {
+        let state = std::mem::replace(&mut self.state, T::zero());
+        self.state = state * &self.mult_factor % &self.mod_factor;
         self.value()
     }
 
     /// Retorna o pŕoximo valor pseudo aleatório gerado pela estrutura com um tamanho específicado de bits.
-    pub fn rand(&mut self) -> BigUint {
-        let temp = &self.calculate_next();
-        let msb: BigUint = 1.to_biguint().unwrap() << (self.size - 1);
-        let ones: BigUint = (1.to_biguint().unwrap() << self.size) - 1.to_biguint().unwrap();
-        temp.bitand(ones).bitor(msb)
+    pub fn rand(&mut self) -> T {
+        let temp = self.calculate_next();
+        let msb: T = T::one().shl(self.size - 1);
+        let ones: T = T::one().shl(self.size) - T::one();
+        (temp & ones) | msb
     }
 
     /// Realiza um aquecimento na semente para evitar que os valores iniciais correspondam à semente e/ou exponham o multiplicador utilizado.
-    fn warm_up(s: &BigUint, mu: &BigUint, mo: &BigUint) -> BigUint {
-        if (s << 1) < *mo {
-            Mlcg::warm_up(&(s * mu % mo), mu, mo)
-        } else {
-            s.clone()
+    fn warm_up(s: &T, mu: &T, mo: &T) -> T {
+        let mut s = s.clone();
+        while s.shl(1) < *mo {
+            s = s * mu % mo;
         }
+        s
     }
 }
 
-pub struct LaggedFibonacci<T>
+pub struct LaggedFibonacci<T, F>
 where
-    T: Fn(BigUint, BigUint) -> BigUint,
+    T: BigInteger,
+    F: Fn(T, T) -> T,
 {
     /// Operação realizada entre os valores n-j e n-k, como por exemplo Mul::mul, Add::add ou BitXor::bitxor
-    operation: T,
+    operation: F,
     /// Semente do gerador
-    seed: BigUint,
+    seed: T,
     /// Histórico de valores gerados/inseridos, para geração de novos
-    states: Vec<BigUint>,
+    states: Vec<T>,
     /// Indicação de antecessor mais recente para utilizar na geração do próximo elemento
     ele_j: usize,
     /// Indicação de antecessor menos recente para utilizar na geração do próximo elemento
     ele_k: usize,
     /// Valor pelo qual é realizada a divisão modular na geração do próximo valor.
-    mod_factor: BigUint,
+    mod_factor: T,
     /// Tamanho dos valores gerados
     size: u64,
 }
 
-impl<T> LaggedFibonacci<T>
+impl<T, F> LaggedFibonacci<T, F>
 where
-    T: Fn(BigUint, BigUint) -> BigUint,
+    T: BigInteger,
+    F: Fn(T, T) -> T,
 {
     /// Constrói um novo Lagged-Fibonacci RNG com módulo _mo_ que realiza a operação _op_ entre os elementos n-_j_ e n-_k_, com lista inicial de elementos _elements_.
-    pub fn new(elements: Vec<BigUint>, j: u16, k: u16, mo: BigUint, op: T, size: u64) -> Self {
-        let seed: BigUint = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis()
-            .to_biguint()
-            .unwrap();
+    pub fn new(elements: Vec<T>, j: u16, k: u16, mo: T, op: F, size: u64) -> Self {
+        let seed = T::from_u64(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64,
+        );
 
         let mut temp = LaggedFibonacci {
             operation: op,
@@ -166,15 +176,7 @@ where
     }
 
     /// Constrói um novo Lagged-Fibonacci RNG com módulo _mo_ que realiza a operação _op_ entre os elementos n-_j_ e n-_k_.
-    pub fn new_from_seed(
-        elements: Vec<BigUint>,
-        j: u16,
-        k: u16,
-        mo: BigUint,
-        op: T,
-        size: u64,
-        s: &BigUint,
-    ) -> Self {
+    pub fn new_from_seed(elements: Vec<T>, j: u16, k: u16, mo: T, op: F, size: u64, s: &T) -> Self {
         let mut temp = LaggedFibonacci {
             operation: op,
             seed: s.clone(),
@@ -188,8 +190,8 @@ where
         temp
     }
 
-    pub fn new_std(size: u64, s: &BigUint, op: T) -> Self {
-        let lf_mod = 2.to_biguint().unwrap().pow(4253);
+    pub fn new_std(size: u64, s: &T, op: F) -> Self {
+        let lf_mod = T::from_u64(2).pow(4253);
         LaggedFibonacci::new_from_seed(Vec::new(), 7, 10, lf_mod, op, size, s)
     }
 
@@ -199,10 +201,7 @@ where
 
         match j_k_pair {
             (j, k) if j > k => {
-                let temp = self.ele_j;
-
-                self.ele_j = self.ele_k;
-                self.ele_k = temp;
+                std::mem::swap(&mut self.ele_j, &mut self.ele_k);
             }
             (j, k) if j == k => {
                 self.ele_j -= 1;
@@ -218,13 +217,13 @@ where
             }
             (k, l) if l < k => {
                 let mut temp = Mlcg::new_mersene_from_seed(
-                    16087.to_biguint().unwrap(),
+                    T::from_u64(16087),
                     self.size as u32,
                     self.size,
                     self.seed(),
                 );
                 while self.states.len() < self.ele_k {
-                    self.states.insert(0, temp.rand() | 1.to_biguint().unwrap())
+                    self.states.insert(0, temp.rand() | T::one())
                 }
             }
             (_, _) => (),
@@ -232,29 +231,29 @@ where
     }
 
     /// Retorna o último valor pseudo aleatório gerado pela estrutura.
-    pub fn value(&self) -> BigUint {
+    pub fn value(&self) -> T {
         self.states.last().unwrap().clone()
     }
 
     /// Retorna a semente da estrutura.
-    pub fn seed(&self) -> &BigUint {
+    pub fn seed(&self) -> &T {
         &self.seed
     }
 
     /// Retorna o pŕoximo valor pseudo aleatório gerado pela estrutura.
-    fn calculate_next(&mut self) -> BigUint {
-        let ele_j: BigUint = self.states[self.states.len() - self.ele_j].clone();
-        let ele_k: BigUint = self.states.remove(0);
+    fn calculate_next(&mut self) -> T {
+        let ele_j: T = self.states[self.states.len() - self.ele_j].clone();
+        let ele_k: T = self.states.remove(0);
         self.states
             .push((self.operation)(ele_j, ele_k) % &self.mod_factor);
         self.value()
     }
 
     /// Retorna o pŕoximo valor pseudo aleatório gerado pela estrutura com um tamanho específicado de bits.
-    pub fn rand(&mut self) -> BigUint {
-        let temp = &self.calculate_next();
-        let msb: BigUint = 1.to_biguint().unwrap() << (self.size - 1);
-        let ones: BigUint = (1.to_biguint().unwrap() << self.size) - 1.to_biguint().unwrap();
-        temp.bitand(ones).bitor(msb)
+    pub fn rand(&mut self) -> T {
+        let temp = self.calculate_next();
+        let msb: T = T::one().shl(self.size - 1);
+        let ones: T = T::one().shl(self.size) - T::one();
+        (temp & ones) | msb
     }
 }
@@ -0,0 +1,671 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
+
+use log::info;
+use num_bigint::{BigInt, BigUint, Sign};
+
+use crate::backend::BigInteger;
+use crate::functions::small_primes;
+use crate::rand_gen::Mlcg;
+
+/// Maior número de dígitos para o qual o SIQS é tentado pelo método padrão; o tempo cresce cerca de 12 vezes a cada 10 dígitos e em 90 dígitos já chega a horas
+pub const SIQS_MAX_DIGITS: u64 = 90;
+
+/// Parâmetros por número de dígitos: (dígitos, primos na base de fatores, meia largura M do intervalo de peneira)
+const PARAMETERS: [(u64, usize, u64); 9] = [
+    (20, 120, 4096),
+    (30, 250, 16384),
+    (40, 600, 32768),
+    (50, 1500, 32768),
+    (60, 4000, 65536),
+    (70, 7000, 65536),
+    (80, 12000, 131072),
+    (90, 16000, 196608),
+    (100, 20000, 262144),
+];
+
+/// Multiplicadores livres de quadrados testados pelo critério de Knuth-Schroeppel
+const MULTIPLIERS: [u64; 24] = [
+    1, 3, 5, 7, 11, 13, 15, 17, 19, 21, 23, 29, 31, 33, 35, 37, 39, 41, 43, 47, 51, 53, 55, 57,
+];
+
+/// Tamanho preferido dos primos q que formam os coeficientes A: quanto menores, mais primos em A e mais polinômios por A
+const A_PRIME_SIZE: f64 = 2000.0;
+
+/// Primos menores que este valor não são peneirados (variação dos primos pequenos), só testados na divisão por tentativa dos candidatos
+const SIEVE_MIN: u32 = 100;
+
+/// Cofatores até este múltiplo do maior primo da base são guardados como relações parciais (variação de um primo grande)
+const LARGE_PRIME_MULTIPLIER: u64 = 64;
+
+/// Quantidade de relações além do tamanho da base de fatores, para garantir várias dependências
+const EXTRA_RELATIONS: usize = 32;
+
+/// Folga, em bits, do limiar da peneira além do primo grande, compensando os primos pequenos não peneirados e o arredondamento dos logaritmos
+const THRESHOLD_SLACK: u64 = 18;
+
+/// Quantidade de rodadas de novas relações quando todas as dependências são triviais, antes de desistir (n pode ser uma potência de primo)
+const MAX_ROUNDS: usize = 4;
+
+/// Relação Y² ≡ (-1)^negative · ∏ p (mod n), com os primos p da base listados com repetição e _square_ o produto dos primos grandes que aparecem ao quadrado
+#[derive(Clone, Debug)]
+struct Relation {
+    y: BigUint,
+    negative: bool,
+    factors: Vec<usize>,
+    square: BigUint,
+}
+
+/// Base de fatores: primos p com (kN/p) ≠ -1, com a raiz quadrada de kN módulo p e o logaritmo usado na peneira
+struct FactorBase {
+    primes: Vec<u32>,
+    roots: Vec<u32>,
+    logs: Vec<u8>,
+}
+
+/// Polinômio Q(x) = ((Ax + B)² - kN)/A e as raízes de Q módulo cada primo da base, deslocadas por M
+struct Polynomial {
+    a: BigUint,
+    b: BigInt,
+    a_factors: Vec<usize>,
+    /// Os B_l com B = Σ ±B_l, que permitem trocar de polinômio pelo código de Gray
+    b_terms: Vec<BigUint>,
+    /// 2·B_l·A⁻¹ módulo cada primo, para atualizar as raízes
+    b_inverses: Vec<Vec<u32>>,
+    roots: Vec<(u32, u32)>,
+}
+
+/// Fatora _n_ pelo crivo quadrático auto-inicializável (SIQS), com os primos que formam os coeficientes A sorteados a partir de _seed_. Retorna um fator próprio, ou None se _n_ for pequeno demais, primo ou uma potência de primo. A base de fatores e o intervalo de peneira dependem do número de dígitos; relações com um primo grande são combinadas aos pares, e as dependências entre elas são achadas por eliminação gaussiana sobre GF(2).
+pub fn siqs<T: BigInteger>(n: &T, seed: &T) -> Option<T> {
+    let n = BigUint::from_bytes_be(&n.to_bytes_be());
+    let digits = n.to_string().len() as u64;
+    let now = Instant::now();
+    let (fb_size, half_width) = parameters(digits);
+    let k = multiplier(&n);
+    let kn = &n * k;
+    let base = factor_base(&kn, fb_size);
+    // Com n pequeno um primo da base pode dividi-lo
+    if let Some(&p) = base.primes.iter().find(|&&p| rem(&n, p) == 0) {
+        return match BigUint::from(p) < n {
+            true => Some(T::from_u64(p as u64)),
+            false => None,
+        };
+    }
+    let largest = *base.primes.last().unwrap() as u64;
+    info!(
+        "Running SIQS on a {}-digit number: multiplier {}, {} primes up to {} in the factor base and sieve interval of {}",
+        digits,
+        k,
+        base.primes.len(),
+        largest,
+        2 * half_width
+    );
+
+    let mut seed_gen: Mlcg<BigUint> =
+        Mlcg::new_std(64, &BigUint::from_bytes_be(&seed.to_bytes_be()));
+    let mut sieve = vec![0u8; 2 * half_width as usize];
+    let large_bound = largest * LARGE_PRIME_MULTIPLIER;
+    // |Q(x)| ≤ M·√(kN/2): posições cuja soma dos logaritmos passa do tamanho de Q menos o do primo grande são candidatas
+    let threshold = ((kn.bits() / 2 + 64 - (half_width.leading_zeros() as u64))
+        .saturating_sub(64 - large_bound.leading_zeros() as u64 + THRESHOLD_SLACK))
+        as u8;
+
+    let mut relations: Vec<Relation> = Vec::new();
+    let mut partials: HashMap<u64, Relation> = HashMap::new();
+    let mut seen: HashSet<BigUint> = HashSet::new();
+    let mut used_a: HashSet<Vec<usize>> = HashSet::new();
+    let (mut polynomials, mut full, mut needed) =
+        (0u64, 0usize, base.primes.len() + EXTRA_RELATIONS);
+    let mut next_report = needed / 10;
+
+    for _ in 0..MAX_ROUNDS {
+        while relations.len() < needed {
+            let mut poly = first_polynomial(&kn, &base, half_width, &mut seed_gen, &mut used_a)?;
+            let count = 1u64 << (poly.b_terms.len() - 1);
+            for i in 0..count {
+                if i > 0 {
+                    next_polynomial(&mut poly, &base, i);
+                }
+                polynomials += 1;
+                sieve_polynomial(&mut sieve, &poly, &base);
+                for (position, _) in sieve.iter().enumerate().filter(|(_, v)| **v >= threshold) {
+                    let relation =
+                        match check_candidate(&n, &kn, &poly, &base, position, half_width) {
+                            Some(relation) => relation,
+                            None => continue,
+                        };
+                    let (relation, large) = relation;
+                    if !seen.insert(relation.y.clone()) {
+                        continue;
+                    }
+                    match large {
+                        1 => {
+                            full += 1;
+                            relations.push(relation);
+                        }
+                        large if large < large_bound => match partials.remove(&large) {
+                            Some(other) => relations.push(combine(&n, relation, other, large)),
+                            None => {
+                                partials.insert(large, relation);
+                            }
+                        },
+                        _ => {}
+                    }
+                }
+            }
+            if relations.len() >= next_report {
+                info!(
+                    "SIQS: {}/{} relations ({} full, {} from large primes) after {} polynomials in {:.1}s",
+                    relations.len(),
+                    needed,
+                    full,
+                    relations.len() - full,
+                    polynomials,
+                    now.elapsed().as_secs_f32()
+                );
+                next_report = relations.len() + needed / 10;
+            }
+        }
+
+        let dependencies = find_dependencies(&relations, base.primes.len());
+        info!(
+            "SIQS: {} dependencies found among {} relations after {:.1}s",
+            dependencies.len(),
+            relations.len(),
+            now.elapsed().as_secs_f32()
+        );
+        for dependency in dependencies {
+            if let Some(factor) = square_root(&n, &base, &relations, &dependency) {
+                info!(
+                    "SIQS found {} after {} polynomials in {:.4}s",
+                    factor,
+                    polynomials,
+                    now.elapsed().as_secs_f32()
+                );
+                return Some(T::from_bytes_be(&factor.to_bytes_be()));
+            }
+        }
+        // Todas as dependências foram triviais: junta mais relações
+        needed += EXTRA_RELATIONS;
+    }
+    None
+}
+
+/// Interpola os parâmetros da tabela para _digits_ dígitos
+fn parameters(digits: u64) -> (usize, u64) {
+    let first = PARAMETERS[0];
+    if digits <= first.0 {
+        return (first.1, first.2);
+    }
+    for pair in PARAMETERS.windows(2) {
+        let ((d0, f0, m0), (d1, f1, m1)) = (pair[0], pair[1]);
+        if digits <= d1 {
+            let fb = f0 + (f1 - f0) * (digits - d0) as usize / (d1 - d0) as usize;
+            let m = m0 + (m1 - m0) * (digits - d0) / (d1 - d0);
+            return (fb, m);
+        }
+    }
+    let last = PARAMETERS[PARAMETERS.len() - 1];
+    (last.1, last.2)
+}
+
+/// Escolhe o multiplicador k pelo critério de Knuth-Schroeppel, que mede quanto os primos pequenos contribuem em média para kN
+fn multiplier(n: &BigUint) -> u64 {
+    let primes: Vec<u64> = small_primes(1000).into_iter().skip(1).collect();
+    let residues: Vec<u64> = primes.iter().map(|&p| rem(n, p as u32) as u64).collect();
+    let n8 = rem(n, 8) as u64;
+    let score = |k: u64| {
+        let mut score = -0.5 * (k as f64).ln();
+        score += match (n8 * k) % 8 {
+            1 => 2.0,
+            5 => 1.0,
+            _ => 0.5,
+        } * 2f64.ln();
+        for (&p, &r) in primes.iter().zip(&residues) {
+            let contribution = (p as f64).ln();
+            if k.is_multiple_of(p) {
+                score += contribution / p as f64;
+            } else if legendre((r * k) % p, p) == 1 {
+                score += 2.0 * contribution / (p - 1) as f64;
+            }
+        }
+        score
+    };
+    MULTIPLIERS
+        .iter()
+        .copied()
+        .max_by(|&a, &b| score(a).partial_cmp(&score(b)).unwrap())
+        .unwrap()
+}
+
+/// Monta a base de fatores com 2 e os primeiros _size_ - 1 primos ímpares p com (kN/p) ≠ -1
+fn factor_base(kn: &BigUint, size: usize) -> FactorBase {
+    let mut base = FactorBase {
+        primes: vec![2],
+        roots: vec![rem(kn, 2)],
+        logs: vec![1],
+    };
+    let mut limit = 1 << 12;
+    while base.primes.len() < size {
+        base.primes.truncate(1);
+        base.roots.truncate(1);
+        base.logs.truncate(1);
+        for p in small_primes(limit).into_iter().skip(1) {
+            let r = rem(kn, p as u32) as u64;
+            let root = match r {
+                0 => 0,
+                _ if legendre(r, p) == 1 => sqrt_mod(r, p),
+                _ => continue,
+            };
+            base.primes.push(p as u32);
+            base.roots.push(root as u32);
+            base.logs.push((p as f64).log2().round() as u8);
+            if base.primes.len() == size {
+                break;
+            }
+        }
+        limit *= 2;
+    }
+    base
+}
+
+/// Sorteia um novo A = ∏ q_l, com os q_l primos da base e A próximo de √(2kN)/M, e monta o primeiro polinômio de A
+fn first_polynomial(
+    kn: &BigUint,
+    base: &FactorBase,
+    half_width: u64,
+    seed_gen: &mut Mlcg<BigUint>,
+    used: &mut HashSet<Vec<usize>>,
+) -> Option<Polynomial> {
+    let target = (kn * 2u32).isqrt() / half_width;
+    let target_log = bits_f64(&target);
+    // Candidatos a q: primos peneirados que não dividem k
+    let usable: Vec<usize> = (0..base.primes.len())
+        .filter(|&i| base.primes[i] >= SIEVE_MIN && base.roots[i] != 0)
+        .collect();
+    if usable.len() < 4 {
+        return None;
+    }
+    // s primos de tamanho perto de A_PRIME_SIZE (ou da mediana dos candidatos, se a base for pequena), com o último escolhido para ajustar o produto
+    let median = base.primes[usable[usable.len() / 2]] as f64;
+    let middle = A_PRIME_SIZE.min(median).log2();
+    let s = ((target_log / middle).round() as usize).max(1);
+    let ideal = 2f64.powf(target_log / s as f64);
+    let pool: Vec<usize> = usable
+        .iter()
+        .copied()
+        .filter(|&i| {
+            let p = base.primes[i] as f64;
+            p >= ideal / 2.0 && p <= ideal * 2.0
+        })
+        .collect();
+    let pool = match pool.len() >= s + 2 {
+        true => pool,
+        false => usable.clone(),
+    };
+
+    let mut chosen: Vec<usize> = Vec::new();
+    for _ in 0..1000 {
+        chosen.clear();
+        let mut product = BigUint::from(1u32);
+        while chosen.len() + 1 < s {
+            let index = pool[(rem(&seed_gen.rand(), pool.len() as u32)) as usize];
+            if !chosen.contains(&index) {
+                chosen.push(index);
+                product *= base.primes[index];
+            }
+        }
+        let remainder = bits_f64(&target) - bits_f64(&product);
+        let last = usable
+            .iter()
+            .copied()
+            .filter(|i| !chosen.contains(i))
+            .min_by(|&a, &b| {
+                let da = ((base.primes[a] as f64).log2() - remainder).abs();
+                let db = ((base.primes[b] as f64).log2() - remainder).abs();
+                da.partial_cmp(&db).unwrap()
+            })?;
+        chosen.push(last);
+        chosen.sort_unstable();
+        if used.insert(chosen.clone()) {
+            break;
+        }
+    }
+
+    let a: BigUint = chosen
+        .iter()
+        .map(|&i| BigUint::from(base.primes[i]))
+        .product();
+    let gammas: Vec<u64> = chosen
+        .iter()
+        .map(|&i| {
+            let q = base.primes[i] as u64;
+            let inverse = mod_inverse(rem(&(&a / q), q as u32) as u64, q);
+            let gamma = base.roots[i] as u64 * inverse % q;
+            gamma.min(q - gamma)
+        })
+        .collect();
+    let b_terms: Vec<BigUint> = chosen
+        .iter()
+        .zip(&gammas)
+        .map(|(&i, &gamma)| &a / base.primes[i] * gamma)
+        .collect();
+    let b = BigInt::from_biguint(Sign::Plus, b_terms.iter().sum());
+
+    // Os restos de A e dos B_l módulo cada primo saem dos q_l, sem divisões de inteiros grandes
+    let mut roots = vec![(u32::MAX, u32::MAX); base.primes.len()];
+    let mut b_inverses = vec![vec![0u32; base.primes.len()]; b_terms.len()];
+    for (i, &p) in base.primes.iter().enumerate() {
+        if p == 2 || chosen.contains(&i) {
+            continue;
+        }
+        let p64 = p as u64;
+        let q_mod: Vec<u64> = chosen
+            .iter()
+            .map(|&j| base.primes[j] as u64 % p64)
+            .collect();
+        let a_inverse = mod_inverse(q_mod.iter().fold(1, |acc, q| acc * q % p64), p64);
+        let mut b_mod = 0;
+        for (l, gamma) in gammas.iter().enumerate() {
+            let term = q_mod
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != l)
+                .fold(gamma % p64, |acc, (_, q)| acc * q % p64);
+            b_mod = (b_mod + term) % p64;
+            b_inverses[l][i] = (2 * term * a_inverse % p64) as u32;
+        }
+        roots[i] = poly_roots(b_mod, base.roots[i] as u64, a_inverse, p64, half_width);
+    }
+    Some(Polynomial {
+        a,
+        b,
+        a_factors: chosen,
+        b_terms,
+        b_inverses,
+        roots,
+    })
+}
+
+/// Raízes de Q módulo _p_ já deslocadas por M: x = A⁻¹(±t - B) + M, com _b_mod_ = B mod p
+fn poly_roots(b_mod: u64, t: u64, a_inverse: u64, p: u64, half_width: u64) -> (u32, u32) {
+    let shift = half_width % p;
+    let r1 = ((t + p - b_mod) % p * a_inverse + shift) % p;
+    let r2 = ((2 * p - t - b_mod) % p * a_inverse + shift) % p;
+    (r1 as u32, r2 as u32)
+}
+
+/// Passa para o polinômio _i_ do código de Gray: o termo B_v que muda de sinal é o do bit v trocado entre i - 1 e i
+fn next_polynomial(poly: &mut Polynomial, base: &FactorBase, i: u64) {
+    let v = i.trailing_zeros() as usize;
+    let negative = ((i ^ (i >> 1)) >> v) & 1 == 1;
+    let twice = BigInt::from_biguint(Sign::Plus, &poly.b_terms[v] * 2u32);
+    match negative {
+        true => poly.b -= twice,
+        false => poly.b += twice,
+    }
+    let deltas = &poly.b_inverses[v];
+    for (i, root) in poly.roots.iter_mut().enumerate() {
+        if root.0 == u32::MAX {
+            continue;
+        }
+        let p = base.primes[i];
+        let delta = match negative {
+            true => deltas[i],
+            false => p - deltas[i],
+        };
+        let shift = |r: u32| match r + delta >= p {
+            true => r + delta - p,
+            false => r + delta,
+        };
+        *root = (shift(root.0), shift(root.1));
+    }
+}
+
+/// Soma log p em cada posição x do intervalo com Q(x) ≡ 0 (mod p)
+fn sieve_polynomial(sieve: &mut [u8], poly: &Polynomial, base: &FactorBase) {
+    sieve.iter_mut().for_each(|v| *v = 0);
+    for (i, &(r1, r2)) in poly.roots.iter().enumerate() {
+        if r1 == u32::MAX || base.primes[i] < SIEVE_MIN {
+            continue;
+        }
+        let (p, log) = (base.primes[i] as usize, base.logs[i]);
+        for v in sieve.iter_mut().skip(r1 as usize).step_by(p) {
+            *v = v.wrapping_add(log);
+        }
+        if r2 != r1 {
+            for v in sieve.iter_mut().skip(r2 as usize).step_by(p) {
+                *v = v.wrapping_add(log);
+            }
+        }
+    }
+}
+
+/// Fatora Q(x) na base por divisão por tentativa, testando só os primos cujas raízes batem com a posição. Retorna a relação e o cofator que sobrou (1 para uma relação completa)
+fn check_candidate(
+    n: &BigUint,
+    kn: &BigUint,
+    poly: &Polynomial,
+    base: &FactorBase,
+    position: usize,
+    half_width: u64,
+) -> Option<(Relation, u64)> {
+    let x = BigInt::from(position as i64 - half_width as i64);
+    let y = BigInt::from_biguint(Sign::Plus, poly.a.clone()) * x + &poly.b;
+    let y_squared = y.magnitude() * y.magnitude();
+    let (negative, mut q) = match y_squared >= *kn {
+        true => (false, (y_squared - kn) / &poly.a),
+        false => (true, (kn - y_squared) / &poly.a),
+    };
+    let mut factors = poly.a_factors.clone();
+    for (i, &p) in base.primes.iter().enumerate() {
+        let (r1, r2) = poly.roots[i];
+        let hit = r1 == u32::MAX || {
+            let offset = position as u32 % p;
+            offset == r1 || offset == r2
+        };
+        if !hit {
+            continue;
+        }
+        while rem(&q, p) == 0 {
+            q /= p;
+            factors.push(i);
+        }
+    }
+    let cofactor = match q.bits() {
+        0..=64 => q.to_u64_digits().first().copied().unwrap_or(0),
+        _ => return None,
+    };
+    let relation = Relation {
+        y: y.magnitude() % n,
+        negative,
+        factors,
+        square: BigUint::from(1u32),
+    };
+    Some((relation, cofactor))
+}
+
+/// Combina duas relações parciais com o mesmo primo grande _large_ em uma relação completa
+fn combine(n: &BigUint, first: Relation, second: Relation, large: u64) -> Relation {
+    let mut factors = first.factors;
+    factors.extend(second.factors);
+    Relation {
+        y: first.y * second.y % n,
+        negative: first.negative ^ second.negative,
+        factors,
+        square: BigUint::from(large),
+    }
+}
+
+/// Acha subconjuntos de relações cujo produto é um quadrado, por eliminação gaussiana sobre GF(2): cada relação é uma linha com a paridade dos expoentes (e do sinal), aumentada com a identidade para lembrar quais relações foram somadas
+fn find_dependencies(relations: &[Relation], base_len: usize) -> Vec<Vec<usize>> {
+    let columns = base_len + 1;
+    let words = columns.div_ceil(64);
+    let total = words + relations.len().div_ceil(64);
+    let mut rows: Vec<Vec<u64>> = relations
+        .iter()
+        .enumerate()
+        .map(|(r, relation)| {
+            let mut row = vec![0u64; total];
+            if relation.negative {
+                row[0] ^= 1;
+            }
+            for &i in &relation.factors {
+                row[(i + 1) / 64] ^= 1 << ((i + 1) % 64);
+            }
+            row[words + r / 64] |= 1 << (r % 64);
+            row
+        })
+        .collect();
+
+    let mut pivot = vec![false; rows.len()];
+    for column in 0..columns {
+        let (word, bit) = (column / 64, 1u64 << (column % 64));
+        let chosen = match (0..rows.len()).find(|&r| !pivot[r] && rows[r][word] & bit != 0) {
+            Some(chosen) => chosen,
+            None => continue,
+        };
+        pivot[chosen] = true;
+        let pivot_row = rows[chosen].clone();
+        for (r, row) in rows.iter_mut().enumerate() {
+            if !pivot[r] && row[word] & bit != 0 {
+                row.iter_mut().zip(&pivot_row).for_each(|(a, b)| *a ^= b);
+            }
+        }
+    }
+
+    rows.iter()
+        .enumerate()
+        .filter(|(r, _)| !pivot[*r])
+        .map(|(_, row)| {
+            (0..relations.len())
+                .filter(|r| row[words + r / 64] & (1 << (r % 64)) != 0)
+                .collect()
+        })
+        .collect()
+}
+
+/// Monta X = ∏ Y e Y' = √(∏ Q) módulo n para as relações de _dependency_ e retorna mdc(X - Y', n) se for um fator próprio
+fn square_root(
+    n: &BigUint,
+    base: &FactorBase,
+    relations: &[Relation],
+    dependency: &[usize],
+) -> Option<BigUint> {
+    let mut exponents = vec![0u32; base.primes.len()];
+    let mut x = BigUint::from(1u32);
+    let mut y = BigUint::from(1u32);
+    for &r in dependency {
+        let relation = &relations[r];
+        x = x * &relation.y % n;
+        y = y * &relation.square % n;
+        for &i in &relation.factors {
+            exponents[i] += 1;
+        }
+    }
+    for (i, &e) in exponents.iter().enumerate() {
+        if e > 0 {
+            y = y * BigUint::from(base.primes[i]).modpow(&BigUint::from(e / 2), n) % n;
+        }
+    }
+    let difference = (x + n - y) % n;
+    let g = BigInteger::gcd(&difference, n);
+    match g > BigUint::from(1u32) && g < *n {
+        true => Some(g),
+        false => None,
+    }
+}
+
+/// Resto de _x_ por _p_
+fn rem(x: &BigUint, p: u32) -> u32 {
+    (x % p).to_u32_digits().first().copied().unwrap_or(0)
+}
+
+/// Logaritmo na base 2 aproximado de _x_
+fn bits_f64(x: &BigUint) -> f64 {
+    let bits = x.bits();
+    let shift = bits.saturating_sub(53);
+    let top = (x >> shift).to_u64_digits().first().copied().unwrap_or(0) as f64;
+    top.log2() + shift as f64
+}
+
+/// Símbolo de Legendre (_a_/_p_) por exponenciação
+fn legendre(a: u64, p: u64) -> i32 {
+    match pow_mod(a, (p - 1) / 2, p) {
+        0 => 0,
+        1 => 1,
+        _ => -1,
+    }
+}
+
+/// _a_^_e_ módulo _m_ < 2^32
+fn pow_mod(a: u64, mut e: u64, m: u64) -> u64 {
+    let (mut base, mut result) = (a % m, 1);
+    while e > 0 {
+        if e & 1 == 1 {
+            result = result * base % m;
+        }
+        base = base * base % m;
+        e >>= 1;
+    }
+    result
+}
+
+/// Inverso de _a_ módulo o primo _p_, pelo pequeno teorema de Fermat
+fn mod_inverse(a: u64, p: u64) -> u64 {
+    pow_mod(a, p - 2, p)
+}
+
+/// Raiz quadrada do resíduo quadrático _a_ módulo o primo ímpar _p_ < 2^32, por Tonelli-Shanks
+fn sqrt_mod(a: u64, p: u64) -> u64 {
+    if p % 4 == 3 {
+        return pow_mod(a, (p + 1) / 4, p);
+    }
+    let (mut q, mut s) = (p - 1, 0);
+    while q % 2 == 0 {
+        q /= 2;
+        s += 1;
+    }
+    let z = (2..p).find(|&z| legendre(z, p) == -1).unwrap();
+    let (mut m, mut c, mut t, mut r) = (
+        s,
+        pow_mod(z, q, p),
+        pow_mod(a, q, p),
+        pow_mod(a, q.div_ceil(2), p),
+    );
+    while t != 1 {
+        let mut i = 0;
+        let mut t2 = t;
+        while t2 != 1 {
+            t2 = t2 * t2 % p;
+            i += 1;
+        }
+        let mut b = c;
+        for _ in 0..m - i - 1 {
+            b = b * b % p;
+        }
+        m = i;
+        c = b * b % p;
+        t = t * c % p;
+        r = r * b % p;
+    }
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_a_known_factor() {
+        let p: BigUint = "1000000000000037".parse().unwrap();
+        let q: BigUint = "3000000000000037".parse().unwrap();
+        let factor = siqs(&(p.clone() * q.clone()), &BigUint::from(5429u32)).unwrap();
+        assert!(factor == p || factor == q);
+        // A mesma busca em um número de 40 dígitos, com a base de fatores maior
+        let p: BigUint = "10000000000000000051".parse().unwrap();
+        let q: BigUint = "30000000000000000041".parse().unwrap();
+        let factor = siqs(&(p.clone() * q.clone()), &BigUint::from(5429u32)).unwrap();
+        assert!(factor == p || factor == q);
+    }
+}
@@ -0,0 +1,172 @@
+use std::fmt;
+
+use crate::backend::BigInteger;
+use crate::lucas::lucas_sequence;
+use crate::prime_test::jacobi;
+
+/// Forma especial de um número testado por [test_special_form]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpecialForm {
+    /// Número de Mersenne 2^p - 1
+    Mersenne { p: u32 },
+    /// Número de Fermat 2^(2^m) + 1
+    Fermat { m: u32 },
+    /// Número de Proth k·2^n + 1, com k ímpar e k < 2^n
+    Proth { k: u64, n: u32 },
+    /// Número de Riesel k·2^n - 1, com k ímpar e k < 2^n
+    Riesel { k: u64, n: u32 },
+}
+
+impl SpecialForm {
+    /// Retorna o nome do teste usado para a forma.
+    pub fn test_name(&self) -> &'static str {
+        match self {
+            SpecialForm::Mersenne { .. } => "Lucas-Lehmer",
+            SpecialForm::Fermat { .. } => "Pépin",
+            SpecialForm::Proth { .. } => "Proth",
+            SpecialForm::Riesel { .. } => "Lucas-Lehmer-Riesel",
+        }
+    }
+
+    /// Calcula o valor do número.
+    pub fn value<T: BigInteger>(&self) -> T {
+        match *self {
+            SpecialForm::Mersenne { p } => T::one().shl(p as u64) - T::one(),
+            SpecialForm::Fermat { m } => T::one().shl(1 << m) + T::one(),
+            SpecialForm::Proth { k, n } => T::from_u64(k).shl(n as u64) + T::one(),
+            SpecialForm::Riesel { k, n } => T::from_u64(k).shl(n as u64) - T::one(),
+        }
+    }
+
+    /// Checa se os parâmetros satisfazem as condições do teste: p ≥ 2, m até 30 e, para Proth e Riesel, k ímpar com k < 2^n.
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            SpecialForm::Mersenne { p } => p >= 2,
+            SpecialForm::Fermat { m } => m <= 30,
+            SpecialForm::Proth { k, n } | SpecialForm::Riesel { k, n } => {
+                !k.is_multiple_of(2) && n >= 1 && (n >= 64 || k < 1 << n)
+            }
+        }
+    }
+}
+
+impl fmt::Display for SpecialForm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpecialForm::Mersenne { p } => write!(f, "2^{} - 1", p),
+            SpecialForm::Fermat { m } => write!(f, "2^2^{} + 1", m),
+            SpecialForm::Proth { k, n } => write!(f, "{}·2^{} + 1", k, n),
+            SpecialForm::Riesel { k, n } => write!(f, "{}·2^{} - 1", k, n),
+        }
+    }
+}
+
+/// Aplica o teste determinístico da forma de _form_, que precisa ser válida ([SpecialForm::is_valid]).
+pub fn test_special_form<T: BigInteger>(form: &SpecialForm) -> bool {
+    match *form {
+        SpecialForm::Mersenne { p } => lucas_lehmer::<T>(p),
+        SpecialForm::Fermat { m } => pepin::<T>(m),
+        SpecialForm::Proth { k, n } => proth::<T>(k, n),
+        SpecialForm::Riesel { k, n } => lucas_lehmer_riesel::<T>(k, n),
+    }
+}
+
+/// Reduz _x_ módulo 2^_p_ - 1 sem divisões: como 2^p ≡ 1, x ≡ (x mod 2^p) + ⌊x/2^p⌋, e basta somar as partes até o valor caber em p bits
+pub fn mod_mersenne<T: BigInteger>(x: &T, p: u32) -> T {
+    let mask = T::one().shl(p as u64) - T::one();
+    let mut x = x.clone();
+    while x.bits() > p as u64 {
+        x = (x.clone() & mask.clone()) + x.shr(p as u64);
+    }
+    match x == mask {
+        true => T::zero(),
+        false => x,
+    }
+}
+
+/// Teste de Lucas-Lehmer: 2^_p_ - 1 é primo se e só se s_(p-2) ≡ 0, com s_0 = 4 e s_i+1 = s_i² - 2 (mod 2^p - 1). Os quadrados são reduzidos por [mod_mersenne]. Com p composto o número também é, e p nem é testado.
+pub fn lucas_lehmer<T: BigInteger>(p: u32) -> bool {
+    if p == 2 {
+        return true;
+    }
+    if p < 2
+        || (2..)
+            .take_while(|d| *d <= p / d)
+            .any(|d| p.is_multiple_of(d))
+    {
+        return false;
+    }
+    let modulus = T::one().shl(p as u64) - T::one();
+    let two = T::from_u64(2);
+    let mut s = T::from_u64(4);
+    for _ in 0..p - 2 {
+        let square = mod_mersenne(&(s.clone() * &s), p);
+        s = match square < two {
+            true => square + &modulus - &two,
+            false => square - &two,
+        };
+    }
+    s == T::zero()
+}
+
+/// Teorema de Proth: N = _k_·2^_n_ + 1, com k ímpar e k < 2^n, é primo se e só se a^((N-1)/2) ≡ -1 (mod N) para um a com (a/N) = -1. Esse a é procurado entre 3, 5, 7, ...; um a com fator em comum com N prova que ele é composto.
+pub fn proth<T: BigInteger>(k: u64, n: u32) -> bool {
+    let num = SpecialForm::Proth { k, n }.value::<T>();
+    let root = num.isqrt();
+    if root.clone() * root == num {
+        return false;
+    }
+    let mut a = 3;
+    loop {
+        let base = T::from_u64(a);
+        match jacobi(&base, &num) {
+            -1 => break,
+            0 => return base == num,
+            _ => a += 2,
+        }
+    }
+    let minus_one = num.clone() - T::one();
+    T::from_u64(a).modpow(&minus_one.shr(1), &num) == minus_one
+}
+
+/// Teste de Pépin: o número de Fermat F_m = 2^(2^_m_) + 1 é primo se e só se 3^((F_m-1)/2) ≡ -1 (mod F_m), para m ≥ 1. É o teorema de Proth com k = 1 e a = 3, já que (3/F_m) = -1.
+pub fn pepin<T: BigInteger>(m: u32) -> bool {
+    if m == 0 {
+        return true;
+    }
+    let num = SpecialForm::Fermat { m }.value::<T>();
+    let minus_one = num.clone() - T::one();
+    T::from_u64(3).modpow(&minus_one.shr(1), &num) == minus_one
+}
+
+/// Teste de Lucas-Lehmer-Riesel: N = _k_·2^_n_ - 1, com k ímpar e k < 2^n, é primo se e só se s_(n-2) ≡ 0 (mod N), com s_i+1 = s_i² - 2 e s_0 = V_k(P, 1), sendo P o primeiro valor a partir de 3 com ((P-2)/N) = 1 e ((P+2)/N) = -1 (método de Rödseth). Com k = 1 é o próprio Lucas-Lehmer.
+pub fn lucas_lehmer_riesel<T: BigInteger>(k: u64, n: u32) -> bool {
+    let num = SpecialForm::Riesel { k, n }.value::<T>();
+    if n < 2 {
+        // Com n = 1 só k = 1 é válido, e N = 1
+        return false;
+    }
+    if num == T::from_u64(3) {
+        return true;
+    }
+    let root = num.isqrt();
+    if root.clone() * root == num {
+        return false;
+    }
+    let mut p: u64 = 3;
+    loop {
+        let (minus, plus) = (T::from_u64(p - 2), T::from_u64(p + 2));
+        match (jacobi(&minus, &num), jacobi(&plus, &num)) {
+            (1, -1) => break,
+            (0, _) if minus != num => return false,
+            (_, 0) if plus != num => return false,
+            _ => p += 1,
+        }
+    }
+    let two = T::from_u64(2);
+    let mut s = lucas_sequence(p as i64, 1, &T::from_u64(k), &num).v;
+    for _ in 0..n - 2 {
+        s = (s.clone() * &s + &num - &two) % &num;
+    }
+    s == T::zero()
+}
@@ -0,0 +1,98 @@
+use std::convert::TryFrom;
+
+use num_bigint::BigUint;
+
+use ine5429_primes::backend::BigInteger;
+use ine5429_primes::lucas::strong_lucas_tester;
+use ine5429_primes::prime_test::{
+    aks_tester, fermat_tester, miller_rabin_tester, solovay_strassen_tester,
+};
+use ine5429_primes::pseudoprime::korselt;
+use ine5429_primes::rand_gen::Mlcg;
+
+/// Quantidade de números aleatórios conferidos em cada teste
+const SAMPLES: usize = 2000;
+
+/// Maior tamanho, em bits, dos números aleatórios conferidos com o AKS, que é lento demais em modo de depuração para números maiores
+const AKS_MAX_BITS: u64 = 12;
+
+/// Maior tamanho, em bits, dos números aleatórios em que os testes probabilísticos são conferidos entre si
+const MAX_BITS: u64 = 64;
+
+/// Números de Carmichael e pseudoprimos fortes na base 2, que enganam o Fermat e o Miller-Rabin com bases ruins
+const PSEUDOPRIMES: [u64; 12] = [
+    561, 1105, 1729, 2465, 2821, 6601, 8911, 2047, 3277, 4033, 4681, 8321,
+];
+
+/// Confere o teste AKS contra os testes probabilísticos (Miller-Rabin, Solovay-Strassen e forte de Lucas) em números aleatórios de até 12 bits, incluindo a faixa dos primos pequenos, e em pseudoprimos conhecidos. O Fermat só pode discordar em números de Carmichael.
+#[test]
+fn aks_agrees_with_probabilistic_testers() {
+    let seed = BigUint::from(5429u32);
+    let mut gen: Mlcg<BigUint> = Mlcg::new_std(512, &seed);
+    let mut numbers: Vec<BigUint> = (0..SAMPLES)
+        .map(|i| {
+            let bits = 2 + i as u64 % (AKS_MAX_BITS - 1);
+            gen.rand() % BigUint::one().shl(bits)
+        })
+        .collect();
+    numbers.extend((0..64u64).map(BigUint::from));
+    numbers.extend(PSEUDOPRIMES.iter().map(|&n| BigUint::from(n)));
+
+    for n in &numbers {
+        let expected = aks_tester(n);
+        assert_eq!(
+            miller_rabin_tester(n, &seed),
+            expected,
+            "Miller-Rabin on {}",
+            n
+        );
+        assert_eq!(
+            solovay_strassen_tester(n, &seed),
+            expected,
+            "Solovay-Strassen on {}",
+            n
+        );
+        assert_eq!(strong_lucas_tester(n), expected, "strong Lucas on {}", n);
+        if fermat_tester(n, &seed) != expected {
+            let n = u64::try_from(n).unwrap();
+            assert!(!expected && korselt(n), "Fermat on {}", n);
+        }
+    }
+}
+
+/// Confere os testes probabilísticos entre si em números aleatórios de até 64 bits, em que o AKS seria lento demais, e nos mesmos pseudoprimos; os números ímpares são mais frequentes que os pares para que apareçam primos de todos os tamanhos.
+#[test]
+fn probabilistic_testers_agree_on_larger_numbers() {
+    let seed = BigUint::from(5429u32);
+    let mut gen: Mlcg<BigUint> = Mlcg::new_std(512, &seed);
+    let mut numbers: Vec<BigUint> = (0..SAMPLES)
+        .map(|i| {
+            let bits = 2 + i as u64 % (MAX_BITS - 1);
+            (gen.rand() % BigUint::one().shl(bits)) | BigUint::one()
+        })
+        .collect();
+    numbers.extend(PSEUDOPRIMES.iter().map(|&n| BigUint::from(n)));
+
+    let mut primes = 0;
+    for n in &numbers {
+        let expected = miller_rabin_tester(n, &seed);
+        assert_eq!(
+            solovay_strassen_tester(n, &seed),
+            expected,
+            "Solovay-Strassen on {}",
+            n
+        );
+        assert_eq!(strong_lucas_tester(n), expected, "strong Lucas on {}", n);
+        if fermat_tester(n, &seed) != expected {
+            let n = u64::try_from(n).unwrap();
+            assert!(!expected && korselt(n), "Fermat on {}", n);
+        }
+        primes += expected as usize;
+    }
+    // Perto de 2^bits a densidade de primos ímpares é 2/(bits·ln 2): a amostra precisa ter primos e compostos
+    assert!(
+        primes > SAMPLES / 20 && primes < SAMPLES / 2,
+        "{} primes",
+        primes
+    );
+}
@@ -0,0 +1,227 @@
+use num_bigint::BigUint;
+
+use ine5429_primes::rand_gen::Mlcg;
+use ine5429_primes::rsa::{KeyFormat, RsaPrivateKey, RsaPublicKey};
+use ine5429_primes::sha256::sha256;
+
+/// Vetores de RSA-OAEP e RSA-PSS gerados pelo OpenSSL; o formato está descrito no cabeçalho do arquivo
+const VECTORS: &str = include_str!("data/rsa_vectors.txt");
+
+/// Uma mesma chave de 1024 bits gerada pelo OpenSSL (genpkey) e exportada por ele nos quatro formatos
+const OPENSSL_PRIVATE_PKCS1: &str = include_str!("data/rsa_private_pkcs1.pem");
+const OPENSSL_PRIVATE_PKCS8: &str = include_str!("data/rsa_private_pkcs8.pem");
+const OPENSSL_PUBLIC_PKCS1: &str = include_str!("data/rsa_public_pkcs1.pem");
+const OPENSSL_PUBLIC_SPKI: &str = include_str!("data/rsa_public_spki.pem");
+
+/// Módulo da chave do OpenSSL, como impresso por openssl rsa -modulus
+const OPENSSL_MODULUS: &str = "D1D9682480343C5D006E7781C11CB457A3338A1C2AE5DB67C6CF32F2604033E86AB9B47D2B1C257E844893DA20F27A655590E24DCEE3747351335C6F8BA2510195ED1DAF094F0B48B39EBA00B56A449601BBA1433FE4A6B8F50BD86329A46AC3C8AB073811624234CA7D55723882BCE51A1EFA5A165A60B2C9CDA92CEA4F85A3";
+
+/// Confere o SHA-256 contra os exemplos do FIPS 180-4
+#[test]
+fn sha256_matches_fips_180_4() {
+    let vectors: [(&[u8], &str); 3] = [
+        (
+            b"abc",
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        ),
+        (
+            b"",
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        ),
+        (
+            b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+        ),
+    ];
+    for (message, digest) in vectors.iter() {
+        assert_eq!(hex(&sha256(message)), *digest);
+    }
+    assert_eq!(
+        hex(&sha256(&vec![b'a'; 1_000_000])),
+        "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+    );
+}
+
+/// Confere as primitivas do módulo rsa contra os vetores de [VECTORS]: cada cifra e assinatura do OpenSSL precisa ser aceita, e recusada quando adulterada ou com outro rótulo, mensagem ou tamanho de salt; com a seed ou o salt fixados, a saída precisa ser idêntica à do vetor.
+#[test]
+fn rsa_matches_openssl_vectors() {
+    let mut rng: Mlcg<BigUint> = Mlcg::new_std(512, &BigUint::from(5429u32));
+    let mut key: Option<RsaPrivateKey<BigUint>> = None;
+    let mut checked = 0;
+    let mut lines = VECTORS.lines();
+    while let Some(line) = lines.next() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            [] => continue,
+            [comment, ..] if comment.starts_with('#') => continue,
+            ["key", _] => {
+                let pem: Vec<&str> = lines
+                    .by_ref()
+                    .take_while(|line| !line.starts_with("-----END"))
+                    .collect();
+                let pem = format!("{}\n-----END RSA PRIVATE KEY-----\n", pem.join("\n"));
+                key = Some(RsaPrivateKey::from_pem(&pem).unwrap());
+                continue;
+            }
+            _ => {}
+        }
+        let key = key.as_ref().unwrap();
+        let public = key.public_key();
+        match fields.as_slice() {
+            ["oaep", message, label, ciphertext] => {
+                let (message, label, ciphertext) =
+                    (unhex(message), unhex(label), unhex(ciphertext));
+                assert_eq!(
+                    key.decrypt_oaep(&ciphertext, &label, &mut rng),
+                    Ok(message),
+                    "{}",
+                    line
+                );
+                let mut other_label = label.clone();
+                other_label.push(0);
+                assert!(
+                    key.decrypt_oaep(&ciphertext, &other_label, &mut rng)
+                        .is_err(),
+                    "{}",
+                    line
+                );
+                let mut tampered = ciphertext;
+                *tampered.last_mut().unwrap() ^= 1;
+                assert!(
+                    key.decrypt_oaep(&tampered, &label, &mut rng).is_err(),
+                    "{}",
+                    line
+                );
+            }
+            ["oaep-seed", message, label, seed, ciphertext] => {
+                let ciphertext = unhex(ciphertext);
+                assert_eq!(
+                    public.encrypt_oaep_with_seed(&unhex(message), &unhex(label), &unhex(seed)),
+                    Ok(ciphertext.clone()),
+                    "{}",
+                    line
+                );
+                assert_eq!(
+                    key.decrypt_oaep(&ciphertext, &unhex(label), &mut rng),
+                    Ok(unhex(message)),
+                    "{}",
+                    line
+                );
+            }
+            ["pss", message, salt_len, signature] => {
+                let (message, signature) = (unhex(message), unhex(signature));
+                let salt_len: usize = salt_len.parse().unwrap();
+                assert!(
+                    public.verify_pss(&message, &signature, salt_len),
+                    "{}",
+                    line
+                );
+                assert!(
+                    !public.verify_pss(&message, &signature, salt_len + 1),
+                    "{}",
+                    line
+                );
+                let mut other_message = message.clone();
+                other_message.push(0);
+                assert!(
+                    !public.verify_pss(&other_message, &signature, salt_len),
+                    "{}",
+                    line
+                );
+                let mut tampered = signature;
+                *tampered.last_mut().unwrap() ^= 1;
+                assert!(
+                    !public.verify_pss(&message, &tampered, salt_len),
+                    "{}",
+                    line
+                );
+            }
+            ["pss-salt", message, salt, signature] => {
+                let salt = unhex(salt);
+                let signature = unhex(signature);
+                assert_eq!(
+                    key.sign_pss_with_salt(&unhex(message), &salt, &mut rng),
+                    Ok(signature.clone()),
+                    "{}",
+                    line
+                );
+                assert!(
+                    public.verify_pss(&unhex(message), &signature, salt.len()),
+                    "{}",
+                    line
+                );
+            }
+            ["raw", message, ciphertext] => {
+                let message = BigUint::from_bytes_be(&unhex(message));
+                let ciphertext = BigUint::from_bytes_be(&unhex(ciphertext));
+                assert_eq!(
+                    public.encrypt_raw(&message),
+                    Ok(ciphertext.clone()),
+                    "{}",
+                    line
+                );
+                assert_eq!(
+                    key.decrypt_raw(&ciphertext, &mut rng),
+                    Ok(message),
+                    "{}",
+                    line
+                );
+            }
+            _ => panic!("malformed vector line: {}", line),
+        }
+        checked += 1;
+    }
+    assert_eq!(checked, 18);
+}
+
+/// Lê a chave do OpenSSL nos quatro formatos: todos precisam dar a mesma chave, com o módulo esperado, e a recodificação de cada um precisa reproduzir o PEM do OpenSSL byte a byte.
+#[test]
+fn keys_match_openssl_encodings() {
+    let private = RsaPrivateKey::<BigUint>::from_pem(OPENSSL_PRIVATE_PKCS1).unwrap();
+    assert_eq!(
+        private.n,
+        BigUint::parse_bytes(OPENSSL_MODULUS.as_bytes(), 16).unwrap()
+    );
+    assert_eq!(private.e, BigUint::from(65537u32));
+    assert_eq!(
+        RsaPrivateKey::from_pem(OPENSSL_PRIVATE_PKCS8).as_ref(),
+        Ok(&private)
+    );
+    assert_eq!(
+        RsaPrivateKey::from_primes(private.p.clone(), private.q.clone(), private.e.clone())
+            .map(|key| key.public_key()),
+        Some(private.public_key())
+    );
+    assert_eq!(private.to_pem(KeyFormat::Pkcs1), OPENSSL_PRIVATE_PKCS1);
+    assert_eq!(private.to_pem(KeyFormat::Pkcs8), OPENSSL_PRIVATE_PKCS8);
+
+    let public = private.public_key();
+    assert_eq!(
+        RsaPublicKey::from_pem(OPENSSL_PUBLIC_PKCS1),
+        Ok(public.clone())
+    );
+    assert_eq!(
+        RsaPublicKey::from_pem(OPENSSL_PUBLIC_SPKI),
+        Ok(public.clone())
+    );
+    assert_eq!(public.to_pem(KeyFormat::Pkcs1), OPENSSL_PUBLIC_PKCS1);
+    assert_eq!(public.to_pem(KeyFormat::Pkcs8), OPENSSL_PUBLIC_SPKI);
+
+    // Os rótulos decidem o formato: uma chave pública não é lida como privada, nem o contrário
+    assert!(RsaPrivateKey::<BigUint>::from_pem(OPENSSL_PUBLIC_SPKI).is_err());
+    assert!(RsaPublicKey::<BigUint>::from_pem(OPENSSL_PRIVATE_PKCS8).is_err());
+}
+
+/// Converte bytes em texto hexadecimal
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Converte texto hexadecimal em bytes, com "-" para o vazio
+fn unhex(text: &str) -> Vec<u8> {
+    let text = text.trim_start_matches('-');
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap())
+        .collect()
+}